@@ -5,40 +5,93 @@ use std::mem;
 use tuig::{
     io::{
         fmt::{FormattedExt, Text},
-        text1, Action, Key, Screen,
+        text1, Action, Key, Screen, XY,
     },
     Replies,
 };
 
 use crate::{
+    clock::{self, GameTime},
     constants::{gameplay::MAX_USERNAME, graphics::HEADER_HEIGHT},
     event::Event,
+    settings,
     state::GameState,
 };
 
+use super::{draft::Draft, Mouse};
+
+/// The width of the timestamp before each message, including the space after
+const TIME_WIDTH: usize = 6;
+/// The width of the label before the options or whatever's being typed, e.g. `> ` or `say `
+const PROMPT_WIDTH: usize = TIME_WIDTH + MAX_USERNAME + 1;
+
+/// The width of the side pane listing DMs, including the vertical divider.
+fn list_pane_size(size: XY) -> usize {
+    // a split pane can be narrower than the list would like to be
+    (size.x() / 10).clamp(15, 30).min(size.x())
+}
+
+/// What a message actually says.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum Message {
-    Normal { text: String, from_player: bool },
+enum Content {
+    Normal {
+        text: String,
+        from_player: bool,
+    },
+    /// A file sent one way or the other; for files the player got, the path is where it ended up
+    File {
+        path: String,
+        from_player: bool,
+    },
     System(String),
 }
 
+impl Content {
+    /// The text a search looks through
+    fn text(&self) -> &str {
+        match self {
+            Content::Normal { text, .. } => text,
+            Content::File { path, .. } => path,
+            Content::System(text) => text,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Message {
+    content: Content,
+    /// When the message was sent, in-game
+    at: GameTime,
+}
+
 impl Message {
+    fn new(content: Content) -> Message {
+        Message {
+            content,
+            at: clock::now(),
+        }
+    }
+
     fn from_player(text: String) -> Message {
-        Message::Normal {
+        Message::new(Content::Normal {
             text,
             from_player: true,
-        }
+        })
     }
 
     fn from_npc(text: String) -> Message {
-        Message::Normal {
+        Message::new(Content::Normal {
             text,
             from_player: false,
-        }
+        })
+    }
+
+    fn file(path: String, from_player: bool) -> Message {
+        Message::new(Content::File { path, from_player })
     }
 
     fn system(text: &str) -> Message {
-        Message::System(text.into())
+        Message::new(Content::System(text.into()))
     }
 }
 
@@ -56,6 +109,18 @@ struct DM {
     sel: usize,
     /// Whether the DM is still open or whether they've closed it
     open: bool,
+    /// Whatever the player's typing, instead of picking an option
+    draft: Draft,
+    /// Whether they're typing something
+    typing: bool,
+}
+
+/// A search through every DM's history.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Search {
+    query: Draft,
+    /// The match currently being shown, as (DM index, message index)
+    hit: Option<(usize, usize)>,
 }
 
 /// The direct message tab.
@@ -67,6 +132,10 @@ pub struct ChatApp {
     dms: Vec<DM>,
     /// Which DM they're currently looking at
     current_dm: usize,
+    /// The search the player's doing, if they are
+    search: Option<Search>,
+    /// How many messages in the current DM the player's scrolled up past, to see older ones
+    scroll: usize,
 }
 
 impl ChatApp {
@@ -77,6 +146,140 @@ impl ChatApp {
     fn clear_current_unread(&mut self) {
         self.dms[self.current_dm].unread = 0;
     }
+
+    /// Find the DM with someone, starting a new one if there isn't one yet
+    fn dm_with(&mut self, target: &str) -> usize {
+        match self.dms.iter().position(|d| d.target == target) {
+            Some(i) => i,
+            None => {
+                self.dms.push(DM {
+                    msgs: vec![Message::system("Chat started")],
+                    target: target.into(),
+                    open: true,
+                    ..Default::default()
+                });
+                self.dms.len() - 1
+            }
+        }
+    }
+
+    /// Look at a different DM, starting from its latest messages
+    fn switch_dm(&mut self, dm: usize) {
+        self.current_dm = dm;
+        self.scroll = 0;
+        self.clear_current_unread();
+    }
+
+    /// Pick one of the current DM's options and send it
+    fn choose(&mut self, option: usize, replies: &mut Replies<Event>) {
+        let dm = &mut self.dms[self.current_dm];
+        let mut options = mem::replace(&mut dm.options, vec![]);
+        let selected = options.remove(option);
+        let ev = Event::player_chat(&dm.target, &selected);
+        dm.msgs.push(Message::from_player(selected));
+        dm.sel = 0;
+        self.scroll = 0;
+        replies.queue(ev);
+    }
+
+    /// Which option (if any) is drawn at this column of the options row, given how they're laid out in `render`
+    fn option_at(&self, x: usize) -> Option<usize> {
+        let mut start = PROMPT_WIDTH + 2;
+        for (i, opt) in self.dm().options.iter().enumerate() {
+            let end = start + opt.chars().count();
+            if (start..end).contains(&x) {
+                return Some(i);
+            }
+            start = end + 3;
+        }
+        None
+    }
+
+    /// The most recent non-system message in the current DM, formatted for pinning to notes
+    fn pinnable(&self) -> Option<String> {
+        let dm = self.dm();
+        dm.msgs.iter().rev().find_map(|m| match &m.content {
+            Content::Normal {
+                text,
+                from_player: true,
+            } => Some(format!("me to {}: {}", dm.target, text)),
+            Content::Normal {
+                text,
+                from_player: false,
+            } => Some(format!("{}: {}", dm.target, text)),
+            Content::File {
+                path,
+                from_player: true,
+            } => Some(format!("sent {}: {}", dm.target, path)),
+            Content::File {
+                path,
+                from_player: false,
+            } => Some(format!("{} sent: {}", dm.target, path)),
+            Content::System(_) => None,
+        })
+    }
+
+    /// Every message in every DM containing the query (ignoring case), as (DM index, message index), in order
+    fn matches(&self, query: &str) -> Vec<(usize, usize)> {
+        if query.is_empty() {
+            return vec![];
+        }
+        let query = query.to_lowercase();
+        let mut found = vec![];
+        for (d, dm) in self.dms.iter().enumerate() {
+            for (m, msg) in dm.msgs.iter().enumerate() {
+                if msg.content.text().to_lowercase().contains(&query) {
+                    found.push((d, m));
+                }
+            }
+        }
+        found
+    }
+
+    /// Jump to the next search match after the current one, wrapping around to the first, and switching DMs as needed.
+    ///
+    /// If there's no current match, the first one from the start of the current DM is picked.
+    fn next_match(&mut self) {
+        let search = match &self.search {
+            Some(s) => s,
+            None => return,
+        };
+        let hits = self.matches(&search.query.text);
+        let next = match search.hit {
+            Some(hit) => hits.iter().find(|&&h| h > hit),
+            None => hits.iter().find(|&&h| h >= (self.current_dm, 0)),
+        };
+        let next = next.or_else(|| hits.first()).copied();
+        if let Some((d, _)) = next {
+            if d != self.current_dm {
+                self.switch_dm(d);
+            }
+        }
+        if let Some(search) = &mut self.search {
+            search.hit = next;
+        }
+    }
+
+    /// Handle input while searching
+    fn search_input(&mut self, key: Key) -> bool {
+        let search = match &mut self.search {
+            Some(s) => s,
+            None => return false,
+        };
+        match key {
+            Key::Enter => self.next_match(),
+            Key::Escape => self.search = None,
+            key => {
+                if !search.query.key(key) {
+                    return false;
+                }
+                // search as the player types
+                search.hit = None;
+                self.next_match();
+            }
+        }
+        true
+    }
 }
 
 impl super::App for ChatApp {
@@ -94,28 +297,50 @@ impl super::App for ChatApp {
             Action::KeyPress { key, .. } => key,
             _ => return false,
         };
+        if self.search.is_some() {
+            return self.search_input(key);
+        }
+        let typing = !self.dm().draft.is_empty();
         match key {
-            Key::Left if self.dm().sel > 0 => self.dms[self.current_dm].sel -= 1,
-            Key::Right if self.dm().sel < self.dm().options.len() - 1 => {
-                self.dms[self.current_dm].sel += 1
-            }
-            Key::Enter if !self.dm().options.is_empty() => {
+            Key::Enter if typing => {
                 let dm = &mut self.dms[self.current_dm];
-                let mut options = mem::replace(&mut dm.options, vec![]);
-                let selected = options.remove(dm.sel);
-                let ev = Event::player_chat(&dm.target, &selected);
-                dm.msgs.push(Message::from_player(selected));
-                dm.sel = 0;
-                replies.queue(ev);
+                let text = dm.draft.take();
+                if !text.trim().is_empty() {
+                    replies.queue(Event::player_chat(&dm.target, text.trim()));
+                    dm.msgs.push(Message::from_player(text.trim().into()));
+                    self.scroll = 0;
+                }
             }
-            Key::Up if self.current_dm > 0 => {
-                self.current_dm -= 1;
-                self.clear_current_unread();
+            Key::Escape if typing => {
+                self.dms[self.current_dm].draft.take();
             }
+            Key::Up if self.current_dm > 0 => self.switch_dm(self.current_dm - 1),
             Key::Down if self.current_dm < self.dms.len() - 1 => {
-                self.current_dm += 1;
-                self.clear_current_unread();
+                self.switch_dm(self.current_dm + 1)
+            }
+            // once the player's started typing, keys edit what they're typing instead of picking options
+            key if typing => {
+                if !self.dms[self.current_dm].draft.key(key) {
+                    return false;
+                }
+            }
+            // and anything printable starts typing, even if it's also bound to something
+            Key::Char(_) if self.dm().open => {
+                self.dms[self.current_dm].draft.key(key);
             }
+            key if key == settings::current().pin => {
+                // pinning doesn't change anything on this tab
+                if let Some(note) = self.pinnable() {
+                    replies.queue(Event::PinNote(note));
+                }
+                return false;
+            }
+            key if key == settings::current().search => self.search = Some(Search::default()),
+            Key::Left if self.dm().sel > 0 => self.dms[self.current_dm].sel -= 1,
+            Key::Right if self.dm().sel + 1 < self.dm().options.len() => {
+                self.dms[self.current_dm].sel += 1
+            }
+            Key::Enter if !self.dm().options.is_empty() => self.choose(self.dm().sel, replies),
             _ => return false,
         };
         true
@@ -127,27 +352,66 @@ impl super::App for ChatApp {
                 from,
                 text,
                 options,
-            } => (from, text, options),
+            } => (from, Message::from_npc(text.clone()), Some(options)),
+            Event::NPCSentFile { from, path, .. } => {
+                (from, Message::file(path.clone(), false), None)
+            }
+            Event::PlayerSentFile { to, file } => {
+                (to, Message::file(file.name.clone(), true), None)
+            }
+            // team reports show up in the member's DM without touching whatever replies are on offer
+            Event::TeamReport { from, text } => (from, Message::from_npc(text.clone()), None),
+            Event::TeamChanged { name, joined } => {
+                let text = if *joined {
+                    format!("{} joined your team", name)
+                } else {
+                    format!("{} left your team", name)
+                };
+                (name, Message::system(&text), None)
+            }
+            Event::NPCTyping { from } => {
+                let i = self.dm_with(from);
+                self.dms[i].typing = true;
+                return true;
+            }
+            Event::NPCClosedChat { from } => {
+                let dm = match self.dms.iter_mut().find(|d| &d.target == from) {
+                    Some(dm) => dm,
+                    None => return false,
+                };
+                dm.msgs
+                    .push(Message::system(&format!("{} closed the chat", from)));
+                dm.options.clear();
+                dm.sel = 0;
+                dm.draft.take();
+                dm.open = false;
+                dm.typing = false;
+                return true;
+            }
             _ => return false,
         };
-        match self.dms.iter_mut().find(|d| &d.target == sender) {
-            Some(dm) => {
-                dm.msgs.push(Message::from_npc(message.clone()));
-                dm.unread += 1;
-                dm.options = options.clone();
-                dm.open = true;
+        // the player doesn't need to be notified about what they did themselves
+        let from_player = matches!(
+            message.content,
+            Content::File {
+                from_player: true,
+                ..
             }
-            None => self.dms.push(DM {
-                msgs: vec![
-                    Message::system("Chat started"),
-                    Message::from_npc(message.clone()),
-                ],
-                options: options.clone(),
-                sel: 0,
-                target: sender.into(),
-                unread: 1,
-                open: true,
-            }),
+        );
+        let unread = if from_player { 0 } else { 1 };
+        let i = self.dm_with(sender);
+        let dm = &mut self.dms[i];
+        dm.msgs.push(message);
+        dm.unread += unread;
+        if let Some(options) = options {
+            // only chat messages come with options, and a chat message means they're done typing it
+            dm.options = options.clone();
+            dm.typing = false;
+        }
+        dm.open = true;
+        // keep whatever the player's scrolled back to in place
+        if i == self.current_dm && self.scroll > 0 {
+            self.scroll += 1;
         }
         if focused {
             self.clear_current_unread();
@@ -163,21 +427,98 @@ impl super::App for ChatApp {
             .sum()
     }
 
+    fn mouse(&mut self, m: Mouse, size: XY, replies: &mut Replies<Event>) -> bool {
+        if self.dms.is_empty() {
+            return false;
+        }
+        let pos = match m {
+            Mouse::ScrollUp if self.scroll + 1 < self.dm().msgs.len() => {
+                self.scroll += 1;
+                return true;
+            }
+            Mouse::ScrollDown if self.scroll > 0 => {
+                self.scroll -= 1;
+                return true;
+            }
+            Mouse::Click(pos) => pos,
+            _ => return false,
+        };
+        let list_x = size.x() - list_pane_size(size);
+        if pos.y() < HEADER_HEIGHT {
+            false
+        } else if pos.x() > list_x {
+            // the list scrolls to the bottom, just like the chatlog, if there's more DMs than fit
+            let hidden = self.dms.len().saturating_sub(size.y() - HEADER_HEIGHT);
+            let clicked = pos.y() - HEADER_HEIGHT + hidden;
+            if clicked < self.dms.len() && clicked != self.current_dm {
+                self.switch_dm(clicked);
+                true
+            } else {
+                false
+            }
+        } else if pos.y() + 2 == size.y() && pos.x() < list_x && self.dm().draft.is_empty() {
+            match self.option_at(pos.x()) {
+                Some(i) => {
+                    self.choose(i, replies);
+                    true
+                }
+                None => false,
+            }
+        } else {
+            false
+        }
+    }
+
+    fn paste(&mut self, text: &str) -> bool {
+        if self.dms.is_empty() {
+            return false;
+        }
+        if let Some(search) = &mut self.search {
+            for c in text.chars() {
+                search.query.key(Key::Char(c));
+            }
+            search.hit = None;
+            self.next_match();
+            return true;
+        }
+        let dm = &mut self.dms[self.current_dm];
+        if !dm.open {
+            return false;
+        }
+        for c in text.chars() {
+            dm.draft.key(Key::Char(c));
+        }
+        true
+    }
+
     fn render(&self, state: &GameState, screen: &mut Screen) {
         let size = screen.size();
-        // The width of the side pane, including the vertical divider.
-        let list_pane_size = (size.x() / 10).clamp(15, 30);
+        let list_pane_size = list_pane_size(size);
 
         if !self.dms.is_empty() {
             let dm = self.dm();
-            // per message: 1 for name, 1 for colon, 1 for message contents, 1 for newline
-            // plus 1 + 2 * current_dm.options.len() for the options line including spaces between
-            let chunks =
-                dm.msgs.iter().map(|_| 1 + 1 + 1 + 1).sum::<usize>() + dm.options.len() * 2;
-            let mut output = Vec::with_capacity(chunks);
-            for msg in &dm.msgs {
-                match msg {
-                    Message::Normal { text, from_player } => {
+            // when a search match is in this DM, only show up to it, so it's at the bottom of the screen
+            let hit = match &self.search {
+                Some(Search {
+                    hit: Some((d, m)), ..
+                }) if *d == self.current_dm => Some(*m),
+                _ => None,
+            };
+            let shown = match hit {
+                Some(m) => &dm.msgs[..=m],
+                None => &dm.msgs[..dm.msgs.len() - self.scroll],
+            };
+            // per message: 1 for time, 1 for name, 1 for colon, 1 for message contents, 1 for newline
+            let mut output = Vec::with_capacity(shown.len() * 5);
+            let mut day = None;
+            for (i, msg) in shown.iter().enumerate() {
+                if day != Some(msg.at.day()) {
+                    day = Some(msg.at.day());
+                    output.push(text1!(bright_black "-- day {} --\n"(msg.at.day())));
+                }
+                output.push(text1!(bright_black "{:02}:{:02} "(msg.at.hour(), msg.at.minute())));
+                let body = match &msg.content {
+                    Content::Normal { text, from_player } => {
                         let name = if *from_player {
                             text1![cyan "{0:>1$}"(state.player_name, MAX_USERNAME)]
                         } else {
@@ -185,33 +526,85 @@ impl super::App for ChatApp {
                         };
                         output.push(name);
                         output.push(Text::plain(": "));
-                        output.push(Text::plain(text));
-                        output.push(Text::plain("\n"));
+                        Text::plain(text)
                     }
-                    Message::System(text) => {
-                        output.push(Text::of(text.clone()).bold());
-                        output.push(Text::plain("\n"));
+                    Content::File { path, from_player } => {
+                        let name = if *from_player {
+                            &state.player_name
+                        } else {
+                            &dm.target
+                        };
+                        output.push(text1![bright_black "{0:>1$}"(name, MAX_USERNAME)]);
+                        output.push(Text::plain(": "));
+                        text1![cyan "[file] {}"(path)]
                     }
+                    Content::System(text) => Text::of(text.clone()).bold(),
+                };
+                if Some(i) == hit {
+                    output.push(body.on_yellow().black());
+                } else {
+                    output.push(body);
                 }
+                output.push(Text::plain("\n"));
+            }
+            if hit.is_none() && self.scroll > 0 {
+                output.push(text1!(bright_black "-- {} newer messages below --"(self.scroll)));
+            } else if hit.is_none() && dm.typing {
+                output.push(text1!(bright_black "{} is typing..."(dm.target)));
             }
+            // the bottom two rows are for the options and typing, so they're always in the same spot to click on
+            let bottom = size.y().saturating_sub(2).max(HEADER_HEIGHT);
+            screen
+                .textbox(output)
+                .pos(0, HEADER_HEIGHT)
+                .height(bottom - HEADER_HEIGHT)
+                .indent(TIME_WIDTH + MAX_USERNAME + 2)
+                .first_indent(0)
+                .scroll_bottom(true)
+                .width(size.x() - list_pane_size);
+
+            // 1 for the prompt, then 2 per option: the option itself and the spacing before it
+            let mut options = Vec::with_capacity(1 + 2 * dm.options.len());
             for (i, opt) in dm.options.iter().enumerate() {
                 if i == 0 {
-                    output.push(text1!("{0:>1$}  "(">", MAX_USERNAME + 1)));
+                    options.push(text1!("{0:>1$}  "(">", PROMPT_WIDTH)));
                 } else {
-                    output.push(text1!("   "));
+                    options.push(text1!("   "));
                 }
                 if i == dm.sel {
-                    output.push(text1!(underline "{}"(opt)));
+                    options.push(text1!(underline "{}"(opt)));
                 } else {
-                    output.push(text1!("{}"(opt)));
+                    options.push(text1!("{}"(opt)));
                 }
             }
             screen
-                .textbox(output)
-                .pos(0, HEADER_HEIGHT)
-                .indent(MAX_USERNAME + 2)
-                .first_indent(0)
-                .scroll_bottom(true)
+                .textbox(options)
+                .pos(0, bottom)
+                .height(1)
+                .width(size.x() - list_pane_size);
+
+            let mut input = vec![];
+            if let Some(search) = &self.search {
+                let hits = self.matches(&search.query.text);
+                input.push(text1!(bright_yellow "{0:>1$}  "("search", PROMPT_WIDTH)));
+                search.query.render(&mut input);
+                match search.hit.and_then(|h| hits.iter().position(|&o| o == h)) {
+                    Some(i) => input.push(text1!(bright_black "  ({}/{})"(i + 1, hits.len()))),
+                    None if search.query.is_empty() => (),
+                    None => input.push(text1!(bright_black "  (no matches)")),
+                }
+            } else if !dm.draft.is_empty() {
+                input.push(text1!(cyan "{0:>1$}  "("say", PROMPT_WIDTH)));
+                dm.draft.render(&mut input);
+            } else if dm.open {
+                input.push(
+                    text1!(bright_black "{0:>1$}  (or just type; / searches)"("", PROMPT_WIDTH)),
+                );
+            }
+            screen
+                .textbox(input)
+                .pos(0, bottom + 1)
+                .height(1)
                 .width(size.x() - list_pane_size);
         }
 
@@ -220,21 +613,22 @@ impl super::App for ChatApp {
             .start(HEADER_HEIGHT);
         let mut names = Vec::with_capacity(2 * self.dms.len());
         for (i, dm) in self.dms.iter().enumerate() {
-            if !dm.open {
-                continue;
-            }
-            if dm.unread == 0 {
+            if dm.unread == 0 || !dm.open {
                 names.push(text1!("   "));
             } else if dm.unread > 9 {
                 names.push(text1!(" + ").red());
             } else {
                 names.push(text1!(red " {} "(dm.unread)));
             }
+            // closed chats stay in the list, so they can still be read back through, but they're greyed out
+            let mut name = text1!("{}\n"(dm.target));
+            if !dm.open {
+                name = name.bright_black();
+            }
             if i == self.current_dm {
-                names.push(text1!(underline "{}\n"(dm.target)))
-            } else {
-                names.push(text1!("{}\n"(dm.target)));
+                name = name.underline();
             }
+            names.push(name);
         }
         screen
             .textbox(names)
@@ -256,6 +650,8 @@ mod tests {
         ChatApp {
             dms: vec![],
             current_dm: 0,
+            search: None,
+            scroll: 0,
         }
     }
 
@@ -268,6 +664,8 @@ mod tests {
             options: vec![],
             sel: 0,
             open: true,
+            draft: Draft::default(),
+            typing: false,
         }
     }
 
@@ -457,4 +855,292 @@ mod tests {
         app.input(DOWN, &mut Replies::default());
         assert_eq!(app.notifs(), 3);
     }
+
+    #[test]
+    fn test_team_report_keeps_options() {
+        let mut app = app_dm(&["hi", "no"], 0);
+        app.on_event(
+            &Event::TeamReport {
+                from: "targette".into(),
+                text: "on it".into(),
+            },
+            false,
+        );
+        app.on_event(
+            &Event::TeamChanged {
+                name: "targette".into(),
+                joined: false,
+            },
+            false,
+        );
+        assert_eq!(app.notifs(), 2);
+        assert_eq!(
+            app.dm().msgs[1].content,
+            Content::System("targette left your team".into())
+        );
+        assert_input!(app.input(ENTER) taints, == &[Event::player_chat("targette", "hi")]);
+    }
+
+    #[test]
+    fn test_pin_latest_message() {
+        let mut app = app_dm(&[], 0);
+        app.on_event(
+            &Event::npc_chat("targette", "the password is hunter2", &[]),
+            true,
+        );
+        app.on_event(
+            &Event::TeamChanged {
+                name: "targette".into(),
+                joined: true,
+            },
+            true,
+        );
+        let pin = Action::KeyPress { key: Key::Insert };
+        assert_input!(
+            app.input(pin) clean,
+            == &[Event::PinNote("targette: the password is hunter2".into())]
+        );
+    }
+
+    fn type_str(app: &mut ChatApp, text: &str) {
+        for c in text.chars() {
+            let key = Action::KeyPress { key: Key::Char(c) };
+            assert_input!(app.input(key) taints, .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_type_message() {
+        let mut app = app_dm(&["hello"], 0);
+        type_str(&mut app, " hunter2 ");
+        assert_input!(app.input(ENTER) taints, == &[Event::player_chat("targette", "hunter2")]);
+        assert_eq!(app.dm().msgs.len(), 1);
+        assert_eq!(
+            app.dm().msgs[0].content,
+            Content::Normal {
+                text: "hunter2".into(),
+                from_player: true
+            }
+        );
+        // the options are still there, in case it didn't work
+        assert_eq!(app.dm().options, vec!["hello"]);
+        assert!(app.dm().draft.is_empty());
+    }
+
+    #[test]
+    fn test_typing_captures_arrows() {
+        let mut app = app_dm(&["hello", "goodbye"], 0);
+        type_str(&mut app, "hi");
+        assert_input!(app.input(RIGHT) clean, .is_empty());
+        assert_input!(app.input(LEFT) taints, .is_empty());
+        assert_eq!(app.dm().sel, 0);
+    }
+
+    #[test]
+    fn test_cancel_typing() {
+        let mut app = app_dm(&["hello"], 0);
+        type_str(&mut app, "nevermind");
+        let esc = Action::KeyPress { key: Key::Escape };
+        assert_input!(app.input(esc) taints, .is_empty());
+        assert!(app.dm().draft.is_empty());
+        assert_input!(app.input(ENTER) taints, == &[Event::player_chat("targette", "hello")]);
+    }
+
+    #[test]
+    fn test_files_in_chat() {
+        let mut app = app_dm(&["hello"], 0);
+        let file = crate::event::Attachment {
+            name: "loot.txt".into(),
+            contents: "shiny".into(),
+        };
+        app.on_event(
+            &Event::PlayerSentFile {
+                to: "targette".into(),
+                file: file.clone(),
+            },
+            false,
+        );
+        assert_eq!(app.notifs(), 0);
+        app.on_event(
+            &Event::NPCSentFile {
+                from: "targette".into(),
+                file,
+                path: "/inbox/targette/loot.txt".into(),
+            },
+            false,
+        );
+        assert_eq!(app.notifs(), 1);
+        assert_eq!(
+            app.pinnable(),
+            Some("targette sent: /inbox/targette/loot.txt".into())
+        );
+        // neither touches the options
+        assert_eq!(app.dm().options, vec!["hello"]);
+    }
+
+    #[test]
+    fn test_messages_timestamped() {
+        let mut app = app();
+        let before = clock::now();
+        app.on_event(&Event::npc_chat("targette", "hi", &[]), false);
+        let after = clock::now();
+        for msg in &app.dm().msgs {
+            assert!(before <= msg.at && msg.at <= after);
+        }
+    }
+
+    #[test]
+    fn test_npc_closes_chat() {
+        let mut app = app_dm(&["hello"], 0);
+        type_str(&mut app, "wait");
+        let closed = Event::NPCClosedChat {
+            from: "targette".into(),
+        };
+        assert!(app.on_event(&closed, false));
+        assert!(!app.dm().open);
+        assert!(app.dm().options.is_empty());
+        assert!(app.dm().draft.is_empty());
+        assert_eq!(
+            app.dm().msgs.last().unwrap().content,
+            Content::System("targette closed the chat".into())
+        );
+        // can't type into a closed chat
+        let w = Action::KeyPress {
+            key: Key::Char('w'),
+        };
+        assert_input!(app.input(w) clean, .is_empty());
+        // but it opens right back up if they say something
+        app.on_event(&Event::npc_chat("targette", "actually", &[]), false);
+        assert!(app.dm().open);
+    }
+
+    #[test]
+    fn test_close_unknown_chat() {
+        let mut app = app();
+        let closed = Event::NPCClosedChat {
+            from: "nobody".into(),
+        };
+        assert!(!app.on_event(&closed, false));
+        assert!(app.dms.is_empty());
+    }
+
+    #[test]
+    fn test_search_across_dms() {
+        let mut app = app();
+        app.on_event(&Event::npc_chat("alice", "the code is 1234", &[]), false);
+        app.on_event(&Event::npc_chat("bob", "nothing here", &[]), false);
+        app.on_event(&Event::npc_chat("bob", "CODE red", &[]), false);
+        app.current_dm = 1;
+        let search = Action::KeyPress {
+            key: settings::current().search,
+        };
+        assert_input!(app.input(search) taints, .is_empty());
+        type_str(&mut app, "code");
+        // starts from the current DM
+        assert_eq!(app.current_dm, 1);
+        assert_eq!(app.search.as_ref().unwrap().hit, Some((1, 2)));
+        // and wraps around to the others
+        assert_input!(app.input(ENTER) taints, .is_empty());
+        assert_eq!(app.current_dm, 0);
+        assert_eq!(app.search.as_ref().unwrap().hit, Some((0, 1)));
+        assert_input!(app.input(ENTER) taints, .is_empty());
+        assert_eq!(app.search.as_ref().unwrap().hit, Some((1, 2)));
+        let esc = Action::KeyPress { key: Key::Escape };
+        assert_input!(app.input(esc) taints, .is_empty());
+        assert_eq!(app.search, None);
+        assert_eq!(app.current_dm, 1);
+    }
+
+    #[test]
+    fn test_type_slash_first() {
+        let mut app = app_dm(&[], 0);
+        type_str(&mut app, "/shrug");
+        assert_eq!(app.search, None);
+        assert_input!(app.input(ENTER) taints, == &[Event::player_chat("targette", "/shrug")]);
+    }
+
+    #[test]
+    fn test_search_no_matches() {
+        let mut app = app();
+        app.on_event(&Event::npc_chat("alice", "hello", &[]), false);
+        app.search = Some(Search::default());
+        type_str(&mut app, "xyz");
+        assert_eq!(app.search.as_ref().unwrap().hit, None);
+        assert_eq!(app.current_dm, 0);
+    }
+
+    const SIZE: XY = XY(100, 30);
+
+    #[test]
+    fn test_click_dm() {
+        let mut app = app();
+        app.on_event(&Event::npc_chat("alice", "hi", &[]), false);
+        app.on_event(&Event::npc_chat("bob", "hey", &[]), false);
+        let mut replies = Replies::default();
+        // the list starts just right of the divider, below the header
+        assert!(app.mouse(Mouse::Click(XY(90, HEADER_HEIGHT + 1)), SIZE, &mut replies));
+        assert_eq!(app.current_dm, 1);
+        assert_eq!(app.dm().unread, 0);
+        assert!(!app.mouse(Mouse::Click(XY(90, HEADER_HEIGHT + 5)), SIZE, &mut replies));
+        assert_eq!(app.current_dm, 1);
+    }
+
+    #[test]
+    fn test_click_option() {
+        let mut app = app_dm(&["yes", "no"], 0);
+        let mut replies = Replies::default();
+        let row = SIZE.y() - 2;
+        // between the two options
+        let gap = PROMPT_WIDTH + 2 + 4;
+        assert!(!app.mouse(Mouse::Click(XY(gap, row)), SIZE, &mut replies));
+        assert!(app.mouse(Mouse::Click(XY(gap + 2, row)), SIZE, &mut replies));
+        assert_eq!(replies._messages(), &[Event::player_chat("targette", "no")]);
+        assert!(app.dm().options.is_empty());
+    }
+
+    #[test]
+    fn test_scroll_wheel() {
+        let mut app = app();
+        for text in ["one", "two"] {
+            app.on_event(&Event::npc_chat("alice", text, &[]), true);
+        }
+        let mut replies = Replies::default();
+        assert!(!app.mouse(Mouse::ScrollDown, SIZE, &mut replies));
+        assert!(app.mouse(Mouse::ScrollUp, SIZE, &mut replies));
+        assert!(app.mouse(Mouse::ScrollUp, SIZE, &mut replies));
+        // the first message always stays on screen
+        assert!(!app.mouse(Mouse::ScrollUp, SIZE, &mut replies));
+        assert_eq!(app.scroll, 2);
+        // new messages don't move the view
+        app.on_event(&Event::npc_chat("alice", "three", &[]), true);
+        assert_eq!(app.scroll, 3);
+        // but typing something brings it back down
+        type_str(&mut app, "ok");
+        assert_input!(app.input(ENTER) taints, .len() == 1);
+        assert_eq!(app.scroll, 0);
+    }
+
+    #[test]
+    fn test_paste() {
+        let mut app = app_dm(&[], 0);
+        assert!(app.paste("hunter2"));
+        assert_eq!(app.dm().draft.text, "hunter2");
+        app.dms[0].open = false;
+        assert!(!app.paste("more"));
+    }
+
+    #[test]
+    fn test_typing_indicator() {
+        let mut app = app();
+        let typing = Event::NPCTyping {
+            from: "alice".into(),
+        };
+        assert!(app.on_event(&typing, false));
+        // the DM starts as soon as they start typing
+        assert!(app.dm().typing);
+        assert_eq!(app.notifs(), 0);
+        app.on_event(&Event::npc_chat("alice", "hi", &[]), false);
+        assert!(!app.dm().typing);
+        assert_eq!(app.notifs(), 1);
+    }
 }
@@ -3,23 +3,28 @@ use std::{collections::VecDeque, sync::Arc};
 use tuig::{
     io::{
         fmt::Text,
-        text,
+        text, text1,
         ui::helpers::{TextInput, TextInputRequest},
-        Action, Screen,
+        Action, Key, Screen, XY,
     },
     Replies,
 };
 
 use crate::{
-    event::Event,
-    machine::Machine,
+    constants::gameplay::{ACCESS_LOG, PLAYER_ADDR, PLAYER_HOST, PLAYER_USER},
+    event::{Event, Pid},
+    machine::{Entry, File, Machine},
+    settings,
     state::GameState,
     tools::{autocomplete, Tool},
+    world::World,
 };
 
-use super::App;
+use super::{App, Mouse};
 
 const MAX_SCROLL_LINES: usize = 1000;
+/// How many lines of scrollback one notch of the mouse wheel scrolls past
+const WHEEL_LINES: usize = 3;
 
 /// The high-level state of the CLI, for passing to commands.
 ///
@@ -28,8 +33,82 @@ const MAX_SCROLL_LINES: usize = 1000;
 pub struct CliState {
     /// The machine currently logged into
     pub machine: Arc<Machine>,
+    /// That machine's hostname
+    pub host: String,
+    /// Who the session's logged in as there
+    pub user: String,
     /// The current working directory of the CLI
     pub cwd: String,
+    /// The world the machine is in, for tools that reach beyond it
+    pub world: Arc<World>,
+    /// Which terminal session the command was run from and which job it is there, so its output can find its way back
+    pub pid: Pid,
+}
+
+impl CliState {
+    /// Turn a path relative to the working directory into an absolute one
+    pub fn path(&self, path: &str) -> String {
+        if path.starts_with('/') {
+            path.into()
+        } else {
+            format!("{}{}", self.cwd, path)
+        }
+    }
+
+    /// Note down something the session's done in the machine's access log, if it's someone else's machine
+    pub fn log(&self, what: &str) {
+        if self.host != PLAYER_HOST {
+            let line = format!("{} from {}: {}", self.user, PLAYER_ADDR, what);
+            self.machine.log(ACCESS_LOG, &line);
+        }
+    }
+
+    /// Make sure the session's user can get at an absolute path, logging that they're trying to
+    fn access(&self, verb: &str, path: &str) -> Result<(), String> {
+        // logged before it's done, so anything that edits the access log can take its own entry out too
+        self.log(&format!("{} {}", verb, path));
+        if self.machine.can_access(&self.user, path) {
+            Ok(())
+        } else {
+            Err(format!("{}: permission denied", path))
+        }
+    }
+
+    /// Read a file as the session's user. See [`Machine::read`].
+    pub fn read(&self, path: &str) -> Result<File, String> {
+        self.access("read", path)?;
+        self.machine.read(path)
+    }
+
+    /// List a directory as the session's user. See [`Machine::readdir`].
+    pub fn readdir(&self, path: &str) -> Result<impl Iterator<Item = (String, Entry)>, String> {
+        self.access("list", path)?;
+        self.machine.readdir(path)
+    }
+
+    /// Write a file as the session's user. See [`Machine::write`].
+    pub fn write(&self, path: &str, contents: String) -> Result<(), String> {
+        self.access("write", path)?;
+        self.machine.write(path, contents)
+    }
+
+    /// Check the session's user can go into a directory, e.g. to `cd` into it. See [`Machine::readdir`].
+    pub fn enter(&self, path: &str) -> Result<(), String> {
+        self.access("enter", path)?;
+        self.machine.readdir(path).map(|_| ())
+    }
+
+    /// Make a directory as the session's user. See [`Machine::mkdir`].
+    pub fn mkdir(&self, path: &str, make_parents: bool) -> Result<(), String> {
+        self.access("mkdir", path)?;
+        self.machine.mkdir(path, make_parents)
+    }
+
+    /// Delete a file or directory as the session's user. See [`Machine::remove`].
+    pub fn remove(&self, path: &str) -> Result<(), String> {
+        self.access("delete", path)?;
+        self.machine.remove(path)
+    }
 }
 
 pub struct CliApp {
@@ -43,7 +122,11 @@ pub struct CliApp {
     help: String,
     /// Lines of output that haven't been read yet
     unread: usize,
+    /// How many lines of scrollback the player's scrolled up past, to see older output
+    offset: usize,
 
+    /// The name of the session's tab
+    name: &'static str,
     /// The current state of the CLI
     state: CliState,
 }
@@ -57,15 +140,43 @@ impl Default for CliApp {
             input: TextInput::new("> ", 100),
             help: Default::default(),
             unread: Default::default(),
+            offset: 0,
+            name: "terminal",
             state: CliState {
                 machine: Default::default(),
+                host: PLAYER_HOST.into(),
+                user: PLAYER_USER.into(),
                 cwd: "/".into(),
+                world: Default::default(),
+                pid: Pid::default(),
             },
         }
     }
 }
 
 impl CliApp {
+    /// Create a CLI logged into the player's machine in the given world, starting at its root directory.
+    pub fn new(world: Arc<World>) -> Self {
+        Self {
+            state: CliState {
+                machine: world.player(),
+                host: PLAYER_HOST.into(),
+                user: PLAYER_USER.into(),
+                cwd: "/".into(),
+                world,
+                pid: Pid::default(),
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Make this a different terminal session than the main one, with its own ID and tab name.
+    pub fn session(mut self, id: usize, name: &'static str) -> Self {
+        self.state.pid.session = id;
+        self.name = name;
+        self
+    }
+
     /// Add a line to the scrollback, potentially popping off an old line too
     fn add_scroll(&mut self, line: Vec<Text>) {
         if self.scroll.len() == MAX_SCROLL_LINES {
@@ -73,6 +184,10 @@ impl CliApp {
         }
         self.scroll.push_back(line.clone());
         self.unread += 1;
+        // keep whatever the player's scrolled back to in place
+        if self.offset > 0 {
+            self.offset = (self.offset + 1).min(self.scroll.len() - 1);
+        }
     }
 
     /// Actually run a command.
@@ -84,11 +199,18 @@ impl CliApp {
         if trimmed.is_empty() {
             return;
         }
+        // every command gets a new job number, so anything it leaves running can't be mistaken for the next one
+        self.state.pid.job += 1;
+        events.queue(Event::CommandEntered {
+            pid: self.state.pid,
+            line: trimmed.into(),
+        });
         let (cmd, rest) = match trimmed.split_once(' ') {
             Some(p) => p,
             None => (trimmed, ""),
         };
         if let Some(tool) = self.state.machine.tools.get(cmd).map(|r| r.value().clone()) {
+            self.state.log(&format!("ran {}", trimmed));
             events.spawn_boxed(tool.run(rest.trim(), &self.state));
             self.prompt = false;
         } else {
@@ -98,6 +220,23 @@ impl CliApp {
         }
     }
 
+    /// Everything that's been output since the last command was entered, joined up for pinning to notes
+    fn last_output(&self) -> String {
+        let is_cmd = |line: &Vec<Text>| line.first().map(|t| t.text == "> ").unwrap_or(false);
+        let start = self
+            .scroll
+            .iter()
+            .rposition(is_cmd)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let lines: Vec<String> = self
+            .scroll
+            .range(start..)
+            .map(|line| line.iter().map(|t| t.text.as_str()).collect::<String>())
+            .collect();
+        lines.concat().trim().to_owned()
+    }
+
     /// Tries to run autocomplete for the given line, i.e. autocompleting a tool name or letting the tool autocomplete
     fn autocomplete(&self, line: &str) -> String {
         if let Some((cmd, rest)) = line.split_once(char::is_whitespace) {
@@ -122,12 +261,24 @@ impl CliApp {
 impl App for CliApp {
     #[cfg_attr(coverage, no_coverage)]
     fn name(&self) -> &'static str {
-        "terminal"
+        self.name
     }
 
     fn input(&mut self, a: Action, replies: &mut Replies<Event>) -> bool {
         self.unread = 0;
+        let pin = settings::current().pin;
+        if matches!(a, Action::KeyPress { key } if key == pin) {
+            let output = self.last_output();
+            if !output.is_empty() {
+                replies.queue(Event::PinNote(output));
+            }
+            return false;
+        }
         if self.prompt {
+            if matches!(a, Action::KeyPress { .. }) {
+                // typing jumps back down to the prompt
+                self.offset = 0;
+            }
             let tir = self.input.action(a);
             let tainted = tir.is_tainting();
             match tir {
@@ -150,26 +301,51 @@ impl App for CliApp {
         if focused {
             self.unread = 0;
         }
+        let ours = self.state.pid;
         match ev {
-            Event::CommandOutput(line) => {
+            Event::CommandOutput { pid, line } if *pid == ours => {
                 self.add_scroll(line.clone());
                 true
             }
-            Event::CommandDone => {
+            Event::CommandOutput { pid, line } if pid.session == ours.session => {
+                // something still going in the background, so label which command it's from
+                let mut line = line.clone();
+                line.insert(0, text1!(bright_black "[{}] "(pid.job)));
+                self.add_scroll(line);
+                true
+            }
+            Event::CommandDone(pid) if *pid == ours => {
                 self.prompt = true;
                 true
             }
             Event::InstallTool(tool) => {
-                let tool = tool.take().expect("Tool taken by something other than CLI");
-                self.state
-                    .machine
-                    .tools
-                    .insert(tool.name().into(), tool.into());
+                // every session sees this, but only the first one needs to install it, since tools go on the
+                // player's machine no matter which one the session's on
+                if let (Some(tool), Some(player)) =
+                    (tool.take(), self.state.world.host(PLAYER_HOST))
+                {
+                    player.tools.insert(tool.name().into(), tool.into());
+                }
                 false
             }
-            Event::ChangeDir(new_dir) => {
+            Event::ChangeDir { pid, dir } if *pid == ours => {
                 // we blindly trust that whoever sent that event knew what they were doing
-                self.state.cwd = new_dir.to_owned();
+                self.state.cwd = dir.to_owned();
+                false
+            }
+            Event::Login { pid, host, user } if *pid == ours => {
+                if let Some(machine) = self.state.world.host(host) {
+                    // start off at home, if there is one
+                    let home = format!("/home/{}/", user);
+                    self.state.cwd = if machine.readdir(&home).is_ok() {
+                        home
+                    } else {
+                        "/".into()
+                    };
+                    self.state.machine = machine;
+                    self.state.host = host.clone();
+                    self.state.user = user.clone();
+                }
                 false
             }
             _ => false,
@@ -180,6 +356,29 @@ impl App for CliApp {
         self.unread
     }
 
+    fn mouse(&mut self, m: Mouse, _size: XY, _events: &mut Replies<Event>) -> bool {
+        let old = self.offset;
+        match m {
+            Mouse::ScrollUp => {
+                self.offset = (self.offset + WHEEL_LINES).min(self.scroll.len().saturating_sub(1))
+            }
+            Mouse::ScrollDown => self.offset = self.offset.saturating_sub(WHEEL_LINES),
+            Mouse::Click(_) => (),
+        }
+        self.offset != old
+    }
+
+    fn paste(&mut self, text: &str) -> bool {
+        if !self.prompt {
+            return false;
+        }
+        self.offset = 0;
+        for c in text.chars() {
+            self.input.action(Action::KeyPress { key: Key::Char(c) });
+        }
+        true
+    }
+
     fn render(&self, _state: &GameState, screen: &mut Screen) {
         let help_height = if !self.help.is_empty() {
             let tb_met = screen
@@ -193,17 +392,19 @@ impl App for CliApp {
             0
         };
 
-        let main_text = self
+        let shown = self.scroll.len() - self.offset;
+        let mut main_text = self
             .scroll
             .iter()
+            .take(shown)
             .flat_map(|v| v)
             .cloned()
-            .chain(if self.prompt {
-                self.input.render()
-            } else {
-                vec![]
-            })
             .collect::<Vec<_>>();
+        if self.offset > 0 {
+            main_text.extend(text!(bright_black "-- {} more lines below --"(self.offset)));
+        } else if self.prompt {
+            main_text.extend(self.input.render());
+        }
         let main_text_height = screen.size().y() - help_height;
         screen
             .textbox(main_text)
@@ -212,3 +413,210 @@ impl App for CliApp {
             .scroll_bottom(true);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tools::{Cat, Cd, Exit, Rm, Scrub, Touch, Upload};
+
+    fn world() -> Arc<World> {
+        let world = World::new(Default::default());
+        let player = world.player();
+        player.write("/apple", "apple\nalso apple".into()).unwrap();
+        player
+            .write("/banana", "banana\nalso banana".into())
+            .unwrap();
+        player.tools.insert("cat".into(), Arc::new(Cat));
+        Arc::new(world)
+    }
+
+    /// Run `cat` on something in a session, returning everything it'd send once it got going
+    fn cat(app: &mut CliApp, file: &str) -> Vec<Event> {
+        app.run_cmd(format!("cat {}", file), &mut Replies::default());
+        let mut replies = Replies::default();
+        Cat.run(file, &app.state).start(&mut replies);
+        replies._messages().to_vec()
+    }
+
+    /// Give every app every event, like the game would
+    fn deliver(apps: &mut [&mut CliApp], events: &[Event]) {
+        for ev in events {
+            for app in apps.iter_mut() {
+                app.on_event(ev, false);
+            }
+        }
+    }
+
+    fn scrollback(app: &CliApp) -> String {
+        app.scroll
+            .iter()
+            .flatten()
+            .map(|t| t.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn commands_get_new_pids() {
+        let mut app = CliApp::new(world()).session(3, "terminal 2");
+        let mut replies = Replies::default();
+        app.run_cmd("cat apple".into(), &mut replies);
+        app.run_cmd("cat banana".into(), &mut replies);
+        let pids: Vec<Pid> = replies
+            ._messages()
+            .iter()
+            .filter_map(|e| match e {
+                Event::CommandEntered { pid, .. } => Some(*pid),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pids.len(), 2);
+        assert!(pids.iter().all(|p| p.session == 3));
+        assert_ne!(pids[0], pids[1]);
+    }
+
+    #[test]
+    fn interleaved_output_goes_to_its_own_session() {
+        let world = world();
+        let mut a = CliApp::new(world.clone()).session(1, "terminal 2");
+        let mut b = CliApp::new(world).session(2, "terminal 3");
+        let from_a = cat(&mut a, "apple");
+        let from_b = cat(&mut b, "banana");
+        assert!(!a.prompt && !b.prompt);
+        // both tools going at once, so their output arrives all mixed up
+        let mut mixed = vec![];
+        for (ea, eb) in from_a.iter().zip(&from_b) {
+            mixed.push(ea.clone());
+            mixed.push(eb.clone());
+        }
+        deliver(&mut [&mut a, &mut b], &mixed);
+
+        let (a_text, b_text) = (scrollback(&a), scrollback(&b));
+        assert!(a_text.contains("apple\nalso apple\n"));
+        assert!(!a_text.contains("banana"));
+        assert!(b_text.contains("banana\nalso banana\n"));
+        assert!(!b_text.contains("apple"));
+        assert!(a.prompt && b.prompt);
+    }
+
+    #[test]
+    fn background_jobs_dont_take_over() {
+        let mut app = CliApp::new(world()).session(1, "terminal 2");
+        let first = cat(&mut app, "apple");
+        let (done, output): (Vec<_>, Vec<_>) = first
+            .into_iter()
+            .partition(|e| matches!(e, Event::CommandDone(_)));
+        // the first command says it's done, but keeps going in the background
+        deliver(&mut [&mut app], &done);
+        assert!(app.prompt);
+        let second = cat(&mut app, "banana");
+        deliver(&mut [&mut app], &output);
+        // so its output's labelled, and saying it's done again doesn't bring the prompt back early
+        deliver(&mut [&mut app], &done);
+        assert!(!app.prompt);
+        assert!(scrollback(&app).contains("[1] apple\n"));
+        deliver(&mut [&mut app], &second);
+        assert!(app.prompt);
+        assert!(scrollback(&app).ends_with("banana\nalso banana\n"));
+    }
+
+    #[test]
+    fn logging_in_moves_only_that_session() {
+        let world = world();
+        let remote = Machine::default();
+        remote.mkdir("/home/wes/", true).unwrap();
+        remote.tools.insert("exit".into(), Arc::new(Exit));
+        world.hosts.insert("fox-pc4".into(), Arc::new(remote));
+        let mut app = CliApp::new(world.clone()).session(1, "terminal 2");
+        let mut other = CliApp::new(world).session(2, "terminal 3");
+        let login = Event::Login {
+            pid: app.state.pid,
+            host: "fox-pc4".into(),
+            user: "wes".into(),
+        };
+        deliver(&mut [&mut app, &mut other], &[login]);
+        assert_eq!(
+            (app.state.host.as_str(), app.state.user.as_str()),
+            ("fox-pc4", "wes")
+        );
+        assert_eq!(app.state.cwd, "/home/wes/");
+        assert_eq!(other.state.host, PLAYER_HOST);
+
+        // and `exit` brings it back home
+        app.run_cmd("exit".into(), &mut Replies::default());
+        let mut replies = Replies::default();
+        Exit.run("", &app.state).start(&mut replies);
+        deliver(&mut [&mut app], replies._messages());
+        assert_eq!(app.state.host, PLAYER_HOST);
+        assert_eq!(app.state.user, PLAYER_USER);
+        assert_eq!(app.state.cwd, "/");
+        assert!(Arc::ptr_eq(&app.state.machine, &app.state.world.player()));
+        assert!(app.prompt);
+    }
+
+    #[test]
+    fn remote_sessions_leave_a_trail() {
+        let world = world();
+        let remote = Machine::default();
+        remote.write("/motd", "welcome".into()).unwrap();
+        remote.tools.insert("cat".into(), Arc::new(Cat));
+        remote.tools.insert("cd".into(), Arc::new(Cd));
+        remote.tools.insert("scrub".into(), Arc::new(Scrub));
+        remote.tools.insert("touch".into(), Arc::new(Touch));
+        remote.tools.insert("upload".into(), Arc::new(Upload));
+        world.player().tools.insert("rm".into(), Arc::new(Rm));
+        world.hosts.insert("fox-pc4".into(), Arc::new(remote));
+        let mut app = CliApp::new(world.clone()).session(1, "terminal 2");
+        let login = Event::Login {
+            pid: app.state.pid,
+            host: "fox-pc4".into(),
+            user: "wes".into(),
+        };
+        deliver(&mut [&mut app], &[login]);
+        let log = || -> Vec<String> {
+            let log = world.host("fox-pc4").unwrap().read(ACCESS_LOG).unwrap();
+            // each entry starts with when it happened, which isn't worth pinning down here
+            let from = format!("wes from {}: ", PLAYER_ADDR);
+            log.contents
+                .lines()
+                .map(|l| l[l.find(&from).expect("entry should say who") + from.len()..].to_owned())
+                .collect()
+        };
+
+        for cmd in [
+            "cat /motd",
+            "touch notes",
+            "cd /root",
+            "upload rm",
+            "rm notes",
+        ] {
+            app.run_cmd(cmd.into(), &mut Replies::default());
+        }
+        assert_eq!(
+            log(),
+            &[
+                "ran cat /motd",
+                "read /motd",
+                "ran touch notes",
+                "write /notes",
+                "ran cd /root",
+                "enter /root/",
+                "ran upload rm",
+                "install rm",
+                "ran rm notes",
+                "delete /notes",
+            ]
+        );
+
+        // everything scrub does is logged before it's done, so it can take out its own trail too
+        let scrub = format!("scrub {} {}", PLAYER_ADDR, ACCESS_LOG);
+        app.run_cmd(scrub, &mut Replies::default());
+        assert!(log().is_empty());
+        app.run_cmd("cat /motd".into(), &mut Replies::default());
+        assert_eq!(log(), &["ran cat /motd", "read /motd"]);
+
+        // and nothing's logged at home
+        let mut home = CliApp::new(world.clone()).session(2, "terminal 3");
+        home.run_cmd("cat apple".into(), &mut Replies::default());
+        assert!(world.player().read(ACCESS_LOG).is_err());
+    }
+}
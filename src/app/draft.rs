@@ -0,0 +1,77 @@
+//! A tiny line editor, for apps that need text input they can fully control.
+
+use tuig::io::{fmt::Text, text1, Key};
+
+/// A single line of text being edited, with a cursor.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Draft {
+    pub text: String,
+    /// The cursor's position, in chars
+    cursor: usize,
+}
+
+impl Draft {
+    pub fn of(text: &str) -> Self {
+        Self {
+            text: text.into(),
+            cursor: text.chars().count(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Empty the draft out, returning what was in it
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+
+    /// The byte index of the cursor
+    fn index(&self) -> usize {
+        self.text
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+
+    /// Handle an editing key, returning whether it did anything
+    pub fn key(&mut self, key: Key) -> bool {
+        let len = self.text.chars().count();
+        match key {
+            Key::Char(c) => {
+                let idx = self.index();
+                self.text.insert(idx, c);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let idx = self.index();
+                self.text.remove(idx);
+            }
+            Key::Delete if self.cursor < len => {
+                let idx = self.index();
+                self.text.remove(idx);
+            }
+            Key::Left if self.cursor > 0 => self.cursor -= 1,
+            Key::Right if self.cursor < len => self.cursor += 1,
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = len,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Add the text to some output, with the cursor underlined
+    pub fn render(&self, output: &mut Vec<Text>) {
+        let idx = self.index();
+        let (before, after) = self.text.split_at(idx);
+        let mut after = after.chars();
+        let at = after.next().unwrap_or(' ');
+        output.push(Text::plain(before));
+        output.push(text1!(underline "{}"(at)));
+        output.push(Text::of(after.collect()));
+    }
+}
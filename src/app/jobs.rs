@@ -0,0 +1,214 @@
+//! Displays the jobs the player has taken on.
+
+use tuig::{
+    io::{
+        fmt::{FormattedExt, Text},
+        text, text1, Action, Screen,
+    },
+    Replies,
+};
+
+use crate::{
+    clock::GameTime, constants::graphics::HEADER_HEIGHT, event::Event, jobs::Job, state::GameState,
+};
+
+/// Where a job is at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    Active,
+    Completed,
+    Failed,
+}
+
+/// A single job, as displayed in the list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Listing {
+    job: Job,
+    deadline: Option<GameTime>,
+    /// Which objectives are done; lines up with `job.objectives`
+    done: Vec<bool>,
+    status: Status,
+}
+
+/// The jobs tab.
+///
+/// Lists every job the player's accepted, what's left to do on active ones, and how the rest turned out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JobsApp {
+    /// Every job the player has accepted, oldest first
+    jobs: Vec<Listing>,
+    /// How many updates the player hasn't seen yet
+    unread: usize,
+}
+
+impl JobsApp {
+    fn listing(&mut self, id: &str) -> Option<&mut Listing> {
+        self.jobs.iter_mut().find(|l| l.job.id == id)
+    }
+
+    fn render_listing(listing: &Listing, output: &mut Vec<Text>) {
+        let title = text1!(bold "{}"(listing.job.title));
+        match listing.status {
+            Status::Active => output.push(title.bright_white()),
+            Status::Completed => output.push(title.green()),
+            Status::Failed => output.push(title.red()),
+        }
+        output.extend(text!(" from ", "{}"(listing.job.giver)));
+        match (listing.status, listing.deadline) {
+            (Status::Active, Some(deadline)) => output.extend(text!(", due by {}"(deadline))),
+            (Status::Active, None) => (),
+            (Status::Completed, _) => output.push(text1!(green " (done)")),
+            (Status::Failed, _) => output.push(text1!(red " (failed)")),
+        }
+        output.push(text1!("\n"));
+        if listing.status != Status::Active {
+            return;
+        }
+        for (objective, &done) in listing.job.objectives.iter().zip(&listing.done) {
+            if done {
+                output.extend(text!("  [", green "x", "] {}\n"(objective.describe())));
+            } else {
+                output.extend(text!("  [ ] {}\n"(objective.describe())));
+            }
+        }
+        for reward in &listing.job.rewards {
+            output.extend(text!("  reward: ", cyan "{}"(reward.describe()), "\n"));
+        }
+    }
+}
+
+impl super::App for JobsApp {
+    #[cfg_attr(coverage, no_coverage)]
+    fn name(&self) -> &'static str {
+        "jobs"
+    }
+
+    fn input(&mut self, _a: Action, _replies: &mut Replies<Event>) -> bool {
+        let tainted = self.unread != 0;
+        self.unread = 0;
+        tainted
+    }
+
+    fn on_event(&mut self, ev: &Event, focused: bool) -> bool {
+        match ev {
+            Event::JobAccepted { job, deadline } => {
+                if self.listing(&job.id).is_some() {
+                    return false;
+                }
+                self.jobs.push(Listing {
+                    job: job.clone(),
+                    deadline: *deadline,
+                    done: vec![false; job.objectives.len()],
+                    status: Status::Active,
+                });
+            }
+            Event::JobProgress { id, done } => match self.listing(id) {
+                Some(l) => l.done = done.clone(),
+                None => return false,
+            },
+            Event::JobCompleted(id) => match self.listing(id) {
+                Some(l) => l.status = Status::Completed,
+                None => return false,
+            },
+            Event::JobFailed(id) => match self.listing(id) {
+                Some(l) => l.status = Status::Failed,
+                None => return false,
+            },
+            _ => return false,
+        }
+        if focused {
+            self.unread = 0;
+        } else {
+            self.unread += 1;
+        }
+        true
+    }
+
+    fn notifs(&self) -> usize {
+        self.unread
+    }
+
+    fn render(&self, _state: &GameState, screen: &mut Screen) {
+        let mut output = vec![];
+        // active jobs first, then finished ones, newest first within each group
+        let active = self
+            .jobs
+            .iter()
+            .rev()
+            .filter(|l| l.status == Status::Active);
+        let finished = self
+            .jobs
+            .iter()
+            .rev()
+            .filter(|l| l.status != Status::Active);
+        for listing in active.chain(finished) {
+            Self::render_listing(listing, &mut output);
+        }
+        if output.is_empty() {
+            output.push(text1!("No jobs yet. Ask around in chat."));
+        }
+        screen
+            .textbox(output)
+            .pos(0, HEADER_HEIGHT)
+            .indent(4)
+            .first_indent(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{app::App, jobs::Objective};
+
+    use super::*;
+
+    fn job() -> Job {
+        Job::new("heist", "admin", "do crimes")
+            .objective(Objective::steal("corp", "/payroll"))
+            .objective(Objective::delete("corp", "/logs"))
+    }
+
+    #[test]
+    fn test_job_accepted() {
+        let mut app = JobsApp::default();
+        assert!(app.on_event(
+            &Event::JobAccepted {
+                job: job(),
+                deadline: None
+            },
+            false
+        ));
+        assert_eq!(app.jobs.len(), 1);
+        assert_eq!(app.jobs[0].done, vec![false, false]);
+        assert_eq!(app.notifs(), 1);
+    }
+
+    #[test]
+    fn test_job_progress_and_completion() {
+        let mut app = JobsApp::default();
+        app.on_event(
+            &Event::JobAccepted {
+                job: job(),
+                deadline: None,
+            },
+            true,
+        );
+        app.on_event(
+            &Event::JobProgress {
+                id: "heist".into(),
+                done: vec![true, false],
+            },
+            true,
+        );
+        assert_eq!(app.jobs[0].done, vec![true, false]);
+        app.on_event(&Event::JobCompleted("heist".into()), true);
+        assert_eq!(app.jobs[0].status, Status::Completed);
+        assert_eq!(app.notifs(), 0);
+    }
+
+    #[test]
+    fn test_unknown_job_ignored() {
+        let mut app = JobsApp::default();
+        assert!(!app.on_event(&Event::JobFailed("heist".into()), false));
+        assert_eq!(app.notifs(), 0);
+    }
+}
@@ -0,0 +1,161 @@
+//! Shows the hosts the player's found on the network.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use tuig::{
+    io::{fmt::Text, text, text1, Action, Screen},
+    Replies,
+};
+
+use crate::{
+    constants::{
+        gameplay::{ALERT_LIMIT, PLAYER_HOST},
+        graphics::HEADER_HEIGHT,
+    },
+    event::Event,
+    net::{Addr, Subnet},
+    state::GameState,
+    world::World,
+};
+
+/// The map tab.
+///
+/// Lists every host the player knows about, grouped by subnet, along with how much noise they've made on each.
+pub struct MapApp {
+    world: Arc<World>,
+    /// How many hosts the player knew about as of the last event
+    known: usize,
+    /// How many hosts were on the map the last time the player looked at it
+    seen: usize,
+}
+
+impl MapApp {
+    pub fn new(world: Arc<World>) -> Self {
+        let known = world.net.known.len();
+        Self {
+            world,
+            known,
+            seen: known,
+        }
+    }
+
+    /// Every known host with its address, grouped by subnet and sorted by address
+    fn hosts(&self) -> BTreeMap<Subnet, Vec<(Addr, String)>> {
+        let mut subnets: BTreeMap<Subnet, Vec<(Addr, String)>> = BTreeMap::new();
+        for host in self.world.net.known.iter() {
+            if let Some(addr) = self.world.net.addr_of(&host) {
+                subnets
+                    .entry(Subnet::of(addr))
+                    .or_default()
+                    .push((addr, host.clone()));
+            }
+        }
+        for hosts in subnets.values_mut() {
+            hosts.sort();
+        }
+        subnets
+    }
+}
+
+impl super::App for MapApp {
+    #[cfg_attr(coverage, no_coverage)]
+    fn name(&self) -> &'static str {
+        "map"
+    }
+
+    fn input(&mut self, _a: Action, _replies: &mut Replies<Event>) -> bool {
+        let tainted = self.seen != self.known;
+        self.seen = self.known;
+        tainted
+    }
+
+    fn on_event(&mut self, _ev: &Event, focused: bool) -> bool {
+        // hosts get found by tools poking at the world directly, so just keep an eye on how many there are
+        let known = self.world.net.known.len();
+        let tainted = known != self.known;
+        self.known = known;
+        if focused {
+            self.seen = known;
+        }
+        tainted
+    }
+
+    fn notifs(&self) -> usize {
+        self.known.saturating_sub(self.seen)
+    }
+
+    fn render(&self, _state: &GameState, screen: &mut Screen) {
+        let mut output: Vec<Text> = vec![];
+        for (subnet, hosts) in self.hosts() {
+            output.push(text1!(bold bright_white "{}\n"(subnet)));
+            for (addr, host) in hosts {
+                output.extend(text!("{:<15}  "(addr.to_string()), "{}"(host)));
+                let noise = self.world.alerts.get(&host).map(|n| *n).unwrap_or(0);
+                if host == PLAYER_HOST {
+                    output.push(text1!(bright_black " (you)"));
+                } else if noise >= ALERT_LIMIT {
+                    output.push(text1!(red " (on alert)"));
+                } else if noise > 0 {
+                    output.push(text1!(yellow " (noticed something)"));
+                }
+                output.push(text1!("\n"));
+            }
+        }
+        if self.known <= 1 {
+            output.push(text1!("\nNothing else out there yet. Try `scan`."));
+        }
+        screen
+            .textbox(output)
+            .pos(0, HEADER_HEIGHT)
+            .indent(2)
+            .first_indent(0);
+    }
+
+    fn save(&self) -> Option<String> {
+        Some(self.seen.to_string())
+    }
+
+    fn load(&mut self, data: &str) {
+        self.known = self.world.net.known.len();
+        self.seen = data.trim().parse().unwrap_or(self.known);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tuig::io::Key;
+
+    use crate::app::App;
+
+    use super::*;
+
+    #[test]
+    fn test_new_hosts_notify() {
+        let world = Arc::new(World::new(Default::default()));
+        world.net.add("fox-pc4", Addr([10, 0, 1, 40]));
+        world.net.add("hexacorp-db04", Addr([172, 16, 4, 20]));
+        let mut app = MapApp::new(world.clone());
+        assert!(!app.on_event(&Event::Tick, false));
+
+        world.net.learn("fox-pc4");
+        world.net.learn("hexacorp-db04");
+        assert!(app.on_event(&Event::Tick, false));
+        assert_eq!(app.notifs(), 2);
+        assert!(!app.on_event(&Event::Tick, false));
+        let subnets: Vec<_> = app.hosts().into_keys().collect();
+        assert_eq!(subnets, vec![Subnet([10, 0, 1]), Subnet([172, 16, 4])]);
+        assert_eq!(app.hosts()[&Subnet([10, 0, 1])].len(), 2);
+
+        // what the player hasn't looked at yet is still news after loading, and what they have isn't
+        let reload = |app: &MapApp| {
+            let mut reloaded = MapApp::new(world.clone());
+            reloaded.load(&app.save().unwrap());
+            reloaded.notifs()
+        };
+        assert_eq!(reload(&app), 2);
+        let look = Action::KeyPress { key: Key::Down };
+        assert!(app.input(look, &mut Replies::default()));
+        assert_eq!(app.notifs(), 0);
+        assert_eq!(reload(&app), 0);
+    }
+}
@@ -1,7 +1,7 @@
 //! Common structural code for apps.
 
 use tuig::{
-    io::{Action, Screen},
+    io::{Action, Screen, XY},
     Replies,
 };
 
@@ -31,9 +31,41 @@ pub trait App: Send + Sync + 'static {
     fn notifs(&self) -> usize;
     /// Display the game state on screen.
     ///
-    /// You can be sure that this will never be called except when the module is the active one; feel free to use it
-    /// for e.g. clearing notifications.
+    /// `screen` is only as big as the app's pane, which might be half the window when the screen's split. Its top
+    /// [`HEADER_HEIGHT`](crate::constants::graphics::HEADER_HEIGHT) rows are hidden under the header either way, so
+    /// apps can lay themselves out the same whether they're split or not.
+    ///
+    /// You can be sure that this will never be called except when the module is visible; feel free to use it for e.g.
+    /// clearing notifications.
     fn render(&self, state: &GameState, screen: &mut Screen);
+
+    /// Serialize whatever this app wants kept between sessions, or `None` if it has nothing worth saving.
+    ///
+    /// The result is handed back to [`App::load`] when the game's next loaded.
+    fn save(&self) -> Option<String> {
+        None
+    }
+    /// Restore whatever [`App::save`] saved last time.
+    fn load(&mut self, _data: &str) {}
+
+    /// Whether the app wants every key, even the ones that would normally switch tabs (e.g. while rebinding them).
+    fn capturing(&self) -> bool {
+        false
+    }
+
+    /// Handle something done with the mouse, given the size of the screen so clicks can be matched up with whatever's
+    /// rendered there. Like with [`App::render`], both are relative to the app's pane.
+    ///
+    /// Returns whether this item was tainted, i.e. true if it needs to be redrawn.
+    fn mouse(&mut self, _m: Mouse, _size: XY, _events: &mut Replies<Event>) -> bool {
+        false
+    }
+    /// Take text the player selected with the mouse, as if they'd typed it into whatever they're typing into.
+    ///
+    /// Returns whether this item was tainted, i.e. true if it needs to be redrawn.
+    fn paste(&mut self, _text: &str) -> bool {
+        false
+    }
 }
 
 /// Assert things about the outcomes of an `App` receiving input
@@ -61,4 +93,15 @@ macro_rules! assert_input {
 mod chat;
 pub use chat::ChatApp;
 mod cli;
+mod draft;
 pub use cli::{CliApp, CliState};
+mod jobs;
+pub use jobs::JobsApp;
+mod map;
+pub use map::MapApp;
+mod mouse;
+pub use mouse::{Frame, Mouse, Selection};
+mod notes;
+pub use notes::NotesApp;
+mod settings;
+pub use settings::SettingsApp;
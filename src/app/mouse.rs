@@ -0,0 +1,184 @@
+//! Mouse support shared between apps: clicks, the scroll wheel, and selecting text by dragging.
+//!
+//! tuig only reports where the mouse moved to and which buttons were pressed, so [`Redshell`](crate::game) keeps
+//! track of where the mouse is and turns the raw actions into [`Mouse`] events for the current app. Selecting text
+//! is handled entirely outside the apps: whatever's on screen under the selection is copied out of the last
+//! [`Frame`] and [`App::paste`](super::App::paste)d into the current app.
+
+use tuig::io::{
+    fmt::{Cell, Color},
+    Screen, XY,
+};
+
+/// Something the mouse did, for an app to react to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mouse {
+    /// The left button was clicked (pressed and released without moving) at this spot on the screen
+    Click(XY),
+    /// The scroll wheel was turned up, to see older things
+    ScrollUp,
+    /// The scroll wheel was turned down, to see newer things
+    ScrollDown,
+}
+
+/// A stretch of the screen being selected, in reading order, like in a terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selection {
+    /// Where the drag started
+    pub from: XY,
+    /// Where the mouse is now
+    pub to: XY,
+}
+
+impl Selection {
+    /// Start selecting at a spot on the screen
+    pub fn at(pos: XY) -> Self {
+        Self { from: pos, to: pos }
+    }
+
+    /// Whether the mouse has moved at all since the selection started, i.e. whether it was a drag and not a click
+    pub fn dragged(&self) -> bool {
+        self.from != self.to
+    }
+
+    /// The start and end of the selection, in reading order, as (y, x)
+    fn bounds(&self) -> ((usize, usize), (usize, usize)) {
+        let from = (self.from.y(), self.from.x());
+        let to = (self.to.y(), self.to.x());
+        (from.min(to), from.max(to))
+    }
+
+    /// Whether a spot on screen is selected
+    pub fn contains(&self, pos: XY) -> bool {
+        let (start, end) = self.bounds();
+        (start..=end).contains(&(pos.y(), pos.x()))
+    }
+
+    /// Highlight the selection on screen
+    pub fn highlight(&self, screen: &mut Screen) {
+        let (start, end) = self.bounds();
+        for y in start.0..=end.0.min(screen.size().y().saturating_sub(1)) {
+            for (x, cell) in screen[y].iter_mut().enumerate() {
+                if self.contains(XY(x, y)) {
+                    cell.fmt.fg = Color::Black;
+                    cell.fmt.bg = Color::White;
+                }
+            }
+        }
+    }
+
+    /// The selected text. Lines are trimmed and joined up with spaces, since everywhere it can be pasted only takes a
+    /// single line.
+    pub fn text(&self, frame: &Frame) -> String {
+        let (start, end) = self.bounds();
+        let mut lines = vec![];
+        for (y, row) in frame.rows.iter().enumerate().take(end.0 + 1).skip(start.0) {
+            let x0 = if y == start.0 { start.1 } else { 0 };
+            let x1 = if y == end.0 { end.1 + 1 } else { row.len() };
+            let line: String = row.iter().take(x1.min(row.len())).skip(x0).collect();
+            let line = line.trim();
+            if !line.is_empty() {
+                lines.push(line.to_owned());
+            }
+        }
+        lines.join(" ")
+    }
+}
+
+/// The text of the last frame drawn, so selections can be copied out of it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub rows: Vec<Vec<char>>,
+}
+
+impl Frame {
+    /// Copy the text off a screen
+    pub fn of(screen: &Screen) -> Self {
+        let rows = (0..screen.size().y())
+            .map(|y| screen[y].iter().map(|c: &Cell| c.ch).collect())
+            .collect();
+        Self { rows }
+    }
+
+    /// Where some text first shows up on a row, starting from column `from`, if it does at all
+    pub fn find(&self, y: usize, text: &str, from: usize) -> Option<usize> {
+        let row = self.rows.get(y)?;
+        let text: Vec<char> = text.chars().collect();
+        if text.is_empty() || row.len() < text.len() {
+            return None;
+        }
+        (from..=row.len() - text.len()).find(|&x| row[x..x + text.len()] == text[..])
+    }
+
+    /// How big the screen was
+    pub fn size(&self) -> XY {
+        XY(
+            self.rows.first().map(|r| r.len()).unwrap_or(0),
+            self.rows.len(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(rows: &[&str]) -> Frame {
+        Frame {
+            rows: rows.iter().map(|r| r.chars().collect()).collect(),
+        }
+    }
+
+    #[test]
+    fn select_within_line() {
+        let frame = frame(&["hello there world"]);
+        let sel = Selection {
+            from: XY(6, 0),
+            to: XY(10, 0),
+        };
+        assert_eq!(sel.text(&frame), "there");
+    }
+
+    #[test]
+    fn select_backwards_across_lines() {
+        let frame = frame(&["  one two  ", "three four ", "five   "]);
+        let sel = Selection {
+            from: XY(3, 2),
+            to: XY(6, 0),
+        };
+        assert_eq!(sel.text(&frame), "two three four five");
+        assert!(sel.contains(XY(0, 1)));
+        assert!(!sel.contains(XY(5, 0)));
+        assert!(!sel.contains(XY(4, 2)));
+    }
+
+    #[test]
+    fn select_off_screen() {
+        let frame = frame(&["abc"]);
+        let sel = Selection {
+            from: XY(1, 0),
+            to: XY(20, 5),
+        };
+        assert_eq!(sel.text(&frame), "bc");
+        assert_eq!(frame.size(), XY(3, 1));
+    }
+
+    #[test]
+    fn find_text_on_row() {
+        let frame = frame(&["chat  terminal  chat", "notes"]);
+        assert_eq!(frame.find(0, "chat", 0), Some(0));
+        assert_eq!(frame.find(0, "chat", 1), Some(16));
+        assert_eq!(frame.find(0, "terminal", 0), Some(6));
+        assert_eq!(frame.find(0, "notes", 0), None);
+        assert_eq!(frame.find(1, "notes", 0), Some(0));
+        assert_eq!(frame.find(2, "notes", 0), None);
+    }
+
+    #[test]
+    fn click_is_not_a_drag() {
+        let mut sel = Selection::at(XY(4, 4));
+        assert!(!sel.dragged());
+        sel.to = XY(5, 4);
+        assert!(sel.dragged());
+    }
+}
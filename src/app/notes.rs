@@ -0,0 +1,331 @@
+//! Lets the player keep notes.
+
+use tuig::{
+    io::{text, text1, Action, Key, Screen},
+    Replies,
+};
+
+use crate::{constants::graphics::HEADER_HEIGHT, event::Event, state::GameState};
+
+use super::draft::Draft;
+
+/// What the notes tab is currently doing with input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Moving around the list of notes
+    Browse,
+    /// Editing a note; `None` is a new one
+    Edit(Option<usize>, Draft),
+    /// Typing a search query
+    Search(Draft),
+}
+
+/// The notes tab.
+///
+/// Holds freeform notes the player writes, or pins from elsewhere (e.g. with Insert in the terminal or chat), and lets
+/// them edit, delete, and search them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotesApp {
+    /// Every note, oldest first
+    notes: Vec<String>,
+    /// Which note is selected, as an index into the filtered list
+    sel: usize,
+    /// Only notes containing this (case-insensitively) are shown
+    query: String,
+    mode: Mode,
+    /// How many notes were pinned since the player last looked
+    unread: usize,
+}
+
+impl Default for NotesApp {
+    fn default() -> Self {
+        Self {
+            notes: vec![],
+            sel: 0,
+            query: String::new(),
+            mode: Mode::Browse,
+            unread: 0,
+        }
+    }
+}
+
+impl NotesApp {
+    /// The indices of every note that matches the current search
+    fn filtered(&self, query: &str) -> Vec<usize> {
+        let query = query.to_lowercase();
+        (0..self.notes.len())
+            .filter(|&i| self.notes[i].to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// The index (into `notes`) of the selected note, if there is one
+    fn selected(&self) -> Option<usize> {
+        self.filtered(&self.query).get(self.sel).copied()
+    }
+
+    fn browse(&mut self, key: Key) -> bool {
+        let shown = self.filtered(&self.query).len();
+        match key {
+            Key::Up if self.sel > 0 => self.sel -= 1,
+            Key::Down if self.sel + 1 < shown => self.sel += 1,
+            Key::Char('n') | Key::Insert => self.mode = Mode::Edit(None, Draft::default()),
+            Key::Char('e') | Key::Enter => match self.selected() {
+                Some(i) => self.mode = Mode::Edit(Some(i), Draft::of(&self.notes[i])),
+                None => return false,
+            },
+            Key::Char('d') | Key::Delete => match self.selected() {
+                Some(i) => {
+                    self.notes.remove(i);
+                    self.sel = self.sel.min(shown.saturating_sub(2));
+                }
+                None => return false,
+            },
+            Key::Char('/') => self.mode = Mode::Search(Draft::of(&self.query)),
+            Key::Escape if !self.query.is_empty() => {
+                self.query.clear();
+                self.sel = 0;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn edit(&mut self, idx: Option<usize>, mut draft: Draft, key: Key) -> bool {
+        match key {
+            Key::Enter => {
+                let text = draft.text.trim().to_owned();
+                match (idx, text.is_empty()) {
+                    (Some(i), true) => {
+                        self.notes.remove(i);
+                        self.sel = 0;
+                    }
+                    (Some(i), false) => self.notes[i] = text,
+                    (None, true) => (),
+                    (None, false) => {
+                        self.notes.push(text);
+                        // clear the search so the new note is definitely visible, then select it
+                        self.query.clear();
+                        self.sel = self.notes.len() - 1;
+                    }
+                }
+                self.mode = Mode::Browse;
+            }
+            Key::Escape => self.mode = Mode::Browse,
+            key => {
+                let changed = draft.key(key);
+                self.mode = Mode::Edit(idx, draft);
+                return changed;
+            }
+        }
+        true
+    }
+
+    fn search(&mut self, mut draft: Draft, key: Key) -> bool {
+        match key {
+            Key::Enter => {
+                self.query = draft.text;
+                self.mode = Mode::Browse;
+            }
+            Key::Escape => {
+                self.query.clear();
+                self.mode = Mode::Browse;
+            }
+            key => {
+                let changed = draft.key(key);
+                self.mode = Mode::Search(draft);
+                return changed;
+            }
+        }
+        self.sel = 0;
+        true
+    }
+}
+
+impl super::App for NotesApp {
+    #[cfg_attr(coverage, no_coverage)]
+    fn name(&self) -> &'static str {
+        "notes"
+    }
+
+    fn input(&mut self, a: Action, _replies: &mut Replies<Event>) -> bool {
+        self.unread = 0;
+        let key = match a {
+            Action::KeyPress { key } => key,
+            _ => return false,
+        };
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Browse => self.browse(key),
+            Mode::Edit(idx, draft) => self.edit(idx, draft, key),
+            Mode::Search(draft) => self.search(draft, key),
+        }
+    }
+
+    fn on_event(&mut self, ev: &Event, focused: bool) -> bool {
+        match ev {
+            Event::PinNote(text) => {
+                self.notes.push(text.replace('\n', " ").trim().to_owned());
+                if !focused {
+                    self.unread += 1;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn notifs(&self) -> usize {
+        self.unread
+    }
+
+    fn save(&self) -> Option<String> {
+        Some(self.notes.join("\n"))
+    }
+
+    fn load(&mut self, data: &str) {
+        self.notes = data.lines().map(|l| l.to_owned()).collect();
+        self.sel = 0;
+    }
+
+    fn render(&self, _state: &GameState, screen: &mut Screen) {
+        let mut output = vec![];
+        let (query, searching) = match &self.mode {
+            Mode::Search(draft) => (draft.text.as_str(), true),
+            _ => (self.query.as_str(), false),
+        };
+        match &self.mode {
+            Mode::Browse => output.extend(text!(
+                bright_black "n: new  enter: edit  d: delete  /: search  esc: clear search\n"
+            )),
+            Mode::Edit(..) => output.extend(text!(bright_black "enter: save  esc: cancel\n")),
+            Mode::Search(draft) => {
+                output.push(text1!("search: "));
+                draft.render(&mut output);
+                output.push(text1!("\n"));
+            }
+        }
+        if !searching && !query.is_empty() {
+            output.extend(text!("search: ", bright_white "{}\n"(query)));
+        }
+        let filtered = self.filtered(query);
+        for (pos, &i) in filtered.iter().enumerate() {
+            match &self.mode {
+                Mode::Edit(Some(e), draft) if *e == i => {
+                    output.push(text1!("> "));
+                    draft.render(&mut output);
+                    output.push(text1!("\n"));
+                }
+                Mode::Browse if pos == self.sel => {
+                    output.push(text1!(bright_white "> {}\n"(self.notes[i])));
+                }
+                _ => output.push(text1!("  {}\n"(self.notes[i]))),
+            }
+        }
+        if let Mode::Edit(None, draft) = &self.mode {
+            output.push(text1!("> "));
+            draft.render(&mut output);
+            output.push(text1!("\n"));
+        } else if self.notes.is_empty() {
+            output.push(text1!("No notes yet. Press n to write one, or Insert in chat or the terminal to pin things here."));
+        }
+        screen
+            .textbox(output)
+            .pos(0, HEADER_HEIGHT)
+            .indent(2)
+            .first_indent(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::App;
+
+    use super::*;
+
+    fn press(app: &mut NotesApp, keys: &[Key]) {
+        for &key in keys {
+            app.input(Action::KeyPress { key }, &mut Replies::default());
+        }
+    }
+
+    fn type_str(app: &mut NotesApp, text: &str) {
+        for c in text.chars() {
+            press(app, &[Key::Char(c)]);
+        }
+    }
+
+    fn app_with(notes: &[&str]) -> NotesApp {
+        NotesApp {
+            notes: notes.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_write_note() {
+        let mut app = NotesApp::default();
+        press(&mut app, &[Key::Char('n')]);
+        type_str(&mut app, "admin is sus");
+        press(&mut app, &[Key::Enter]);
+        assert_eq!(app.notes, vec!["admin is sus"]);
+        assert_eq!(app.mode, Mode::Browse);
+    }
+
+    #[test]
+    fn test_edit_note() {
+        let mut app = app_with(&["one", "twp"]);
+        press(&mut app, &[Key::Down, Key::Enter, Key::Backspace]);
+        type_str(&mut app, "o");
+        press(&mut app, &[Key::Home, Key::Delete]);
+        type_str(&mut app, "T");
+        press(&mut app, &[Key::Enter]);
+        assert_eq!(app.notes, vec!["one", "Two"]);
+    }
+
+    #[test]
+    fn test_cancel_edit() {
+        let mut app = app_with(&["one"]);
+        press(&mut app, &[Key::Enter, Key::Backspace, Key::Escape]);
+        assert_eq!(app.notes, vec!["one"]);
+    }
+
+    #[test]
+    fn test_delete_note() {
+        let mut app = app_with(&["one", "two", "three"]);
+        press(&mut app, &[Key::Down, Key::Char('d')]);
+        assert_eq!(app.notes, vec!["one", "three"]);
+        // emptying a note out while editing deletes it too
+        press(&mut app, &[Key::Enter]);
+        press(&mut app, &[Key::Backspace; 5]);
+        press(&mut app, &[Key::Enter]);
+        assert_eq!(app.notes, vec!["one"]);
+    }
+
+    #[test]
+    fn test_search_notes() {
+        let mut app = app_with(&["corp password: hunter2", "buy milk", "PASSWORD for yotie"]);
+        press(&mut app, &[Key::Char('/')]);
+        type_str(&mut app, "password");
+        press(&mut app, &[Key::Enter]);
+        assert_eq!(app.filtered(&app.query), vec![0, 2]);
+        press(&mut app, &[Key::Down, Key::Char('d')]);
+        assert_eq!(app.notes, vec!["corp password: hunter2", "buy milk"]);
+        press(&mut app, &[Key::Escape]);
+        assert_eq!(app.filtered(&app.query), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_pin_note() {
+        let mut app = NotesApp::default();
+        assert!(app.on_event(&Event::PinNote("admin: hi\n".into()), false));
+        assert_eq!(app.notes, vec!["admin: hi"]);
+        assert_eq!(app.notifs(), 1);
+    }
+
+    #[test]
+    fn test_save_load() {
+        let app = app_with(&["one", "two"]);
+        let mut loaded = NotesApp::default();
+        loaded.load(&app.save().unwrap());
+        assert_eq!(loaded, app);
+    }
+}
@@ -0,0 +1,300 @@
+//! Lets the player change their settings.
+
+use tuig::{
+    io::{fmt::Text, text, text1, Action, Key, Screen},
+    Replies,
+};
+
+use crate::{
+    constants::graphics::HEADER_HEIGHT,
+    event::Event,
+    settings::{key_name, Settings, Theme},
+    state::GameState,
+};
+
+/// The text speeds the player can pick between, as percentages of normal.
+const TEXT_SPEEDS: [u32; 7] = [25, 50, 75, 100, 150, 200, 400];
+/// The clock speeds the player can pick between, in in-game seconds per real second.
+const CLOCK_SPEEDS: [u32; 5] = [15, 30, 60, 120, 240];
+
+/// A single row in the settings list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    TextSpeed,
+    InstantText,
+    ClockSpeed,
+    Theme,
+    BoldText,
+    NextTab,
+    PrevTab,
+    Pin,
+    Search,
+}
+
+impl Field {
+    const ALL: [Field; 9] = [
+        Field::TextSpeed,
+        Field::InstantText,
+        Field::ClockSpeed,
+        Field::Theme,
+        Field::BoldText,
+        Field::NextTab,
+        Field::PrevTab,
+        Field::Pin,
+        Field::Search,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Field::TextSpeed => "text speed",
+            Field::InstantText => "instant text",
+            Field::ClockSpeed => "clock speed",
+            Field::Theme => "color theme",
+            Field::BoldText => "bold text",
+            Field::NextTab => "next tab",
+            Field::PrevTab => "previous tab",
+            Field::Pin => "pin to notes",
+            Field::Search => "search chat",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        match self {
+            Field::TextSpeed => format!("{}%", settings.text_speed),
+            Field::InstantText => on_off(settings.instant_text).into(),
+            Field::ClockSpeed => format!("{}x", settings.clock_speed),
+            Field::Theme => settings.theme.name().into(),
+            Field::BoldText => on_off(settings.bold_text).into(),
+            Field::NextTab => key_name(settings.next_tab),
+            Field::PrevTab => key_name(settings.prev_tab),
+            Field::Pin => key_name(settings.pin),
+            Field::Search => key_name(settings.search),
+        }
+    }
+
+    /// The key binding this field sets, if it's a key binding
+    fn binding<'s>(&self, settings: &'s mut Settings) -> Option<&'s mut Key> {
+        match self {
+            Field::NextTab => Some(&mut settings.next_tab),
+            Field::PrevTab => Some(&mut settings.prev_tab),
+            Field::Pin => Some(&mut settings.pin),
+            Field::Search => Some(&mut settings.search),
+            _ => None,
+        }
+    }
+
+    /// Step the value forwards or backwards through its options, returning whether it changed
+    fn adjust(&self, settings: &mut Settings, forward: bool) -> bool {
+        match self {
+            Field::TextSpeed => step(&TEXT_SPEEDS, &mut settings.text_speed, forward),
+            Field::ClockSpeed => step(&CLOCK_SPEEDS, &mut settings.clock_speed, forward),
+            Field::Theme => step(&Theme::ALL, &mut settings.theme, forward),
+            Field::InstantText => {
+                settings.instant_text = !settings.instant_text;
+                true
+            }
+            Field::BoldText => {
+                settings.bold_text = !settings.bold_text;
+                true
+            }
+            Field::NextTab | Field::PrevTab | Field::Pin | Field::Search => false,
+        }
+    }
+}
+
+fn on_off(b: bool) -> &'static str {
+    if b {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Move `value` to the next or previous option, stopping at the ends. Values that aren't an option (e.g. from a
+/// hand-edited config file) snap to the first one.
+fn step<T: Copy + PartialEq>(options: &[T], value: &mut T, forward: bool) -> bool {
+    let next = match (options.iter().position(|o| o == value), forward) {
+        (None, _) => 0,
+        (Some(i), true) if i + 1 < options.len() => i + 1,
+        (Some(i), false) if i > 0 => i - 1,
+        _ => return false,
+    };
+    *value = options[next];
+    true
+}
+
+/// The settings tab.
+///
+/// Lists every setting, and lets the player change them. Changes apply immediately, and are saved to the config file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SettingsApp {
+    settings: Settings,
+    /// Which field is selected
+    sel: usize,
+    /// Whether the next key pressed will be bound to the selected field
+    rebinding: bool,
+}
+
+impl SettingsApp {
+    /// Create a settings tab, starting from the settings currently in use.
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    fn changed(&self, replies: &mut Replies<Event>) {
+        replies.queue(Event::SettingsChanged(self.settings.clone()));
+    }
+}
+
+impl super::App for SettingsApp {
+    #[cfg_attr(coverage, no_coverage)]
+    fn name(&self) -> &'static str {
+        "settings"
+    }
+
+    fn input(&mut self, a: Action, replies: &mut Replies<Event>) -> bool {
+        let key = match a {
+            Action::KeyPress { key } => key,
+            _ => return false,
+        };
+        let field = Field::ALL[self.sel];
+        if self.rebinding {
+            self.rebinding = false;
+            if key != Key::Escape {
+                if let Some(binding) = field.binding(&mut self.settings) {
+                    *binding = key;
+                }
+                self.changed(replies);
+            }
+            return true;
+        }
+        match key {
+            Key::Up if self.sel > 0 => self.sel -= 1,
+            Key::Down if self.sel + 1 < Field::ALL.len() => self.sel += 1,
+            Key::Enter if field.binding(&mut self.settings).is_some() => self.rebinding = true,
+            Key::Left | Key::Right | Key::Enter => {
+                if !field.adjust(&mut self.settings, key != Key::Left) {
+                    return false;
+                }
+                self.changed(replies);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn on_event(&mut self, _ev: &Event, _focused: bool) -> bool {
+        false
+    }
+
+    fn notifs(&self) -> usize {
+        0
+    }
+
+    fn capturing(&self) -> bool {
+        self.rebinding
+    }
+
+    fn render(&self, _state: &GameState, screen: &mut Screen) {
+        let mut output: Vec<Text> = text!(
+            bright_black "up/down: select  left/right: change  enter: toggle or rebind\n\n"
+        );
+        for (i, field) in Field::ALL.iter().enumerate() {
+            let value = if i == self.sel && self.rebinding {
+                text1!(bright_yellow "press a key (esc to cancel)")
+            } else {
+                text1!(cyan "{}"(field.value(&self.settings)))
+            };
+            if i == self.sel {
+                output.push(text1!(bright_white "> {:<14}"(field.name())));
+            } else {
+                output.push(text1!("  {:<14}"(field.name())));
+            }
+            output.push(value);
+            output.push(text1!("\n"));
+        }
+        screen.textbox(output).pos(0, HEADER_HEIGHT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::App;
+
+    use super::*;
+
+    const UP: Action = Action::KeyPress { key: Key::Up };
+    const DOWN: Action = Action::KeyPress { key: Key::Down };
+    const LEFT: Action = Action::KeyPress { key: Key::Left };
+    const RIGHT: Action = Action::KeyPress { key: Key::Right };
+    const ENTER: Action = Action::KeyPress { key: Key::Enter };
+
+    fn app_at(field: Field) -> SettingsApp {
+        SettingsApp {
+            sel: Field::ALL.iter().position(|&f| f == field).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select_fields() {
+        let mut app = SettingsApp::default();
+        assert_input!(app.input(UP) clean, .is_empty());
+        assert_input!(app.input(DOWN) taints, .is_empty());
+        assert_eq!(app.sel, 1);
+    }
+
+    #[test]
+    fn test_change_text_speed() {
+        let mut app = app_at(Field::TextSpeed);
+        let faster = Settings {
+            text_speed: 150,
+            ..Default::default()
+        };
+        assert_input!(app.input(RIGHT) taints, == &[Event::SettingsChanged(faster)]);
+        assert_input!(app.input(LEFT) taints, .len() == 1);
+        assert_eq!(app.settings, Settings::default());
+    }
+
+    #[test]
+    fn test_options_stop_at_ends() {
+        let mut app = app_at(Field::Theme);
+        assert_input!(app.input(LEFT) clean, .is_empty());
+        app.input(RIGHT, &mut Replies::default());
+        app.input(RIGHT, &mut Replies::default());
+        assert_input!(app.input(RIGHT) clean, .is_empty());
+        assert_eq!(app.settings.theme, Theme::Monochrome);
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut app = app_at(Field::BoldText);
+        assert_input!(app.input(ENTER) taints, .len() == 1);
+        assert!(app.settings.bold_text);
+    }
+
+    #[test]
+    fn test_rebind_key() {
+        let mut app = app_at(Field::Pin);
+        assert_input!(app.input(ENTER) taints, .is_empty());
+        assert!(app.capturing());
+        let p = Action::KeyPress {
+            key: Key::Char('p'),
+        };
+        assert_input!(app.input(p) taints, .len() == 1);
+        assert!(!app.capturing());
+        assert_eq!(app.settings.pin, Key::Char('p'));
+    }
+
+    #[test]
+    fn test_cancel_rebind() {
+        let mut app = app_at(Field::NextTab);
+        app.input(ENTER, &mut Replies::default());
+        let esc = Action::KeyPress { key: Key::Escape };
+        assert_input!(app.input(esc) taints, .is_empty());
+        assert_eq!(app.settings, Settings::default());
+    }
+}
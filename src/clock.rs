@@ -0,0 +1,130 @@
+//! The in-game clock, which runs at some multiple of real time.
+//!
+//! There's only ever one clock, so it's global; anything that needs to know what time it is in-game can just ask.
+
+use std::{
+    fmt,
+    ops::Add,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::constants::gameplay::{CLOCK_SPEED, START_TIME};
+
+/// A single moment in in-game time, measured in seconds since midnight on the first day.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameTime(pub u64);
+
+impl GameTime {
+    /// Which day it is, starting from day 1
+    pub fn day(&self) -> u64 {
+        self.0 / (24 * 60 * 60) + 1
+    }
+
+    /// The hour of the day, 0-23
+    pub fn hour(&self) -> u64 {
+        self.0 / (60 * 60) % 24
+    }
+
+    /// The minute of the hour, 0-59
+    pub fn minute(&self) -> u64 {
+        self.0 / 60 % 60
+    }
+
+    /// How much in-game time is left between `self` and some later time, or `None` if it's already passed.
+    pub fn until(&self, later: GameTime) -> Option<Duration> {
+        later.0.checked_sub(self.0).map(Duration::from_secs)
+    }
+}
+
+impl Add<Duration> for GameTime {
+    type Output = GameTime;
+
+    fn add(self, rhs: Duration) -> GameTime {
+        GameTime(self.0 + rhs.as_secs())
+    }
+}
+
+impl fmt::Display for GameTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {} {:02}:{:02}",
+            self.day(),
+            self.hour(),
+            self.minute()
+        )
+    }
+}
+
+/// The actual clock, tracking when it was last adjusted so the current time can be computed.
+struct Clock {
+    /// The in-game time as of `since`
+    base: GameTime,
+    /// The real time that `base` was set
+    since: Instant,
+    /// How many in-game seconds pass per real second
+    speed: f32,
+}
+
+impl Clock {
+    fn now(&self) -> GameTime {
+        let elapsed = self.since.elapsed().as_secs_f32() * self.speed;
+        GameTime(self.base.0 + elapsed as u64)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CLOCK: Mutex<Clock> = Mutex::new(Clock {
+        base: START_TIME,
+        since: Instant::now(),
+        speed: CLOCK_SPEED,
+    });
+}
+
+/// The current in-game time.
+pub fn now() -> GameTime {
+    CLOCK.lock().unwrap().now()
+}
+
+/// Jump the clock to a specific time, e.g. when loading a save.
+pub fn set(time: GameTime) {
+    let mut clock = CLOCK.lock().unwrap();
+    clock.base = time;
+    clock.since = Instant::now();
+}
+
+/// Change how many in-game seconds pass per real second, without making the current time jump.
+pub fn set_speed(speed: f32) {
+    let mut clock = CLOCK.lock().unwrap();
+    clock.base = clock.now();
+    clock.since = Instant::now();
+    clock.speed = speed;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn time_splits_into_parts() {
+        let t = GameTime(2 * 24 * 60 * 60 + 13 * 60 * 60 + 7 * 60 + 59);
+        assert_eq!(t.day(), 3);
+        assert_eq!(t.hour(), 13);
+        assert_eq!(t.minute(), 7);
+        assert_eq!(t.to_string(), "day 3 13:07");
+    }
+
+    #[test]
+    fn time_until_later() {
+        let t = GameTime(100);
+        assert_eq!(t.until(GameTime(160)), Some(Duration::from_secs(60)));
+        assert_eq!(t.until(GameTime(100)), Some(Duration::ZERO));
+        assert_eq!(t.until(GameTime(99)), None);
+    }
+
+    #[test]
+    fn time_adds_duration() {
+        assert_eq!(GameTime(10) + Duration::from_secs(50), GameTime(60));
+    }
+}
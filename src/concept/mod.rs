@@ -68,12 +68,11 @@ fn render_demo(io: &mut dyn IoSystem) {
     std::thread::sleep(Duration::from_secs(5));
 }
 
-// TODO: #35
-// /// Run the intro cutscene
-// fn intro_demo(io: &mut dyn IoSystem) {
-//     let size = io.size();
-//     redshell::cutscenes::intro(io, &mut Screen::new(size)).expect("Failed to run intro");
-// }
+/// Run the intro cutscene
+fn intro_demo(io: &mut dyn IoSystem) {
+    let size = io.size();
+    crate::cutscenes::intro(io, &mut Screen::new(size)).expect("Failed to run intro");
+}
 
 /// Run the demo of the chat app
 fn chat_demo(io: &mut dyn IoSystem) {
@@ -204,8 +203,7 @@ pub fn run(mut args: impl Iterator<Item = String>) {
     let arg = args.next();
     let concept = match arg.as_ref().map(|s| s.as_str()) {
         Some("render") => render_demo,
-        // TODO: #35
-        // Some("intro") => intro_demo,
+        Some("intro") => intro_demo,
         Some("chat") => chat_demo,
         Some("mouse") => mouse_demo,
         _ => {
@@ -2,12 +2,106 @@
 
 /// Constants with gameplay implications, albeit potentially minor.
 pub mod gameplay {
+    use crate::{
+        clock::GameTime,
+        net::{Addr, Subnet},
+    };
+
     /// The maximum length of a username. Mostly used to compute minimum size.
     pub const MAX_USERNAME: usize = 10;
+    /// The hostname the player's own machine is filed under.
+    pub const PLAYER_HOST: &str = "localhost";
+    /// What time it is when a new game starts (8 in the morning on day 1).
+    pub const START_TIME: GameTime = GameTime(8 * 60 * 60);
+    /// How many in-game seconds pass per real second, by default.
+    pub const CLOCK_SPEED: f32 = 60.0;
+    /// The most (or, negated, least) an NPC can trust the player, or a faction can like them.
+    pub const MAX_STANDING: i32 = 100;
+    /// How much an NPC's trust changes when the player finishes (or fails) a job they offered.
+    pub const JOB_TRUST: i32 = 10;
+    /// How much the giver's faction's reputation changes when the player finishes (or fails) a job.
+    pub const JOB_REPUTATION: i32 = 5;
+    /// The highest skill level a team member can have.
+    pub const MAX_SKILL: u32 = 10;
+    /// The fewest and most real seconds a team member spends on each part of a job.
+    pub const MEMBER_WORK_SECS: (u64, u64) = (15, 45);
+    /// How many tokens the player starts the game with.
+    pub const STARTING_TOKENS: i64 = 20;
+    /// How many in-game seconds a transfer stays on the header after it happens.
+    pub const TRANSFER_SHOWN_SECS: u64 = 10 * 60;
+    /// How many in-game seconds a newly unlocked tab is announced on the header for.
+    pub const UNLOCK_SHOWN_SECS: u64 = 10 * 60;
+    /// How many in-game seconds something going wrong in the background (like saving) is shown on the header for.
+    pub const FAILURE_SHOWN_SECS: u64 = 30 * 60;
+    /// How many corporate machines are generated for a new world.
+    pub const CORP_HOSTS: usize = 4;
+    /// How many civilian machines are generated for a new world.
+    pub const CIVILIAN_HOSTS: usize = 6;
+    /// How many NPCs are generated for a new world, on top of the hand-written ones.
+    pub const GENERATED_NPCS: usize = 4;
+    /// The subnet the player's machine is on, along with the civilians around them.
+    pub const HOME_SUBNET: Subnet = Subnet([10, 0, 1]);
+    /// The player's machine's address, which is what turns up in other machines' logs.
+    pub const PLAYER_ADDR: Addr = Addr([10, 0, 1, 2]);
+    /// The first two parts of every corp's subnet; each corp gets its own third part.
+    pub const CORP_NET: [u8; 2] = [172, 16];
+    /// How many milliseconds scanning an address with nothing on it takes.
+    pub const SCAN_EMPTY_MS: u64 = 30;
+    /// How many milliseconds scanning an address with a machine on it takes, since its ports get checked too.
+    pub const SCAN_HOST_MS: u64 = 600;
+    /// Who the player's logged in as on their own machine.
+    pub const PLAYER_USER: &str = "root";
+    /// How many milliseconds an exploit takes to run, win or lose.
+    pub const EXPLOIT_MS: u64 = 2000;
+    /// How much noise a machine's admins put up with before they notice and patch whatever's being exploited.
+    pub const ALERT_LIMIT: u32 = 12;
+    /// How much noise a failed login makes on the machine it was tried on.
+    pub const LOGIN_NOISE: u32 = 1;
+    /// Where files downloaded from other machines end up on the player's, in a subdirectory per machine.
+    pub const DOWNLOAD_DIR: &str = "/downloads/";
+    /// What wordlists' filenames end with, so cracking can find them.
+    pub const WORDLIST_EXT: &str = ".dict";
+    /// How many milliseconds pass between each batch of guesses while cracking.
+    pub const CRACK_STEP_MS: u64 = 1000;
+    /// How much guessing the player's machine gets through per batch while cracking, before any upgrades. Each guess
+    /// costs [`HashKind::cost`](crate::machine::crypt::HashKind::cost) per hash it's checked against.
+    pub const CRACK_RATE: u64 = 2000;
+    /// How many times the player's hardware can be upgraded. Each upgrade doubles cracking speed.
+    pub const MAX_HARDWARE: u32 = 4;
+    /// Where every machine keeps its logs.
+    pub const LOG_DIR: &str = "/var/log/";
+    /// The log of logins, successful and otherwise.
+    pub const AUTH_LOG: &str = "/var/log/auth.log";
+    /// The log of everything remote users do: reading, writing, deleting, and running commands.
+    pub const ACCESS_LOG: &str = "/var/log/access.log";
+    /// How many in-game seconds pass between corps' forensic audits of their logs.
+    pub const FORENSICS_SECS: u64 = 2 * 60 * 60;
+    /// How much a corp's reputation of the player drops when an audit turns up evidence against them.
+    pub const TRACE_REPUTATION: i32 = 10;
+    /// The highest security level a machine can have.
+    pub const MAX_SECURITY: u32 = 10;
+    /// How much an NPC has to trust the player before there's no chance of them betraying the team.
+    pub const LOYAL_TRUST: i32 = 20;
+    /// Where the game is saved to and loaded from.
+    pub const SAVE_FILE: &str = "redshell.save";
+    /// How many in-game seconds pass between autosaves.
+    pub const AUTOSAVE_SECS: u64 = 30 * 60;
+    /// Where files NPCs send the player end up, in a subdirectory per NPC.
+    pub const INBOX_DIR: &str = "/inbox/";
+    /// Where the player's settings are saved to and loaded from.
+    pub const CONFIG_FILE: &str = "redshell.cfg";
+    /// How many milliseconds it takes an NPC to type each character of a message, at normal text speed.
+    pub const TYPING_MS_PER_CHAR: usize = 30;
 }
 
 /// Constants specifically relating to how things render.
 pub mod graphics {
     /// How many rows the header takes up. Used by apps for relative positioning.
     pub const HEADER_HEIGHT: usize = 1;
+    /// How many milliseconds it takes each character of a cutscene's text to appear, at normal text speed.
+    pub const REVEAL_MS_PER_CHAR: usize = 15;
+    /// How many percent of the screen a split pane grows or shrinks by at a time.
+    pub const SPLIT_STEP: usize = 5;
+    /// The smallest a split pane can get, as a percentage of the screen.
+    pub const SPLIT_MIN: usize = 20;
 }
@@ -0,0 +1,254 @@
+//! Full-screen effects cutscenes can play between (or instead of) text.
+
+use std::time::{Duration, Instant};
+
+use tuig::io::{
+    cell,
+    fmt::{Cell, Color, FormattedExt, Text},
+    text1, Screen, XY,
+};
+
+use crate::rng::Rng;
+
+// the unit of width for the wave is fractions of the screen height; time is in seconds
+
+/// The width of one shift
+const SHIFT_WIDTH: f32 = 0.1;
+/// How many shifts there are in the wave
+const SHIFT_COUNT: usize = 3;
+/// How fast the wave moves down
+const WAVE_SPEED: f32 = 0.75;
+/// The width of the whole wave
+const WAVE_WIDTH: f32 = SHIFT_WIDTH * SHIFT_COUNT as f32;
+
+/// The shortest and longest pause between scrolling lines, in seconds
+const SCROLL_DELAY: (f32, f32) = (0.25, 0.75);
+
+/// A full-screen effect, as written in a [`Script`](super::Script).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Effect {
+    /// A wave of random characters sweeping down the screen, leaving a few scattered behind it
+    Wave,
+    /// Lines of text scrolling up from the bottom, one by one, pushing everything else off the screen
+    Scroll(Vec<String>),
+}
+
+fn rngat(seed: u64, x: usize, y: usize, xor: u64) -> Rng {
+    // the RNG's trivial to seed, so there's no need to keep anything around between frames
+    Rng::seeded(seed ^ xor ^ (x as u64) ^ (y as u64).rotate_left(32))
+}
+
+fn fadeat(seed: u64, x: usize, y: usize) -> f32 {
+    rngat(seed, x, y, 0x5CA1AB1E7E1ECA57).unit()
+}
+
+fn cellat(seed: u64, x: usize, y: usize) -> Cell {
+    const CHARS: [char; 92] = [
+        'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r',
+        's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J',
+        'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '1', '2',
+        '3', '4', '5', '6', '7', '8', '9', '0', '!', '@', '#', '$', '%', '^', '&', '*', '(', ')',
+        ',', '.', '<', '>', '[', ']', '{', '}', '`', '~', '/', '?', '\\', '|', '\'', '"', ';', ':',
+        '-', '_',
+    ];
+    let ch = *rngat(seed, x, y, 0xBE1A7EDDECEA5ED).pick(&CHARS);
+    let fg = *rngat(seed, x, y, 0xCA11AB1ECA55E77E).pick(&Color::all());
+    Cell::of(ch).fg(fg)
+}
+
+fn leaveat(seed: u64, x: usize, y: usize) -> bool {
+    rngat(seed, x, y, 0xBA11AD0FBADA55E5).chance(0.01)
+}
+
+/// Draw the characters a wave with the given seed left behind, starting `skip` rows down and drawn from the top.
+pub(super) fn render_residue(screen: &mut Screen, seed: u64, skip: usize) {
+    for y in skip..screen.size().y() {
+        for x in 0..screen.size().x() {
+            if leaveat(seed, x, y) {
+                screen[y - skip][x] = cellat(seed, x, y);
+            }
+        }
+    }
+}
+
+/// An [`Effect`] that's currently playing.
+pub(super) enum Playing {
+    Wave {
+        start: Instant,
+        seeds: [u64; SHIFT_COUNT],
+    },
+    Scroll {
+        lines: Vec<Text>,
+        /// How many lines have scrolled on so far
+        scroll: usize,
+        /// When the next line scrolls on
+        next: Instant,
+        /// What the last wave left behind, to be pushed off the screen
+        residue: Option<u64>,
+    },
+}
+
+impl Playing {
+    /// Start playing an effect, with whatever the last wave left behind still on screen
+    pub fn start(effect: &Effect, residue: Option<u64>, rng: &mut Rng) -> Self {
+        match effect {
+            Effect::Wave => Self::Wave {
+                start: Instant::now(),
+                seeds: [rng.next_u64(), rng.next_u64(), rng.next_u64()],
+            },
+            Effect::Scroll(lines) => Self::Scroll {
+                lines: lines.iter().map(|l| text1!(bold green "\n{}"(l))).collect(),
+                scroll: 0,
+                next: Instant::now(),
+                residue,
+            },
+        }
+    }
+
+    /// Move the effect along. Returns whether it needs redrawing.
+    pub fn tick(&mut self, rng: &mut Rng) -> bool {
+        match self {
+            // it's animated, so it always needs redrawing
+            Self::Wave { .. } => true,
+            Self::Scroll {
+                lines,
+                scroll,
+                next,
+                ..
+            } => {
+                if Instant::now() < *next {
+                    return false;
+                }
+                *scroll += 1;
+                let delay = if *scroll < lines.len() {
+                    // while we're still doing lines, pause a bit between each
+                    let (min, max) = SCROLL_DELAY;
+                    min + rng.unit() * (max - min)
+                } else {
+                    // once that's done, scroll the screen off pretty fast
+                    0.01
+                };
+                *next = Instant::now() + Duration::from_secs_f32(delay);
+                true
+            }
+        }
+    }
+
+    /// Whether the effect's over, on a screen this big
+    pub fn done(&self, size: XY) -> bool {
+        match self {
+            Self::Wave { start, .. } => {
+                start.elapsed().as_secs_f32() * WAVE_SPEED - WAVE_WIDTH > 1.0
+            }
+            Self::Scroll { lines, scroll, .. } => *scroll > size.y() + lines.len(),
+        }
+    }
+
+    /// What the effect leaves behind on screen once it's done, if anything
+    pub fn residue(&self) -> Option<u64> {
+        match self {
+            Self::Wave { seeds, .. } => Some(seeds[SHIFT_COUNT - 1]),
+            Self::Scroll { .. } => None,
+        }
+    }
+
+    pub fn render(&self, screen: &mut Screen) {
+        match self {
+            Self::Wave { start, seeds } => {
+                render_wave(screen, start.elapsed().as_secs_f32(), seeds)
+            }
+            Self::Scroll {
+                lines,
+                scroll,
+                residue,
+                ..
+            } => render_scroll(screen, lines, *scroll, *residue),
+        }
+    }
+}
+
+/// Draw the wave, `since_start` seconds in
+fn render_wave(screen: &mut Screen, since_start: f32, seeds: &[u64; SHIFT_COUNT]) {
+    let wave_lead = since_start * WAVE_SPEED;
+    let wave_trail = wave_lead - WAVE_WIDTH;
+    let last = seeds[SHIFT_COUNT - 1];
+    for y in 0..screen.size().y() {
+        let pct = y as f32 / screen.size().y() as f32;
+        if pct > wave_lead {
+            // everything past the leading edge is blank
+            continue;
+        } else if pct < wave_trail {
+            // almost everything past the trailing edge is blank
+            for x in 0..screen.size().x() {
+                if leaveat(last, x, y) {
+                    screen[y][x] = cellat(last, x, y);
+                }
+            }
+        } else {
+            let shift_pos = (wave_lead - pct) / SHIFT_WIDTH;
+            let from_shift = shift_pos as usize;
+            let to_shift = from_shift + 1;
+            let within = shift_pos.fract();
+
+            for x in 0..screen.size().x() {
+                if within < fadeat(seeds[from_shift], x, y) {
+                    if from_shift > 0 {
+                        screen[y][x] = cellat(seeds[from_shift], x, y);
+                    }
+                } else if to_shift < SHIFT_COUNT {
+                    screen[y][x] = cellat(seeds[to_shift], x, y);
+                } else if leaveat(seeds[from_shift], x, y) {
+                    screen[y][x] = cellat(seeds[from_shift], x, y);
+                }
+            }
+        }
+    }
+}
+
+/// Draw the scrolling text, with `scroll` lines scrolled on, over whatever the last wave left behind
+fn render_scroll(screen: &mut Screen, lines: &[Text], scroll: usize, residue: Option<u64>) {
+    // render the lines first so we know where to put the rest
+    let mut text = lines.to_vec();
+    text.resize(scroll, text1!("\n"));
+    let textinfo = screen.textbox(text).scroll_bottom(true).render();
+    let y_off = textinfo.lines;
+    if y_off <= screen.size().y() {
+        let y = screen.size().y() - y_off;
+        screen.horizontal(y).fill(cell!(green on_black '='));
+    }
+    if let Some(seed) = residue {
+        render_residue(screen, seed, y_off);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scroll_runs_off_screen() {
+        let mut rng = Rng::seeded(3);
+        let effect = Effect::Scroll(vec!["ONE".into(), "TWO".into()]);
+        let mut playing = Playing::start(&effect, Some(5), &mut rng);
+        assert_eq!(playing.residue(), None);
+        let size = XY(10, 4);
+        let mut ticks = 0;
+        while !playing.done(size) {
+            if let Playing::Scroll { next, .. } = &mut playing {
+                *next = Instant::now();
+            }
+            assert!(playing.tick(&mut rng));
+            ticks += 1;
+        }
+        // both lines, then the whole screen, then the divider
+        assert_eq!(ticks, 2 + 4 + 1);
+    }
+
+    #[test]
+    fn wave_leaves_residue() {
+        let mut rng = Rng::seeded(3);
+        let playing = Playing::start(&Effect::Wave, None, &mut rng);
+        assert!(playing.residue().is_some());
+        assert!(!playing.done(XY(10, 4)));
+    }
+}
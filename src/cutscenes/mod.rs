@@ -1,7 +1,512 @@
-//! Runners for the various cutscenes.
+//! Cutscenes: scripted sequences of text, effects, and questions for the player, shown instead of the game.
 //!
-//! There isn't really one single interface they could all fit, so no attempt is made.
+//! Each one is written as a [`Script`], which is just data, so new story beats don't need any new code. They're played
+//! by a [`Cutscene`], which is driven from outside much like an app: [`Cutscene::tick`] it regularly, pass it any
+//! [`Cutscene::input`], and [`Cutscene::render`] it whenever either says something changed. [`play`] does exactly that
+//! straight on an [`IoSystem`], for running one on its own. In-game,
+//! [`Event::PlayCutscene`](crate::event::Event::PlayCutscene) plays any script over the top of everything else.
+//!
+//! Any key fast-forwards through whatever's happening (an effect, text being revealed, or a pause), and escape skips
+//! ahead to the next question.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io, mem,
+    time::{Duration, Instant},
+};
+
+use tuig::io::{
+    fmt::{Color, Formatted, FormattedExt, Text},
+    text, text1,
+    ui::helpers::{TextInput, TextInputRequest},
+    Action, IoSystem, Key, Screen, XY,
+};
+
+use crate::{
+    constants::{gameplay::MAX_USERNAME, graphics::REVEAL_MS_PER_CHAR},
+    rng::Rng,
+    settings,
+};
+
+mod effects;
+mod intro;
+mod script;
+
+pub use effects::Effect;
+use effects::Playing;
+pub use intro::{run as intro, script as intro_script, Intro};
+pub use script::{Script, Step};
+
+/// The prompt in front of whatever the player types or picks, lined up with the text after speakers' names
+const PROMPT: &str = "         >  ";
+
+/// A line of text being revealed a character at a time
+struct Reveal {
+    line: Vec<Text>,
+    /// How many characters are in the line
+    len: usize,
+    /// How many characters are visible so far
+    shown: usize,
+    start: Instant,
+}
+
+impl Reveal {
+    /// How many characters should be visible by now
+    fn due(&self) -> usize {
+        let per_char = settings::current().delay(REVEAL_MS_PER_CHAR);
+        if per_char.is_zero() {
+            return self.len;
+        }
+        let due = self.start.elapsed().as_secs_f32() / per_char.as_secs_f32();
+        (due as usize).min(self.len)
+    }
+
+    /// The part of the line that's visible
+    fn visible(&self) -> Vec<Text> {
+        let mut left = self.shown;
+        let mut res = vec![];
+        for part in &self.line {
+            if left == 0 {
+                break;
+            }
+            let mut part = part.clone();
+            let count = part.text.chars().count();
+            if count > left {
+                part.text = part.text.chars().take(left).collect();
+            }
+            left -= count.min(left);
+            res.push(part);
+        }
+        res
+    }
+}
+
+/// What the player's been asked, and how they're answering so far
+enum Prompt {
+    /// Nothing, so they just have to wait
+    Nothing,
+    /// Something they type in
+    Text {
+        key: String,
+        max: usize,
+        input: TextInput,
+    },
+    /// A few options to pick between, and which one's selected
+    Choice {
+        key: String,
+        options: Vec<(String, Script)>,
+        sel: usize,
+    },
+}
+
+/// A [`Script`] being played.
+pub struct Cutscene {
+    /// What's left to happen
+    steps: VecDeque<Step>,
+    /// Everything that's been said so far
+    text: Vec<Text>,
+    /// The line currently being revealed, if any
+    reveal: Option<Reveal>,
+    /// The effect currently playing, if any
+    effect: Option<Playing>,
+    /// The seed of whatever the last wave left on screen, if it hasn't been cleared away yet
+    residue: Option<u64>,
+    prompt: Prompt,
+    /// Everything the player's answered so far, by key
+    answers: BTreeMap<String, String>,
+    /// When the next step happens
+    next: Instant,
+    rng: Rng,
+}
+
+impl Cutscene {
+    pub fn new(script: Script) -> Self {
+        Self {
+            steps: script.steps.into(),
+            text: vec![],
+            reveal: None,
+            effect: None,
+            residue: None,
+            prompt: Prompt::Nothing,
+            answers: BTreeMap::new(),
+            next: Instant::now(),
+            rng: Rng::from_time(),
+        }
+    }
+
+    /// What the player answered to the question saved under `key`, if they've answered it yet
+    pub fn answer(&self, key: &str) -> Option<&str> {
+        self.answers.get(key).map(|s| s.as_str())
+    }
+
+    /// Whether the whole script's played out
+    pub fn done(&self) -> bool {
+        self.steps.is_empty()
+            && self.effect.is_none()
+            && self.reveal.is_none()
+            && matches!(self.prompt, Prompt::Nothing)
+            && Instant::now() >= self.next
+    }
+
+    /// Format a line of text someone's saying, filling in the player's answers
+    fn line(&self, who: Option<&str>, text: &str) -> Vec<Text> {
+        let mut line = match who {
+            Some(who) => text!("{:>1$}: "(who, MAX_USERNAME)),
+            None => vec![],
+        };
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(len) => start + len,
+                None => break,
+            };
+            match self.answers.get(&rest[start + 1..end]) {
+                Some(answer) => {
+                    line.push(text1!(bold bright_white "{}"(&rest[..start])));
+                    line.push(text1!(cyan "{}"(answer)));
+                }
+                None => line.push(text1!(bold bright_white "{}"(&rest[..=end]))),
+            }
+            rest = &rest[end + 1..];
+        }
+        line.push(text1!(bold bright_white "{}"(rest)));
+        line.push(text1!("\n"));
+        line
+    }
+
+    /// Start a step. Returns whether it needs redrawing.
+    fn start(&mut self, step: Step) -> bool {
+        match step {
+            Step::Wait(millis) => {
+                self.next = Instant::now() + settings::current().delay(millis);
+                return false;
+            }
+            Step::Say { who, text } => {
+                let line = self.line(who.as_deref(), &text);
+                let len = line.iter().map(|t| t.text.chars().count()).sum();
+                self.reveal = Some(Reveal {
+                    line,
+                    len,
+                    shown: 0,
+                    start: Instant::now(),
+                });
+            }
+            Step::Clear => {
+                self.text.clear();
+                self.residue = None;
+            }
+            Step::Effect(effect) => {
+                self.effect = Some(Playing::start(&effect, self.residue.take(), &mut self.rng));
+            }
+            Step::Ask { key, max } => {
+                let mut input = TextInput::new(PROMPT, 0);
+                input.set_complete("(type now)".into());
+                self.prompt = Prompt::Text { key, max, input };
+            }
+            Step::Choose { key, options } => {
+                self.prompt = Prompt::Choice {
+                    key,
+                    options,
+                    sel: 0,
+                };
+            }
+        }
+        true
+    }
+
+    /// Move the cutscene along, given the size of the screen it's being drawn on. Returns whether it needs redrawing.
+    pub fn tick(&mut self, size: XY) -> bool {
+        let mut changed = false;
+        loop {
+            if let Some(effect) = &mut self.effect {
+                changed |= effect.tick(&mut self.rng);
+                if !effect.done(size) {
+                    return changed;
+                }
+                self.residue = effect.residue();
+                self.effect = None;
+                changed = true;
+            }
+            if let Some(reveal) = &mut self.reveal {
+                let due = reveal.due();
+                changed |= due != reveal.shown;
+                reveal.shown = due;
+                if due < reveal.len {
+                    return changed;
+                }
+                self.text.append(&mut reveal.line);
+                self.reveal = None;
+            }
+            if !matches!(self.prompt, Prompt::Nothing) || Instant::now() < self.next {
+                return changed;
+            }
+            match self.steps.pop_front() {
+                Some(step) => changed |= self.start(step),
+                None => return changed,
+            }
+        }
+    }
+
+    /// Finish whatever's happening right now. If `skip`, keep going until the next question.
+    fn fast_forward(&mut self, skip: bool) {
+        loop {
+            if let Some(effect) = self.effect.take() {
+                self.residue = effect.residue();
+            }
+            if let Some(reveal) = self.reveal.take() {
+                self.text.extend(reveal.line);
+            }
+            let asks = |s: &Step| matches!(s, Step::Ask { .. } | Step::Choose { .. });
+            if !skip
+                || !matches!(self.prompt, Prompt::Nothing)
+                || self.steps.front().map(asks).unwrap_or(true)
+            {
+                break;
+            }
+            let step = self.steps.pop_front().unwrap();
+            self.start(step);
+        }
+        self.next = Instant::now();
+    }
+
+    /// The player's answered whatever they were asked
+    fn answered(&mut self, answer: &str) {
+        let (key, answer, then) = match mem::replace(&mut self.prompt, Prompt::Nothing) {
+            Prompt::Nothing => return,
+            Prompt::Text { key, max, .. } => {
+                (key, answer.trim().chars().take(max).collect(), vec![])
+            }
+            Prompt::Choice {
+                key, mut options, ..
+            } => {
+                let then = match options.iter().position(|(opt, _)| opt == answer) {
+                    Some(idx) => options.swap_remove(idx).1.steps,
+                    None => vec![],
+                };
+                (key, answer.to_owned(), then)
+            }
+        };
+        self.text
+            .extend(text!("{}"(PROMPT), cyan "{}"(answer), "\n"));
+        for step in then.into_iter().rev() {
+            self.steps.push_front(step);
+        }
+        self.answers.insert(key, answer);
+        self.next = Instant::now();
+    }
+
+    /// Handle the player's input. Returns whether it needs redrawing.
+    pub fn input(&mut self, a: Action) -> bool {
+        match &mut self.prompt {
+            Prompt::Nothing => match a {
+                Action::KeyPress { key: Key::Escape } => {
+                    self.fast_forward(true);
+                    true
+                }
+                Action::KeyPress { .. } => {
+                    self.fast_forward(false);
+                    true
+                }
+                _ => false,
+            },
+            Prompt::Text { input, .. } => match input.action(a) {
+                // there's nothing to autocomplete
+                TextInputRequest::Autocomplete | TextInputRequest::Nothing => false,
+                TextInputRequest::Redraw => true,
+                TextInputRequest::Line(line) if line.trim().is_empty() => true,
+                TextInputRequest::Line(line) => {
+                    self.answered(&line);
+                    true
+                }
+            },
+            Prompt::Choice { options, sel, .. } => match a {
+                Action::KeyPress { key: Key::Left } if *sel > 0 => {
+                    *sel -= 1;
+                    true
+                }
+                Action::KeyPress { key: Key::Right } if *sel + 1 < options.len() => {
+                    *sel += 1;
+                    true
+                }
+                Action::KeyPress { key: Key::Enter } => {
+                    let choice = options[*sel].0.clone();
+                    self.answered(&choice);
+                    true
+                }
+                _ => false,
+            },
+        }
+    }
+
+    pub fn render(&self, screen: &mut Screen) {
+        if let Some(effect) = &self.effect {
+            effect.render(screen);
+            return;
+        }
+        if let Some(seed) = self.residue {
+            effects::render_residue(screen, seed, 0);
+        }
+        let mut text = self.text.clone();
+        if let Some(reveal) = &self.reveal {
+            text.extend(reveal.visible());
+        }
+        match &self.prompt {
+            Prompt::Nothing => (),
+            Prompt::Text { input, .. } => {
+                let mut line = input.render();
+                for elem in &mut line {
+                    if elem.get_fmt().fg == Color::BrightWhite {
+                        elem.get_fmt_mut().fg = Color::Cyan;
+                    }
+                }
+                text.extend(line);
+            }
+            Prompt::Choice { options, sel, .. } => {
+                text.push(text1!("{}"(PROMPT)));
+                for (i, (opt, _)) in options.iter().enumerate() {
+                    if i > 0 {
+                        text.push(text1!("  "));
+                    }
+                    let mut opt = text1!(bold bright_white "{}"(opt));
+                    if i == *sel {
+                        opt = opt.underline();
+                    }
+                    text.push(opt);
+                }
+            }
+        }
+        if let Some(last) = text.last_mut() {
+            // trim trailing newline
+            last.text = last.text.trim_end().into();
+        }
+        screen
+            .textbox(text)
+            .scroll_bottom(true)
+            .indent(MAX_USERNAME + 2)
+            .first_indent(0);
+    }
+}
+
+/// Play a cutscene straight on an [`IoSystem`], until it's done.
+pub fn play(io: &mut dyn IoSystem, screen: &mut Screen, scene: &mut Cutscene) -> io::Result<()> {
+    let mut redraw = true;
+    while !scene.done() {
+        while let Some(action) = io.poll_input()? {
+            match action {
+                Action::Closed => return Err(io::ErrorKind::Interrupted.into()),
+                Action::Redraw => redraw = true,
+                other => redraw |= scene.input(other),
+            }
+        }
+        redraw |= scene.tick(io.size());
+        if redraw {
+            screen.resize(io.size());
+            scene.render(screen);
+            io.draw(screen)?;
+            redraw = false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SIZE: XY = XY(40, 10);
+
+    /// Fast-forward through everything until the player's asked something or it's over
+    fn skim(scene: &mut Cutscene) {
+        scene.tick(SIZE);
+        while matches!(scene.prompt, Prompt::Nothing) && !scene.done() {
+            scene.input(Action::KeyPress {
+                key: Key::Char(' '),
+            });
+            scene.tick(SIZE);
+        }
+    }
+
+    fn said(scene: &Cutscene) -> String {
+        scene.text.iter().map(|t| t.text.as_str()).collect()
+    }
+
+    #[test]
+    fn answers_fill_in_lines() {
+        let mut scene = Cutscene::new(
+            Script::new()
+                .ask("name", 5)
+                .wait(1000)
+                .say("admin", "hi {name}! {missing}"),
+        );
+        skim(&mut scene);
+        assert!(matches!(scene.prompt, Prompt::Text { .. }));
+        scene.answered("  n30n_the_great ");
+        assert_eq!(scene.answer("name"), Some("n30n_"));
+        skim(&mut scene);
+        assert!(scene.done());
+        assert!(said(&scene).ends_with("     admin: hi n30n_! {missing}\n"));
+    }
+
+    #[test]
+    fn choices_branch() {
+        let mut scene = Cutscene::new(
+            Script::new()
+                .choose(
+                    "pick",
+                    vec![
+                        ("a", Script::new().narrate("went a")),
+                        ("b", Script::new().narrate("went b")),
+                    ],
+                )
+                .narrate("after"),
+        );
+        skim(&mut scene);
+        assert!(!scene.input(Action::KeyPress { key: Key::Left }));
+        assert!(scene.input(Action::KeyPress { key: Key::Right }));
+        assert!(scene.input(Action::KeyPress { key: Key::Enter }));
+        skim(&mut scene);
+        assert_eq!(scene.answer("pick"), Some("b"));
+        assert_eq!(said(&scene), format!("{}b\nwent b\nafter\n", PROMPT));
+    }
+
+    #[test]
+    fn text_is_revealed_gradually() {
+        let mut scene = Cutscene::new(Script::new().narrate("a fairly long line of text"));
+        scene.tick(SIZE);
+        let reveal = scene.reveal.as_ref().expect("line should be revealing");
+        assert!(reveal.shown < reveal.len);
+        assert!(scene.input(Action::KeyPress {
+            key: Key::Char('x')
+        }));
+        assert!(scene.reveal.is_none());
+        assert_eq!(said(&scene), "a fairly long line of text\n");
+        assert!(scene.done());
+    }
+
+    #[test]
+    fn escape_skips_to_question() {
+        let mut scene = Cutscene::new(
+            Script::new()
+                .effect(Effect::Wave)
+                .wait(5000)
+                .say("admin", "before")
+                .ask("name", 10)
+                .say("admin", "after"),
+        );
+        scene.tick(SIZE);
+        assert!(scene.effect.is_some());
+        assert!(scene.input(Action::KeyPress { key: Key::Escape }));
+        scene.tick(SIZE);
+        assert!(matches!(scene.prompt, Prompt::Text { .. }));
+        assert!(scene.residue.is_some());
+        assert_eq!(said(&scene), "     admin: before\n");
+    }
 
-// TODO: #35
-// mod intro;
-// pub use intro::run as intro;
+    #[test]
+    fn clear_wipes_screen() {
+        let mut scene = Cutscene::new(Script::new().narrate("gone").clear().narrate("here"));
+        skim(&mut scene);
+        assert_eq!(said(&scene), "here\n");
+    }
+}
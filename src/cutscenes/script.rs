@@ -0,0 +1,332 @@
+//! Cutscenes written as data: a list of [`Step`]s, built up with [`Script`]'s builder methods, or read from text with
+//! [`Script::parse`].
+//!
+//! Written out, a script is one step per line, with `#` starting a comment:
+//!
+//! ```text
+//! effect wave
+//! wait 1000
+//! say admin: what can I call you?
+//! ask name 10
+//! narrate (they think about it)
+//! clear
+//! effect scroll
+//!     LOADING KERNEL...
+//!     SPAWNING DAEMON...
+//! choose played
+//!     option yes
+//!         say admin: cool
+//!     option no
+//!         say admin: you'll pick it up
+//! ```
+//!
+//! A scroll's lines, a choice's options, and each option's script are indented under whatever they belong to.
+
+use super::Effect;
+
+/// One thing that happens in a cutscene. Each step starts once the one before it has finished.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Do nothing for this many milliseconds (at normal text speed)
+    Wait(usize),
+    /// Show a line of text, revealed a character at a time. `{key}` is replaced with the player's answer to that key.
+    Say { who: Option<String>, text: String },
+    /// Clear everything off the screen
+    Clear,
+    /// Play a full-screen effect until it's done
+    Effect(Effect),
+    /// Have the player type an answer, of up to `max` characters, saved under `key`. In-game, answering `name` also
+    /// names the player.
+    Ask { key: String, max: usize },
+    /// Have the player pick one of the options, saved under `key`, then carry on with that option's script before
+    /// the rest of this one
+    Choose {
+        key: String,
+        options: Vec<(String, Script)>,
+    },
+}
+
+/// A cutscene's script, i.e. everything that happens in it, in order.
+///
+/// ```ignore
+/// Script::new()
+///     .say("admin", "what can I call you?")
+///     .ask("name", 10)
+///     .wait(1000)
+///     .say("admin", "you're {name}?")
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Script {
+    pub steps: Vec<Step>,
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Do nothing for a bit
+    pub fn wait(mut self, millis: usize) -> Self {
+        self.steps.push(Step::Wait(millis));
+        self
+    }
+
+    /// Have someone say something
+    pub fn say(mut self, who: &str, text: &str) -> Self {
+        self.steps.push(Step::Say {
+            who: Some(who.into()),
+            text: text.into(),
+        });
+        self
+    }
+
+    /// Show some text that nobody in particular is saying
+    pub fn narrate(mut self, text: &str) -> Self {
+        self.steps.push(Step::Say {
+            who: None,
+            text: text.into(),
+        });
+        self
+    }
+
+    /// Clear the screen
+    pub fn clear(mut self) -> Self {
+        self.steps.push(Step::Clear);
+        self
+    }
+
+    /// Play a full-screen effect
+    pub fn effect(mut self, effect: Effect) -> Self {
+        self.steps.push(Step::Effect(effect));
+        self
+    }
+
+    /// Ask the player to type something
+    pub fn ask(mut self, key: &str, max: usize) -> Self {
+        self.steps.push(Step::Ask {
+            key: key.into(),
+            max,
+        });
+        self
+    }
+
+    /// Ask the player to pick an option, each with its own script to run before carrying on
+    ///
+    /// Panics if there aren't any options, since the player would have nothing to pick.
+    pub fn choose(mut self, key: &str, options: Vec<(&str, Script)>) -> Self {
+        assert!(!options.is_empty(), "choice {} has no options", key);
+        let options = options
+            .into_iter()
+            .map(|(name, script)| (name.to_owned(), script))
+            .collect();
+        self.steps.push(Step::Choose {
+            key: key.into(),
+            options,
+        });
+        self
+    }
+}
+
+/// A line of a written script that actually says something, as `(line number, indentation, text)`
+type Line<'s> = (usize, usize, &'s str);
+
+impl Script {
+    /// Read a script from its written form (see the [module docs](self)), saying which line's wrong if it can't be.
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let lines: Vec<Line> = src
+            .lines()
+            .enumerate()
+            .map(|(n, l)| (n + 1, l.len() - l.trim_start().len(), l.trim()))
+            .filter(|(_, _, l)| !l.is_empty() && !l.starts_with('#'))
+            .collect();
+        let mut at = 0;
+        let script = Self::block(&lines, &mut at, None)?;
+        match lines.get(at) {
+            Some((n, _, _)) => Err(format!("line {}: unexpected indentation", n)),
+            None => Ok(script),
+        }
+    }
+
+    /// Read the lines more indented than `parent` (or all of them, at the top level) into a script, starting at `at`
+    /// and leaving it at the first line that isn't part of it.
+    fn block(lines: &[Line], at: &mut usize, parent: Option<usize>) -> Result<Self, String> {
+        let indent = match lines.get(*at) {
+            Some(&(_, indent, _)) if parent.map(|p| indent > p).unwrap_or(true) => indent,
+            // nothing indented under the parent, so it's empty
+            _ => return Ok(Self::new()),
+        };
+        let mut script = Self::new();
+        while let Some(&(n, this, text)) = lines.get(*at) {
+            if this < indent {
+                break;
+            } else if this > indent {
+                return Err(format!("line {}: unexpected indentation", n));
+            }
+            *at += 1;
+            let step =
+                Self::step(lines, at, text, indent).map_err(|e| format!("line {}: {}", n, e))?;
+            script.steps.push(step);
+        }
+        Ok(script)
+    }
+
+    /// Read one step, starting from the line `text`, along with anything indented under it
+    fn step(lines: &[Line], at: &mut usize, text: &str, indent: usize) -> Result<Step, String> {
+        let (cmd, rest) = match text.split_once(char::is_whitespace) {
+            Some((cmd, rest)) => (cmd, rest.trim()),
+            None => (text, ""),
+        };
+        // everything indented under this line, as (line number, text)
+        let mut children = || {
+            let start = *at;
+            while matches!(lines.get(*at), Some(&(_, i, _)) if i > indent) {
+                *at += 1;
+            }
+            &lines[start..*at]
+        };
+        let step = match cmd {
+            "wait" => Step::Wait(
+                rest.parse()
+                    .map_err(|_| format!("wait needs milliseconds, not `{}`", rest))?,
+            ),
+            "say" => match rest.split_once(':') {
+                Some((who, text)) if !who.trim().is_empty() => Step::Say {
+                    who: Some(who.trim().into()),
+                    text: text.trim().into(),
+                },
+                _ => return Err("say needs `<who>: <text>`".into()),
+            },
+            "narrate" => Step::Say {
+                who: None,
+                text: rest.into(),
+            },
+            "clear" => Step::Clear,
+            "effect" => match rest {
+                "wave" => Step::Effect(Effect::Wave),
+                "scroll" => Step::Effect(Effect::Scroll(
+                    children().iter().map(|(_, _, l)| l.to_string()).collect(),
+                )),
+                other => return Err(format!("unknown effect `{}`", other)),
+            },
+            "ask" => {
+                let mut args = rest.split_whitespace();
+                match (args.next(), args.next().map(str::parse), args.next()) {
+                    (Some(key), Some(Ok(max)), None) => Step::Ask {
+                        key: key.into(),
+                        max,
+                    },
+                    _ => return Err("ask needs `<key> <max length>`".into()),
+                }
+            }
+            "choose" if !rest.is_empty() => {
+                let mut options = vec![];
+                let under = match lines.get(*at) {
+                    Some(&(_, i, _)) if i > indent => i,
+                    _ => return Err("choose needs options indented under it".into()),
+                };
+                while let Some(&(n, i, text)) = lines.get(*at) {
+                    if i <= indent {
+                        break;
+                    }
+                    let name = match text.strip_prefix("option ") {
+                        Some(name) if i == under => name.trim(),
+                        _ => return Err(format!("expected `option <name>` on line {}", n)),
+                    };
+                    *at += 1;
+                    options.push((name.to_owned(), Self::block(lines, at, Some(under))?));
+                }
+                Step::Choose {
+                    key: rest.into(),
+                    options,
+                }
+            }
+            "choose" => return Err("choose needs a key".into()),
+            other => return Err(format!("unknown step `{}`", other)),
+        };
+        Ok(step)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_every_step() {
+        let written = "
+            # comments and blank lines don't count
+
+            effect wave
+            wait 1000
+            say admin: what can I call you?
+            ask name 10
+            narrate (they think about it)
+            clear
+            effect scroll
+                LOADING KERNEL...
+                SPAWNING DAEMON...
+            choose played
+                option yes
+                    say admin: cool
+                option no
+                option maybe
+                    choose really
+                        option no
+                    wait 5
+            say admin: ok
+        ";
+        let built = Script::new()
+            .effect(Effect::Wave)
+            .wait(1000)
+            .say("admin", "what can I call you?")
+            .ask("name", 10)
+            .narrate("(they think about it)")
+            .clear()
+            .effect(Effect::Scroll(vec![
+                "LOADING KERNEL...".into(),
+                "SPAWNING DAEMON...".into(),
+            ]))
+            .choose(
+                "played",
+                vec![
+                    ("yes", Script::new().say("admin", "cool")),
+                    ("no", Script::new()),
+                    (
+                        "maybe",
+                        Script::new()
+                            .choose("really", vec![("no", Script::new())])
+                            .wait(5),
+                    ),
+                ],
+            )
+            .say("admin", "ok");
+        assert_eq!(Script::parse(written), Ok(built));
+    }
+
+    #[test]
+    fn errors_say_where() {
+        let err = |src| Script::parse(src).unwrap_err();
+        assert_eq!(
+            err("wait\nwait 1"),
+            "line 1: wait needs milliseconds, not ``"
+        );
+        assert_eq!(err("clear\nshout hi"), "line 2: unknown step `shout`");
+        assert_eq!(err("say nobody"), "line 1: say needs `<who>: <text>`");
+        assert_eq!(err("ask name"), "line 1: ask needs `<key> <max length>`");
+        assert_eq!(err("clear\n  clear"), "line 2: unexpected indentation");
+        assert_eq!(
+            err("choose x\n  yes"),
+            "line 1: expected `option <name>` on line 2"
+        );
+        assert_eq!(
+            err("choose x\nclear"),
+            "line 1: choose needs options indented under it"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn choices_need_options() {
+        Script::new().choose("x", vec![]);
+    }
+}
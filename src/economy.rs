@@ -0,0 +1,308 @@
+//! The CyberZone's economy: the ration tokens everyone lives and dies by, the player's wallet, and the deals NPCs make.
+
+use std::{
+    fmt,
+    sync::{atomic::Ordering, Arc, Mutex},
+};
+
+use tuig::Replies;
+
+use crate::{
+    clock::{self, GameTime},
+    constants::gameplay::{MAX_HARDWARE, PLAYER_HOST, STARTING_TOKENS},
+    event::Event,
+    tools::Tool,
+    world::World,
+};
+
+/// An amount of ration tokens. Displays as e.g. `120tk`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Tokens(pub i64);
+
+impl fmt::Display for Tokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}tk", self.0)
+    }
+}
+
+/// A single change in the player's balance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transfer {
+    /// How much was added (or, if negative, taken away)
+    pub amount: Tokens,
+    /// What it was for
+    pub memo: String,
+    /// When it happened
+    pub at: GameTime,
+}
+
+struct Ledger {
+    balance: Tokens,
+    history: Vec<Transfer>,
+}
+
+/// The player's wallet, shared between the UI and anything that pays (or charges) them.
+pub struct Wallet(Mutex<Ledger>);
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self::new(Tokens(STARTING_TOKENS))
+    }
+}
+
+impl Wallet {
+    /// Create a wallet with some tokens already in it
+    pub fn new(balance: Tokens) -> Self {
+        Self(Mutex::new(Ledger {
+            balance,
+            history: vec![],
+        }))
+    }
+
+    /// How many tokens are in the wallet right now
+    pub fn balance(&self) -> Tokens {
+        self.0.lock().unwrap().balance
+    }
+
+    /// Every transfer in or out of the wallet, oldest first
+    pub fn history(&self) -> Vec<Transfer> {
+        self.0.lock().unwrap().history.clone()
+    }
+
+    fn record(ledger: &mut Ledger, amount: i64, memo: &str) -> Transfer {
+        ledger.balance.0 += amount;
+        let transfer = Transfer {
+            amount: Tokens(amount),
+            memo: memo.into(),
+            at: clock::now(),
+        };
+        ledger.history.push(transfer.clone());
+        transfer
+    }
+
+    /// Add tokens to the wallet
+    pub fn deposit(&self, amount: Tokens, memo: &str) -> Transfer {
+        Self::record(&mut self.0.lock().unwrap(), amount.0, memo)
+    }
+
+    /// Take tokens out of the wallet, if there are enough
+    pub fn withdraw(&self, amount: Tokens, memo: &str) -> Result<Transfer, String> {
+        let mut ledger = self.0.lock().unwrap();
+        if ledger.balance < amount {
+            return Err(format!("need {}, only have {}", amount, ledger.balance));
+        }
+        Ok(Self::record(&mut ledger, -amount.0, memo))
+    }
+}
+
+/// How much a stolen file is worth to a fence, based on where it came from and what's in it.
+pub fn appraise(path: &str, contents: &str) -> Tokens {
+    let path = path.to_lowercase();
+    let base = if path.contains("payroll") || path.contains("finance") {
+        200
+    } else if path.contains("cred") || contents.contains("password") {
+        150
+    } else if path.contains("/projects/") {
+        120
+    } else if path.ends_with(".eml") {
+        20
+    } else if path.contains("log") {
+        10
+    } else {
+        5
+    };
+    Tokens(base + contents.len() as i64 / 100)
+}
+
+/// A trade an NPC will make with the player, through a chat option.
+pub enum Deal {
+    /// The NPC sells the player a tool, if they don't already have it
+    Sell(Arc<dyn Tool>, Tokens),
+    /// The NPC buys every stolen file the player has that hasn't been sold before, at [`appraise`]d prices
+    BuyData,
+    /// The NPC upgrades the player's hardware, if it's not as good as it gets already
+    Upgrade(Tokens),
+}
+
+impl Deal {
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn sell(tool: impl Tool + 'static, price: i64) -> Self {
+        Self::Sell(Arc::new(tool), Tokens(price))
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn upgrade(price: i64) -> Self {
+        Self::Upgrade(Tokens(price))
+    }
+
+    /// Find every file on the player's machine which matches one on some other machine, and hasn't been sold yet.
+    ///
+    /// Returns the path on the player's machine, along with the file's value.
+    fn stolen(world: &World) -> Vec<(String, String, Tokens)> {
+        let mut found = vec![];
+        for (path, file) in world.player().files() {
+            if file.contents.is_empty() || world.fenced.contains(&file.contents) {
+                continue;
+            }
+            let source = world
+                .hosts
+                .iter()
+                .filter(|h| h.key() != PLAYER_HOST)
+                .flat_map(|h| h.value().files())
+                .find(|(_, f)| f.contents == file.contents);
+            if let Some((original, _)) = source {
+                let value = appraise(&original, &file.contents);
+                found.push((path, file.contents, value));
+            }
+        }
+        found
+    }
+
+    /// Try to make the trade with the named NPC, announcing any money that changes hands.
+    pub fn make(
+        &self,
+        npc: &str,
+        world: &World,
+        replies: &mut Replies<Event>,
+    ) -> Result<(), String> {
+        let transfer = match self {
+            Self::Sell(tool, price) => {
+                let player = world.player();
+                if player.tools.contains_key(tool.name()) {
+                    return Err(format!("already have {}", tool.name()));
+                }
+                let memo = format!("bought {} from {}", tool.name(), npc);
+                let transfer = world.wallet.withdraw(*price, &memo)?;
+                player.tools.insert(tool.name().into(), tool.clone());
+                transfer
+            }
+            Self::BuyData => {
+                let stolen = Self::stolen(world);
+                if stolen.is_empty() {
+                    return Err("nothing worth buying".into());
+                }
+                let player = world.player();
+                let (mut total, mut sold) = (0, 0);
+                for (path, contents, value) in &stolen {
+                    // it's a sale, not a copy; the player doesn't get to keep it, and doesn't get paid for anything
+                    // that's gone missing in the meantime
+                    if player.remove(path).is_err() {
+                        continue;
+                    }
+                    world.fenced.insert(contents.clone());
+                    total += value.0;
+                    sold += 1;
+                }
+                if sold == 0 {
+                    return Err("nothing worth buying".into());
+                }
+                let memo = format!("sold {} files to {}", sold, npc);
+                world.wallet.deposit(Tokens(total), &memo)
+            }
+            Self::Upgrade(price) => {
+                if world.hardware.load(Ordering::Relaxed) >= MAX_HARDWARE {
+                    return Err("hardware's already maxed out".into());
+                }
+                let memo = format!("hardware upgrade from {}", npc);
+                let transfer = world.wallet.withdraw(*price, &memo)?;
+                world.hardware.fetch_add(1, Ordering::Relaxed);
+                transfer
+            }
+        };
+        replies.queue(Event::Transfer(transfer));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{machine::Machine, tools::Cat};
+
+    use super::*;
+
+    fn world() -> World {
+        let world = World::new(Default::default());
+        let corp = Machine::default();
+        corp.mkdir("/srv/finance/", true).unwrap();
+        corp.write("/srv/finance/payroll.csv", "name,salary\n".into())
+            .unwrap();
+        world.hosts.insert("corp".into(), Arc::new(corp));
+        world
+    }
+
+    #[test]
+    fn wallet_tracks_transfers() {
+        let wallet = Wallet::new(Tokens(10));
+        wallet.deposit(Tokens(5), "found it on the floor");
+        assert_eq!(wallet.balance(), Tokens(15));
+        wallet.withdraw(Tokens(15), "lunch").unwrap();
+        assert_eq!(wallet.balance(), Tokens(0));
+        assert!(wallet.withdraw(Tokens(1), "more lunch").is_err());
+        let amounts: Vec<_> = wallet.history().iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![Tokens(5), Tokens(-15)]);
+    }
+
+    #[test]
+    fn appraisal_prefers_juicy_files() {
+        assert!(appraise("/srv/finance/payroll.csv", "") > appraise("/home/x/mail/0.eml", ""));
+        assert!(appraise("/home/x/notes.txt", "my password") > appraise("/home/x/notes.txt", "hi"));
+    }
+
+    #[test]
+    fn selling_tool_charges_once() {
+        let world = world();
+        let deal = Deal::sell(Cat, 15);
+        let mut replies = Replies::default();
+        deal.make("fence", &world, &mut replies).unwrap();
+        assert!(world.player().tools.contains_key("cat"));
+        assert_eq!(world.wallet.balance(), Tokens(STARTING_TOKENS - 15));
+        assert!(matches!(replies._messages(), [Event::Transfer(t)] if t.amount == Tokens(-15)));
+        assert!(deal.make("fence", &world, &mut Replies::default()).is_err());
+        assert_eq!(world.wallet.balance(), Tokens(STARTING_TOKENS - 15));
+    }
+
+    #[test]
+    fn selling_tool_needs_money() {
+        let world = world();
+        let deal = Deal::sell(Cat, STARTING_TOKENS + 1);
+        assert!(deal.make("fence", &world, &mut Replies::default()).is_err());
+        assert!(!world.player().tools.contains_key("cat"));
+    }
+
+    #[test]
+    fn upgrades_stop_at_max() {
+        let world = world();
+        world.wallet.deposit(Tokens(1000), "rich uncle");
+        for _ in 0..MAX_HARDWARE {
+            Deal::upgrade(10)
+                .make("fence", &world, &mut Replies::default())
+                .unwrap();
+        }
+        assert_eq!(world.hardware.load(Ordering::Relaxed), MAX_HARDWARE);
+        let balance = world.wallet.balance();
+        assert!(Deal::upgrade(10)
+            .make("fence", &world, &mut Replies::default())
+            .is_err());
+        assert_eq!(world.wallet.balance(), balance);
+    }
+
+    #[test]
+    fn buying_data_takes_stolen_files() {
+        let world = world();
+        let player = world.player();
+        player.write("/loot", "name,salary\n".into()).unwrap();
+        player.write("/mine", "my own stuff".into()).unwrap();
+        let mut replies = Replies::default();
+        Deal::BuyData.make("fence", &world, &mut replies).unwrap();
+        let value = appraise("/srv/finance/payroll.csv", "name,salary\n");
+        assert_eq!(world.wallet.balance(), Tokens(STARTING_TOKENS + value.0));
+        assert!(player.entry("/loot").is_err());
+        assert!(player.entry("/mine").is_ok());
+
+        // the same data can't be sold twice
+        player.write("/loot", "name,salary\n".into()).unwrap();
+        assert!(Deal::BuyData
+            .make("fence", &world, &mut Replies::default())
+            .is_err());
+    }
+}
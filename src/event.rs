@@ -3,7 +3,10 @@ use std::sync::{Arc, Mutex};
 
 use tuig::{io::fmt::Text, Message};
 
-use crate::{app::App, tools::Tool};
+use crate::{
+    app::App, clock::GameTime, cutscenes::Script, economy::Transfer, jobs::Job, settings::Settings,
+    tools::Tool,
+};
 
 /// Convenience for the things that pass trait objects around, but only one of them.
 pub struct Bundle<T>(Arc<Mutex<Option<T>>>);
@@ -65,6 +68,24 @@ trait_bundle! {
     add_tab(App) => AddTab,
 }
 
+/// Identifies one run of a command: which terminal session it was run in, and which of that session's jobs it is.
+///
+/// Commands can keep going in the background after they're done, so the session alone isn't enough to tell whether
+/// some output belongs to the command that's running now.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pid {
+    pub session: usize,
+    pub job: usize,
+}
+
+/// A file sent over chat, detached from whatever machine it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// The file's name, without any directories
+    pub name: String,
+    pub contents: String,
+}
+
 /// A single thing which has happened, which an [`Agent`] may or may not want to respond to.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -74,18 +95,30 @@ pub enum Event {
 
     /// Create a new tab on the player's UI
     AddTab(BundledApp),
+    /// Show one of the player's tabs that's been locked until now, by its name
+    UnlockTab(String),
+    /// Play a cutscene instead of the game, until it's over
+    PlayCutscene(Script),
 
     /// Something added a command to the player's CLI
     InstallTool(BundledTool),
+    /// The player has entered a command line into one of their terminal sessions, just before it's run as `pid`
+    CommandEntered { pid: Pid, line: String },
     /// A line of output from a running command
-    CommandOutput(Vec<Text>),
-    /// Command has changed the CLI's directory to the given (absolute) one
-    ChangeDir(String),
-    /// The command that was running is done and the prompt can reappear.
+    CommandOutput { pid: Pid, line: Vec<Text> },
+    /// Command has changed its session's directory to the given (absolute) one
+    ChangeDir { pid: Pid, dir: String },
+    /// Command has logged its session into a machine as some user, e.g. an exploit getting in or `exit` going home
+    Login {
+        pid: Pid,
+        host: String,
+        user: String,
+    },
+    /// The command's done and its session's prompt can reappear.
     ///
     /// Note this doesn't kill the agent or stop more output from coming; it just tells the console to display the
     /// prompt for the next command. (This allows commands to run in the 'background'.)
-    CommandDone,
+    CommandDone(Pid),
 
     /// The player has sent a chat message to some NPC
     PlayerChatMessage { to: String, text: String },
@@ -95,12 +128,50 @@ pub enum Event {
         text: String,
         options: Vec<String>,
     },
+    /// Some NPC has started typing a message to the player
+    NPCTyping { from: String },
+    /// Some NPC has ended the conversation with the player, at least for now
+    NPCClosedChat { from: String },
+    /// The player has sent a file to some NPC
+    PlayerSentFile { to: String, file: Attachment },
+    /// Some NPC has sent the player a file, which has already been saved at `path` on the player's machine
+    NPCSentFile {
+        from: String,
+        file: Attachment,
+        path: String,
+    },
+
+    /// The player has taken on a job from some NPC, which has to be done by the deadline (if any)
+    JobAccepted {
+        job: Job,
+        deadline: Option<GameTime>,
+    },
+    /// The set of finished objectives for a job has changed; `done` lines up with [`Job::objectives`]
+    JobProgress { id: String, done: Vec<bool> },
+    /// The player finished every objective of the job with the given ID in time
+    JobCompleted(String),
+    /// The player ran out of time for the job with the given ID, or someone sabotaged it
+    JobFailed(String),
+
+    /// Tokens have gone into or out of the player's wallet
+    Transfer(Transfer),
+
+    /// An NPC has joined (or left) the player's team
+    TeamChanged { name: String, joined: bool },
+    /// A team member is reporting in on a job they've been sent on
+    TeamReport { from: String, text: String },
+
+    /// The player has pinned some text (e.g. a line of chat or terminal output) to their notes
+    PinNote(String),
+
+    /// The player has changed their settings, which should be applied and saved
+    SettingsChanged(Settings),
 }
 
 impl Event {
     #[cfg_attr(coverage, no_coverage)]
-    pub fn output(line: Vec<Text>) -> Self {
-        Self::CommandOutput(line)
+    pub fn output(pid: Pid, line: Vec<Text>) -> Self {
+        Self::CommandOutput { pid, line }
     }
 
     #[cfg_attr(coverage, no_coverage)]
@@ -121,8 +192,11 @@ impl Event {
     }
 
     #[cfg_attr(coverage, no_coverage)]
-    pub fn cd(to: &str) -> Event {
-        Event::ChangeDir(to.into())
+    pub fn cd(pid: Pid, to: &str) -> Event {
+        Event::ChangeDir {
+            pid,
+            dir: to.into(),
+        }
     }
 }
 
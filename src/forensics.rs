@@ -0,0 +1,332 @@
+//! Corps keeping an eye on their own logs.
+//!
+//! Every [`FORENSICS_SECS`] of in-game time, each corp's security team goes over the logs on its machines, comparing
+//! them to what they saw last time. New lines pointing at the player's address are evidence; logs that have gone
+//! missing or got shorter mean someone's been covering their tracks, and not very carefully. Taking out just the lines
+//! that mention the player, and nothing that was there at the last audit, goes unnoticed.
+//!
+//! What each machine looked like at its last audit is kept in the [`World`], so it's saved along with the logs.
+
+use std::{collections::BTreeMap, fmt, str::FromStr, sync::Arc, time::Duration};
+
+use tuig::{Agent, ControlFlow, Replies};
+
+use crate::{
+    clock::{self, GameTime},
+    constants::gameplay::{CORP_NET, FORENSICS_SECS, LOG_DIR, PLAYER_ADDR, TRACE_REPUTATION},
+    event::Event,
+    machine::Machine,
+    net::Addr,
+    world::World,
+};
+
+/// Whether a line mentions an address, and not just one that starts the same way (like `10.0.1.23` for `10.0.1.2`)
+fn mentions(line: &str, addr: Addr) -> bool {
+    let addr = addr.to_string();
+    line.match_indices(&addr).any(|(i, _)| {
+        let before = line[..i].chars().next_back();
+        let after = line[i + addr.len()..].chars().next();
+        !matches!(before, Some(c) if c.is_ascii_digit() || c == '.')
+            && !matches!(after, Some(c) if c.is_ascii_digit())
+    })
+}
+
+/// What a security team remembers about a machine's logs, as of its last audit.
+///
+/// As text, that's how many lines mentioned the player, then `path=lines` for each log, all separated by spaces.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// How many lines each log had, by path
+    lines: BTreeMap<String, usize>,
+    /// How many of those lines mentioned the player's address
+    evidence: usize,
+}
+
+impl Snapshot {
+    fn of(machine: &Machine) -> Self {
+        let mut snapshot = Self::default();
+        for (path, file) in machine.files() {
+            if !path.starts_with(LOG_DIR) {
+                continue;
+            }
+            let lines = file.contents.lines();
+            snapshot.evidence += lines.clone().filter(|l| mentions(l, PLAYER_ADDR)).count();
+            snapshot.lines.insert(path, lines.count());
+        }
+        snapshot
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.evidence)?;
+        for (path, lines) in &self.lines {
+            write!(f, " {}={}", path, lines)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut parts = s.split_whitespace();
+        let bad = || format!("{} isn't a log snapshot", s);
+        let evidence = parts.next().and_then(|e| e.parse().ok()).ok_or_else(bad)?;
+        let mut lines = BTreeMap::new();
+        for part in parts {
+            let (path, count) = part.rsplit_once('=').ok_or_else(bad)?;
+            lines.insert(path.to_owned(), count.parse().map_err(|_| bad())?);
+        }
+        Ok(Self { lines, evidence })
+    }
+}
+
+/// What an audit turned up on one machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub host: String,
+    /// How many new log lines point at the player
+    pub evidence: usize,
+    /// Whether any logs were deleted or cut short since the last audit
+    pub tampered: bool,
+}
+
+/// The agent which audits every corp machine's logs every so often, and has the corps react to what they find.
+pub struct Forensics {
+    world: Arc<World>,
+    /// When the next audit's due
+    next: GameTime,
+}
+
+impl Forensics {
+    /// Start keeping an eye on every corp machine in the world, as of right now for any that haven't been audited
+    /// before (e.g. in a saved game)
+    pub fn new(world: Arc<World>) -> Self {
+        let forensics = Self {
+            world,
+            next: clock::now() + Duration::from_secs(FORENSICS_SECS),
+        };
+        for (host, machine) in forensics.corp_hosts() {
+            let audits = &forensics.world.audits;
+            audits.entry(host).or_insert_with(|| Snapshot::of(&machine));
+        }
+        forensics
+    }
+
+    /// Every machine on a corp subnet, sorted by hostname
+    fn corp_hosts(&self) -> Vec<(String, Arc<Machine>)> {
+        let mut hosts: Vec<_> = self
+            .world
+            .hosts
+            .iter()
+            .filter(|h| {
+                let addr = self.world.net.addr_of(h.key());
+                matches!(addr, Some(Addr([a, b, _, _])) if [a, b] == CORP_NET)
+            })
+            .map(|h| (h.key().clone(), h.value().clone()))
+            .collect();
+        hosts.sort_unstable_by(|l, r| l.0.cmp(&r.0));
+        hosts
+    }
+
+    /// Go over every corp machine's logs, returning anything suspicious that's turned up since the last audit
+    pub fn audit(&mut self) -> Vec<Finding> {
+        let mut findings = vec![];
+        for (host, machine) in self.corp_hosts() {
+            let now = Snapshot::of(&machine);
+            let before = self
+                .world
+                .audits
+                .remove(&host)
+                .map(|(_, s)| s)
+                .unwrap_or_default();
+            let tampered = before
+                .lines
+                .iter()
+                .any(|(path, &lines)| !matches!(now.lines.get(path), Some(&n) if n >= lines));
+            let evidence = now.evidence.saturating_sub(before.evidence);
+            if evidence > 0 || tampered {
+                findings.push(Finding {
+                    host: host.clone(),
+                    evidence,
+                    tampered,
+                });
+            }
+            self.world.audits.insert(host, now);
+        }
+        findings
+    }
+
+    /// Have the corp deal with whatever was found on one of its machines: patch everything, hold it against the
+    /// player if there's evidence it was them, and let them know about it.
+    fn respond(&self, finding: &Finding, replies: &mut Replies<Event>) {
+        if let Some(machine) = self.world.host(&finding.host) {
+            for mut service in machine.services.iter_mut() {
+                service.vulns.clear();
+            }
+        }
+        let org = finding.host.split('-').next().unwrap_or_default();
+        let secops = format!("{}-secops", org);
+        let mut text = if finding.evidence > 0 {
+            self.world
+                .relations
+                .adjust_reputation(org, -TRACE_REPUTATION);
+            format!(
+                "{} entries in the logs on {} lead back to {}. we know it was you.",
+                finding.evidence, finding.host, PLAYER_ADDR
+            )
+        } else {
+            format!(
+                "someone's been cleaning up the logs on {}. can't prove who. yet.",
+                finding.host
+            )
+        };
+        if finding.evidence > 0 && finding.tampered {
+            text += " deleting logs doesn't make you any less guilty.";
+        }
+        text += " every hole on that box is patched now.";
+        replies.queue(Event::npc_chat(&secops, &text, &[]));
+        replies.queue(Event::NPCClosedChat { from: secops });
+    }
+}
+
+impl Agent<Event> for Forensics {
+    fn react(&mut self, event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        if let Event::Tick = event {
+            let now = clock::now();
+            if now >= self.next {
+                self.next = now + Duration::from_secs(FORENSICS_SECS);
+                for finding in self.audit() {
+                    self.respond(&finding, replies);
+                }
+            }
+        }
+        ControlFlow::Continue
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constants::gameplay::{ACCESS_LOG, AUTH_LOG},
+        machine::{Service, Vuln},
+    };
+
+    use super::*;
+
+    /// A world with one corp machine and one civilian machine, both with a bit of history in their logs
+    fn world() -> Arc<World> {
+        let world = World::new(Default::default());
+        for (host, addr) in [
+            ("hexacorp-db04", Addr([172, 16, 4, 20])),
+            ("fox-pc4", Addr([10, 0, 1, 40])),
+        ] {
+            let machine = Machine::default();
+            machine
+                .append(AUTH_LOG, "sshd: accepted password for wes from 10.0.1.23")
+                .unwrap();
+            machine
+                .append(AUTH_LOG, "sshd: accepted password for bea from 10.0.7.9")
+                .unwrap();
+            let service = Service {
+                name: "ssh".into(),
+                version: "OpenSSH_6.6".into(),
+                vulns: vec![Vuln {
+                    exploit: "sshock".into(),
+                    user: "wes".into(),
+                }],
+            };
+            machine.services.insert(22, service);
+            world.net.add(host, addr);
+            world.hosts.insert(host.into(), Arc::new(machine));
+        }
+        Arc::new(world)
+    }
+
+    fn corp(world: &World) -> Arc<Machine> {
+        world.host("hexacorp-db04").unwrap()
+    }
+
+    #[test]
+    fn addresses_mentioned_exactly() {
+        assert!(mentions(
+            "failed password for wes from 10.0.1.2",
+            PLAYER_ADDR
+        ));
+        assert!(mentions("wes from 10.0.1.2: read /etc/passwd", PLAYER_ADDR));
+        assert!(!mentions(
+            "failed password for wes from 10.0.1.23",
+            PLAYER_ADDR
+        ));
+        assert!(!mentions(
+            "failed password for wes from 110.0.1.2",
+            PLAYER_ADDR
+        ));
+    }
+
+    #[test]
+    fn new_evidence_found_once() {
+        let world = world();
+        let mut forensics = Forensics::new(world.clone());
+        assert_eq!(forensics.audit(), vec![]);
+        let entry = format!("wes from {}: read /etc/passwd", PLAYER_ADDR);
+        corp(&world).log(ACCESS_LOG, &entry);
+        world.host("fox-pc4").unwrap().log(ACCESS_LOG, &entry);
+        let findings = forensics.audit();
+        // civilians don't have security teams
+        assert_eq!(
+            findings,
+            vec![Finding {
+                host: "hexacorp-db04".into(),
+                evidence: 1,
+                tampered: false,
+            }]
+        );
+        assert_eq!(forensics.audit(), vec![]);
+
+        let mut replies = Replies::default();
+        forensics.respond(&findings[0], &mut replies);
+        assert!(corp(&world).services.get(&22).unwrap().vulns.is_empty());
+        assert_eq!(world.relations.reputation("hexacorp"), -TRACE_REPUTATION);
+        assert!(matches!(
+            replies._messages(),
+            [Event::NPCChatMessage { from, .. }, Event::NPCClosedChat { .. }] if from == "hexacorp-secops"
+        ));
+    }
+
+    #[test]
+    fn surgical_scrubbing_goes_unnoticed() {
+        let world = world();
+        let mut forensics = Forensics::new(world.clone());
+        let machine = corp(&world);
+        let entry = format!("sshd: failed password for wes from {}", PLAYER_ADDR);
+        machine.log(AUTH_LOG, &entry);
+        machine.log(ACCESS_LOG, &format!("wes from {}: ran ls", PLAYER_ADDR));
+        // take out exactly what the player added, and nothing else
+        let auth = machine.read(AUTH_LOG).unwrap().contents;
+        let kept: Vec<_> = auth.lines().filter(|l| !mentions(l, PLAYER_ADDR)).collect();
+        machine.write(AUTH_LOG, kept.join("\n") + "\n").unwrap();
+        machine.write(ACCESS_LOG, String::new()).unwrap();
+        assert_eq!(forensics.audit(), vec![]);
+    }
+
+    #[test]
+    fn sloppy_wiping_noticed() {
+        let world = world();
+        let mut forensics = Forensics::new(world.clone());
+        corp(&world).write(AUTH_LOG, String::new()).unwrap();
+        let finding = Finding {
+            host: "hexacorp-db04".into(),
+            evidence: 0,
+            tampered: true,
+        };
+        assert_eq!(forensics.audit(), vec![finding.clone()]);
+        // deleting it entirely is just as obvious, and nobody knows who to blame
+        corp(&world).remove(AUTH_LOG).unwrap();
+        assert_eq!(forensics.audit(), vec![finding.clone()]);
+        forensics.respond(&finding, &mut Replies::default());
+        assert_eq!(world.relations.reputation("hexacorp"), 0);
+    }
+}
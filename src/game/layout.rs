@@ -0,0 +1,223 @@
+//! Splitting the screen between two tabs, so e.g. a chat and the terminal can be used side by side.
+//!
+//! Apps don't know they're in a pane: each one is rendered onto a [`Screen`] the size of its pane, with
+//! [`HEADER_HEIGHT`] rows on top like a full screen would have, and that's copied onto the real screen with those rows
+//! cut off. Mouse positions get the same treatment, via [`Region::local`].
+
+use tuig::io::{
+    cell,
+    fmt::{Color, Formatted},
+    Screen, XY,
+};
+
+use crate::{
+    app::App,
+    constants::graphics::{HEADER_HEIGHT, SPLIT_MIN, SPLIT_STEP},
+    state::GameState,
+};
+
+/// Which way the screen's split
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dir {
+    /// Split with a vertical line, so the panes are side by side
+    Vertical,
+    /// Split with a horizontal line, so one pane's above the other
+    Horizontal,
+}
+
+/// A rectangular part of the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub pos: XY,
+    pub size: XY,
+}
+
+impl Region {
+    /// Everything under the header, on a screen this big
+    pub fn below_header(size: XY) -> Self {
+        Self {
+            pos: XY(0, HEADER_HEIGHT),
+            size: XY(size.x(), size.y().saturating_sub(HEADER_HEIGHT)),
+        }
+    }
+
+    pub fn contains(&self, pos: XY) -> bool {
+        (self.pos.x()..self.pos.x() + self.size.x()).contains(&pos.x())
+            && (self.pos.y()..self.pos.y() + self.size.y()).contains(&pos.y())
+    }
+
+    /// The size of the screen the app in this region thinks it has, header and all
+    pub fn app_size(&self) -> XY {
+        XY(self.size.x(), self.size.y() + HEADER_HEIGHT)
+    }
+
+    /// Where a spot on the screen is from the point of view of the app in this region
+    pub fn local(&self, pos: XY) -> XY {
+        XY(
+            pos.x().saturating_sub(self.pos.x()),
+            (pos.y() + HEADER_HEIGHT).saturating_sub(self.pos.y()),
+        )
+    }
+
+    /// Render an app into this region of the screen. If it's not `focused`, it's dimmed.
+    pub fn render(&self, app: &dyn App, state: &GameState, focused: bool, onto: &mut Screen) {
+        let mut pane = Screen::new(self.app_size());
+        app.render(state, &mut pane);
+        for y in 0..self.size.y() {
+            let row = &pane[y + HEADER_HEIGHT];
+            let dest = &mut onto[self.pos.y() + y][self.pos.x()..self.pos.x() + self.size.x()];
+            dest.copy_from_slice(row);
+            if !focused {
+                for cell in dest {
+                    cell.get_fmt_mut().fg = Color::BrightBlack;
+                }
+            }
+        }
+    }
+}
+
+/// How the screen's split between two tabs.
+///
+/// The focused pane's tab is always the selected one; only the other one's tracked here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Split {
+    pub dir: Dir,
+    /// The index of the tab in the pane that isn't focused
+    pub other: usize,
+    /// Whether the focused pane is the first one, i.e. the left or top one
+    pub first: bool,
+    /// What percentage of the space the first pane gets
+    pub ratio: usize,
+}
+
+impl Split {
+    /// Split the screen evenly, with the focused tab first
+    pub fn new(dir: Dir, other: usize) -> Self {
+        Self {
+            dir,
+            other,
+            first: true,
+            ratio: 50,
+        }
+    }
+
+    /// Where the first and second panes are on a screen this big, leaving a line between them for the divider
+    pub fn panes(&self, size: XY) -> [Region; 2] {
+        let all = Region::below_header(size);
+        let along = match self.dir {
+            Dir::Vertical => all.size.x(),
+            Dir::Horizontal => all.size.y(),
+        };
+        let first = (along * self.ratio / 100).min(along.saturating_sub(1));
+        let second = along.saturating_sub(first + 1);
+        match self.dir {
+            Dir::Vertical => [
+                Region {
+                    pos: all.pos,
+                    size: XY(first, all.size.y()),
+                },
+                Region {
+                    pos: XY(all.pos.x() + first + 1, all.pos.y()),
+                    size: XY(second, all.size.y()),
+                },
+            ],
+            Dir::Horizontal => [
+                Region {
+                    pos: all.pos,
+                    size: XY(all.size.x(), first),
+                },
+                Region {
+                    pos: XY(all.pos.x(), all.pos.y() + first + 1),
+                    size: XY(all.size.x(), second),
+                },
+            ],
+        }
+    }
+
+    /// The focused pane and the other one, on a screen this big
+    pub fn focused(&self, size: XY) -> (Region, Region) {
+        let [a, b] = self.panes(size);
+        if self.first {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    /// Draw the line between the panes
+    pub fn render_divider(&self, onto: &mut Screen) {
+        let [first, _] = self.panes(onto.size());
+        match self.dir {
+            Dir::Vertical => {
+                let x = first.pos.x() + first.size.x();
+                onto.vertical(x)
+                    .start(HEADER_HEIGHT)
+                    .fill(cell!(bright_black '│'));
+            }
+            Dir::Horizontal => {
+                let y = first.pos.y() + first.size.y();
+                onto.horizontal(y).fill(cell!(bright_black '─'));
+            }
+        }
+    }
+
+    /// Move the divider a step back (left or up) or forward (right or down)
+    pub fn nudge(&mut self, back: bool) {
+        self.ratio = if back {
+            self.ratio.saturating_sub(SPLIT_STEP).max(SPLIT_MIN)
+        } else {
+            (self.ratio + SPLIT_STEP).min(100 - SPLIT_MIN)
+        };
+    }
+
+    /// Move focus to the other pane, which shows `selected`, the tab that was focused until now
+    pub fn swap_focus(&mut self, selected: usize) -> usize {
+        self.first = !self.first;
+        std::mem::replace(&mut self.other, selected)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn panes_fill_screen_around_divider() {
+        let split = Split::new(Dir::Vertical, 1);
+        let [a, b] = split.panes(XY(81, 25));
+        assert_eq!(a.pos, XY(0, HEADER_HEIGHT));
+        assert_eq!(a.size, XY(40, 25 - HEADER_HEIGHT));
+        assert_eq!(b.pos, XY(41, HEADER_HEIGHT));
+        assert_eq!(b.size, XY(40, 25 - HEADER_HEIGHT));
+
+        let split = Split::new(Dir::Horizontal, 1);
+        let [a, b] = split.panes(XY(80, 21));
+        assert_eq!(a.size.y() + 1 + b.size.y(), 21 - HEADER_HEIGHT);
+        assert_eq!(b.pos.y(), a.pos.y() + a.size.y() + 1);
+    }
+
+    #[test]
+    fn nudging_is_clamped() {
+        let mut split = Split::new(Dir::Vertical, 1);
+        for _ in 0..100 {
+            split.nudge(false);
+        }
+        assert_eq!(split.ratio, 100 - SPLIT_MIN);
+        for _ in 0..100 {
+            split.nudge(true);
+        }
+        assert_eq!(split.ratio, SPLIT_MIN);
+        split.nudge(false);
+        assert_eq!(split.ratio, SPLIT_MIN + SPLIT_STEP);
+    }
+
+    #[test]
+    fn local_positions_include_header() {
+        let split = Split::new(Dir::Vertical, 1);
+        let [_, b] = split.panes(XY(81, 25));
+        assert!(b.contains(XY(41, HEADER_HEIGHT)));
+        assert!(!b.contains(XY(40, HEADER_HEIGHT)));
+        assert_eq!(b.local(XY(45, 10)), XY(4, 10));
+        assert_eq!(b.app_size(), XY(40, 25));
+    }
+}
@@ -1,181 +1,784 @@
 //! Contains the [`tuig::Game`] implementation and "main function" for the game itself
 
-use std::time::Duration;
+use std::{
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::Duration,
+};
 
 use crate::{
-    app::{App, ChatApp, CliApp},
+    app::{App, ChatApp, CliApp, Frame, JobsApp, MapApp, Mouse, NotesApp, Selection, SettingsApp},
+    clock::{self, GameTime},
+    constants::{
+        gameplay::{
+            AUTOSAVE_SECS, CONFIG_FILE, FAILURE_SHOWN_SECS, GENERATED_NPCS, LOG_DIR, PLAYER_HOST,
+            SAVE_FILE, TRANSFER_SHOWN_SECS, UNLOCK_SHOWN_SECS,
+        },
+        graphics::HEADER_HEIGHT,
+    },
+    cutscenes::{self, Cutscene, Script},
+    economy::{Deal, Transfer},
     event::Event,
+    jobs::{Job, JobBoard, Objective, Reward},
+    npc::{gen, npc, Gate, Matcher, Skill},
+    rng::Rng,
+    save::Save,
+    settings::{self, Settings},
     state::GameState,
+    tutorial::Tutorial,
+    world::World,
 };
 
+mod layout;
+use layout::{Dir, Region, Split};
+
 use tuig::{
-    io::{Action, Key, Screen},
-    Agent, ControlFlow, Game, Replies, Response, Runner,
+    io::{text, Action, Key, MouseButton, Screen, XY},
+    Game, Replies, Response, Runner,
 };
 
-/// A single step in the conversation tree of an [`NPC`]
-struct ChatState {
-    messages: Vec<(String, usize)>,
-    options: Vec<(String, usize)>,
-}
+/// The tabs a new game starts with; the rest are unlocked as the story gets to them
+const STARTING_TABS: &[&str] = &["chat", "settings"];
 
-/// Extremely temporary NPC implementation. Very simplistic, can only do basic conversation trees.
-#[derive(Default)]
-struct NPC {
-    /// The name of the NPC
-    name: String,
-    /// All of the states it could possibly be in
-    all_states: Vec<ChatState>,
-    /// Which state it's currently in
-    state: usize,
-    /// Which message in the state it's currently in
-    message: usize,
-}
+/// With ctrl held, opens the tab picker
+const PICKER_KEY: Key = Key::Char('t');
+/// With ctrl held, closes the selected tab (if it can be closed)
+const CLOSE_KEY: Key = Key::Char('w');
+/// With ctrl held, splits the screen side by side, or unsplits it
+const VSPLIT_KEY: Key = Key::Char('\\');
+/// With ctrl held, splits the screen top and bottom, or unsplits it
+const HSPLIT_KEY: Key = Key::Char('-');
+/// With ctrl held, moves focus to the other pane
+const FOCUS_KEY: Key = Key::Char('o');
+/// With ctrl held, opens another terminal session
+const TERMINAL_KEY: Key = Key::Char('n');
+/// The tab names for terminal sessions past the first, which also limits how many can be open at once
+const SESSION_NAMES: [&str; 8] = [
+    "terminal 2",
+    "terminal 3",
+    "terminal 4",
+    "terminal 5",
+    "terminal 6",
+    "terminal 7",
+    "terminal 8",
+    "terminal 9",
+];
 
-impl NPC {
-    /// Get the current state
-    fn state(&self) -> &ChatState {
-        &self.all_states[*&self.state]
-    }
-
-    /// Get the current message/delay tuple
-    fn message(&self) -> &(String, usize) {
-        &self.state().messages[*&self.message]
-    }
-
-    /// Advance to the next message/state
-    fn advance(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
-        if self.state >= self.all_states.len() {
-            return ControlFlow::Kill;
-        }
-        let (text, delay) = self.message().clone();
-        // advance to the next message (or beyond the end, to indicate to wait for replies)
-        self.message += 1;
-        if self.message != self.state().messages.len() {
-            // if it's not the last message, we can send now (and then just ignore events until the next mssage)
-            replies.queue(Event::NPCChatMessage {
-                from: self.name.clone(),
-                text,
-                options: vec![],
-            });
-            ControlFlow::sleep_for(Duration::from_millis(delay as u64))
-        } else {
-            // otherwise we send the replies and `Continue`, to make sure we don't miss a thing
-            let options = self
-                .state()
-                .options
-                .iter()
-                .map(|(s, _)| s.clone())
-                .collect();
+/// One of the player's tabs, and whether they can see it yet.
+struct Tab {
+    app: Box<dyn App>,
+    /// How many notifications the app had as of the last redraw
+    notifs: usize,
+    /// Whether the tab's been unlocked. Locked tabs aren't shown, but they still get every event, so nothing's missed.
+    unlocked: bool,
+    /// Whether the player can close the tab. Only tabs added during the game (with [`Event::AddTab`]) can be.
+    closable: bool,
+}
 
-            replies.queue(Event::NPCChatMessage {
-                from: self.name.clone(),
-                text,
-                options,
-            });
-            ControlFlow::Continue
+impl Tab {
+    fn new(app: Box<dyn App>) -> Self {
+        let notifs = app.notifs();
+        Self {
+            app,
+            notifs,
+            unlocked: true,
+            closable: false,
         }
     }
 }
 
-impl Agent<Event> for NPC {
-    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
-        self.advance(replies)
-    }
+/// Which modifier keys are held down, since tuig reports them as keys of their own.
+#[derive(Clone, Copy, Debug, Default)]
+struct Mods {
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+}
 
-    fn react(&mut self, event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
-        if self.state >= self.all_states.len() {
-            // reached the end of the conversation tree
-            ControlFlow::Kill
-        } else if self.message >= self.all_states[self.state].messages.len() {
-            // look for a reply
-            let (dest, text) = match event {
-                Event::PlayerChatMessage { to, text } => (to, text),
-                _ => return ControlFlow::Continue,
-            };
-            if dest != &self.name {
-                return ControlFlow::Continue;
-            }
-            let options = &self.all_states[self.state].options;
-            let new_state = match options.iter().find(|(opt, _)| opt == text) {
-                Some((_, s)) => s,
-                None => return ControlFlow::Continue,
-            };
-            self.state = *new_state;
-            self.message = 0;
-            self.advance(replies)
-        } else {
-            // send the next message
-            self.advance(replies)
+impl Mods {
+    /// Keep track of a modifier being pressed or released. Returns whether it was a modifier at all.
+    fn track(&mut self, action: &Action) -> bool {
+        let (key, down) = match action {
+            Action::KeyPress { key } => (key, true),
+            Action::KeyRelease { key } => (key, false),
+            _ => return false,
+        };
+        match key {
+            Key::LeftCtrl | Key::RightCtrl => self.ctrl = down,
+            Key::LeftShift | Key::RightShift => self.shift = down,
+            Key::LeftAlt | Key::RightAlt => self.alt = down,
+            _ => return false,
         }
+        true
     }
 }
 
-/// Create an NPC with kinda grody but mostly functional syntax
-macro_rules! npc {
-    ( $name:literal, $(
-        [
-            $( say $msg:literal : $delay:literal ),* ,
-            ask $( $option:literal => $state:literal ),* $(,)?
-        ]
-    ),* $(,)? ) => {
-        NPC {
-            name: $name.into(),
-            all_states: vec![ $(
-                ChatState {
-                    messages: vec![ $(
-                        ( $msg.into(), $delay )
-                    ),* ],
-                    options: vec![
-                        $( ( $option.into(), $state ) ),*
-                    ],
-                }
-            ),* ],
-            ..Default::default()
-        }
-    };
+/// Which tab an event unlocks, if any: story beats ask for one outright, and some things need a tab to be seen at all.
+fn unlocked_by(event: &Event) -> Option<&str> {
+    match event {
+        Event::UnlockTab(name) => Some(name),
+        Event::JobAccepted { .. } => Some("jobs"),
+        Event::PinNote(_) => Some("notes"),
+        _ => None,
+    }
 }
 
 struct Redshell {
-    apps: Vec<(Box<dyn App>, usize)>,
+    world: Arc<World>,
+    tabs: Vec<Tab>,
+    /// The index of the selected tab in `tabs`, which is always an unlocked one
     sel_app: usize,
     state: GameState,
+    /// The in-game time as of the last redraw, so the header's clock can be kept up to date
+    shown_time: GameTime,
+    /// The most recent transfer in or out of the player's wallet, while it's still recent enough to show
+    last_transfer: Option<Transfer>,
+    /// The in-game time of the last save, so autosaves can happen regularly
+    last_save: GameTime,
+    /// Where the mouse is, since tuig only says when it moves
+    mouse: XY,
+    /// The text the player's selecting with the mouse, while they're holding the button down
+    selecting: Option<Selection>,
+    /// What was on screen last time it was drawn, to copy selections out of and to line clicks up with
+    frame: Mutex<Frame>,
+    /// The cutscene that's playing instead of the game, if any
+    cutscene: Option<Cutscene>,
+    /// The tab that was unlocked most recently, and when, while it's still recent enough to announce on the header
+    last_unlock: Option<(String, GameTime)>,
+    /// The last thing that went wrong in the background, and when, while it's still recent enough to show on the header
+    last_failure: Option<(String, GameTime)>,
+    /// Which modifier keys are held down
+    mods: Mods,
+    /// Which tab is highlighted in the tab picker (by its position among the unlocked ones), while it's open
+    picker: Option<usize>,
+    /// How the screen's split between two tabs, if it is
+    split: Option<Split>,
+    /// The ID the next terminal session will get. IDs aren't reused, so a closed session's leftover commands can't
+    /// print into a new one.
+    next_session: usize,
 }
 
 impl Redshell {
-    pub fn new() -> Self {
+    pub fn new(world: Arc<World>) -> Self {
         Self {
-            apps: vec![
-                (Box::new(ChatApp::default()), 0),
-                (Box::new(CliApp::default()), 0),
+            tabs: vec![
+                Tab::new(Box::new(ChatApp::default())),
+                Tab::new(Box::new(CliApp::new(world.clone()))),
+                Tab::new(Box::new(JobsApp::default())),
+                Tab::new(Box::new(NotesApp::default())),
+                Tab::new(Box::new(MapApp::new(world.clone()))),
+                Tab::new(Box::new(SettingsApp::new(settings::current()))),
             ],
             sel_app: 0,
-            state: Default::default(),
+            state: GameState {
+                wallet: world.wallet.clone(),
+                ..Default::default()
+            },
+            shown_time: clock::now(),
+            last_transfer: None,
+            last_save: clock::now(),
+            mouse: XY(0, 0),
+            selecting: None,
+            frame: Mutex::new(Frame::default()),
+            cutscene: None,
+            last_unlock: None,
+            last_failure: None,
+            mods: Mods::default(),
+            picker: None,
+            split: None,
+            next_session: 1,
+            world,
+        }
+    }
+
+    /// Restore each app's state from a save, matching them up by name.
+    pub fn load(&mut self, save: &Save) {
+        if let Some(name) = save.section("player") {
+            self.state.player_name = name.to_owned();
+        }
+        if let Some(tabs) = save.section("tabs") {
+            let order: Vec<&str> = tabs.lines().collect();
+            for name in SESSION_NAMES.iter().filter(|n| order.contains(n)) {
+                self.add_session(name);
+            }
+            for tab in &mut self.tabs {
+                let name = tab.app.name();
+                tab.unlocked = order.contains(&name) || STARTING_TABS.contains(&name);
+            }
+            // the player might've reordered them, too
+            let pos = |t: &Tab| order.iter().position(|&n| n == t.app.name());
+            self.tabs.sort_by_key(|t| pos(t).unwrap_or(usize::MAX));
+            self.sel_app = self.tabs.iter().position(|t| t.unlocked).unwrap_or(0);
+        }
+        // the tutorial picks up from here, and unlocks whatever it hadn't yet as it goes
+        if let Some(lesson) = save.section("tutorial").and_then(|l| l.parse().ok()) {
+            self.world.tutorial.store(lesson, Ordering::Relaxed);
+        }
+        // the world's been populated from the saved seed by now, so the hosts the player found are all still there
+        for host in save.section("known").unwrap_or_default().lines() {
+            if self.world.net.addr_of(host).is_some() {
+                self.world.net.learn(host);
+            }
+        }
+        self.load_hosts(save);
+        for tab in &mut self.tabs {
+            if let Some(data) = save.section(tab.app.name()) {
+                tab.app.load(data);
+                tab.notifs = tab.app.notifs();
+            }
+        }
+    }
+
+    /// Collect everything worth saving from each app.
+    pub fn save(&self) -> Save {
+        let mut save = Save::default();
+        if !self.state.player_name.is_empty() {
+            save.set("player", self.state.player_name.clone());
+        }
+        let unlocked: Vec<&str> = self.shown().map(|(_, tab)| tab.app.name()).collect();
+        save.set("tabs", unlocked.join("\n"));
+        save.set("seed", self.world.seed.load(Ordering::Relaxed).to_string());
+        let lesson = self.world.tutorial.load(Ordering::Relaxed);
+        save.set("tutorial", lesson.to_string());
+        let mut known: Vec<String> = self.world.net.known.iter().map(|h| h.clone()).collect();
+        known.sort();
+        save.set("known", known.join("\n"));
+        self.save_hosts(&mut save);
+        for tab in &self.tabs {
+            if let Some(data) = tab.app.save() {
+                save.set(tab.app.name(), data);
+            }
+        }
+        save
+    }
+
+    /// Save what the player's done to other machines that regenerating them from the seed wouldn't bring back: their
+    /// logs, which services have been patched, how much noise the player's made, and what forensics last saw.
+    ///
+    /// There's only a handful of machines, so it's simpler to keep every one's logs than to work out which the player's
+    /// touched.
+    fn save_hosts(&self, save: &mut Save) {
+        let mut hosts: Vec<_> = self.world.hosts.iter().map(|h| h.key().clone()).collect();
+        hosts.sort();
+        let (mut patched, mut alerts, mut audits) = (vec![], vec![], vec![]);
+        for host in hosts.iter().filter(|&h| h != PLAYER_HOST) {
+            let machine = match self.world.host(host) {
+                Some(m) => m,
+                None => continue,
+            };
+            let mut logs: Vec<_> = machine
+                .files()
+                .into_iter()
+                .filter(|(path, _)| path.starts_with(LOG_DIR))
+                .collect();
+            logs.sort_unstable_by(|l, r| l.0.cmp(&r.0));
+            let paths: Vec<&str> = logs.iter().map(|(path, _)| path.as_str()).collect();
+            save.set(&format!("logs {}", host), paths.join("\n"));
+            for (path, file) in &logs {
+                save.set(&format!("log {} {}", host, path), file.contents.clone());
+            }
+            for (port, service) in machine.ports() {
+                if service.vulns.is_empty() {
+                    patched.push(format!("{} {}", host, port));
+                }
+            }
+            if let Some(noise) = self.world.alerts.get(host) {
+                alerts.push(format!("{} {}", host, *noise));
+            }
+            if let Some(audit) = self.world.audits.get(host) {
+                audits.push(format!("{} {}", host, *audit));
+            }
+        }
+        save.set("patched", patched.join("\n"));
+        save.set("alerts", alerts.join("\n"));
+        save.set("audits", audits.join("\n"));
+    }
+
+    /// Restore whatever [`Self::save_hosts`] saved, onto machines freshly regenerated from the same seed.
+    fn load_hosts(&self, save: &Save) {
+        for host in self.world.hosts.iter() {
+            let (name, machine) = (host.key(), host.value());
+            let paths = match save.section(&format!("logs {}", name)) {
+                Some(paths) => paths,
+                None => continue,
+            };
+            // anything the player deleted stays deleted
+            for (path, _) in machine.files() {
+                if path.starts_with(LOG_DIR) && !paths.lines().any(|p| p == path) {
+                    let _ = machine.remove(&path);
+                }
+            }
+            for path in paths.lines() {
+                if let Some(contents) = save.section(&format!("log {} {}", name, path)) {
+                    let _ = machine.write(path, contents.to_owned());
+                }
+            }
+        }
+        for line in save.section("patched").unwrap_or_default().lines() {
+            let (host, port) = match line.split_once(' ') {
+                Some((host, port)) => (host, port.parse::<u16>()),
+                None => continue,
+            };
+            if let (Some(machine), Ok(port)) = (self.world.host(host), port) {
+                if let Some(mut service) = machine.services.get_mut(&port) {
+                    service.vulns.clear();
+                }
+            }
+        }
+        for line in save.section("alerts").unwrap_or_default().lines() {
+            if let Some((host, noise)) = line.split_once(' ') {
+                if let Ok(noise) = noise.parse() {
+                    self.world.alerts.insert(host.into(), noise);
+                }
+            }
+        }
+        for line in save.section("audits").unwrap_or_default().lines() {
+            if let Some((host, audit)) = line.split_once(' ') {
+                if let Ok(audit) = audit.parse() {
+                    self.world.audits.insert(host.into(), audit);
+                }
+            }
+        }
+    }
+
+    /// Hide every tab but the ones a new game starts with
+    fn lock(&mut self) {
+        for tab in &mut self.tabs {
+            tab.unlocked = STARTING_TABS.contains(&tab.app.name());
+        }
+        self.sel_app = self.tabs.iter().position(|t| t.unlocked).unwrap_or(0);
+        self.split = None;
+    }
+
+    /// Unlock a tab by name, announcing it on the header. Returns whether there was anything to unlock.
+    fn unlock(&mut self, name: &str) -> bool {
+        match self.tabs.iter_mut().find(|t| t.app.name() == name) {
+            Some(tab) if !tab.unlocked => {
+                tab.unlocked = true;
+                self.last_unlock = Some((name.into(), clock::now()));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The unlocked tabs, in order, with their indices in `tabs`
+    fn shown(&self) -> impl Iterator<Item = (usize, &Tab)> {
+        self.tabs.iter().enumerate().filter(|(_, t)| t.unlocked)
+    }
+
+    /// Where the selected tab is among the unlocked ones
+    fn sel_shown(&self) -> usize {
+        self.shown()
+            .position(|(i, _)| i == self.sel_app)
+            .unwrap_or(0)
+    }
+
+    /// Close a tab by its index in `tabs`, if it can be closed. Returns whether it was.
+    fn close(&mut self, idx: usize) -> bool {
+        if !self.tabs[idx].closable {
+            return false;
+        }
+        let at = self.sel_shown();
+        self.tabs.remove(idx);
+        if self.sel_app > idx {
+            self.sel_app -= 1;
+        } else if self.sel_app == idx {
+            // fall back to the tab before it, like closing a tab in a browser
+            let before = self.shown().nth(at.saturating_sub(1)).map(|(i, _)| i);
+            self.sel_app = before.unwrap_or(0);
+        }
+        if let Some(split) = &mut self.split {
+            // check before shifting it down, or the tab after the closed one would look like the closed one
+            let closed = split.other == idx;
+            if split.other > idx {
+                split.other -= 1;
+            }
+            // a pane with nothing left to show, or showing the same as the other, isn't worth keeping
+            if closed || split.other == self.sel_app {
+                self.split = None;
+            }
+        }
+        true
+    }
+
+    /// Move a tab, by its index in `tabs`, one place left or right among the unlocked ones. Returns its new index, if
+    /// it moved.
+    fn move_tab(&mut self, idx: usize, left: bool) -> Option<usize> {
+        let at = self.shown().position(|(i, _)| i == idx)?;
+        let to = if left { at.checked_sub(1)? } else { at + 1 };
+        let other = self.shown().nth(to).map(|(i, _)| i)?;
+        self.tabs.swap(idx, other);
+        let swapped = |i: &mut usize| {
+            if *i == idx {
+                *i = other;
+            } else if *i == other {
+                *i = idx;
+            }
+        };
+        swapped(&mut self.sel_app);
+        if let Some(split) = &mut self.split {
+            swapped(&mut split.other);
+        }
+        Some(other)
+    }
+
+    /// Which tab is at a spot on the header, by its position among the unlocked ones
+    fn tab_at(&self, x: usize) -> Option<usize> {
+        let frame = self.frame.lock().unwrap();
+        // the header's laid out by tuig, so just look for where each tab's name ended up
+        let mut from = 0;
+        for (n, (_, tab)) in self.shown().enumerate() {
+            let name = tab.app.name();
+            let start = frame.find(0, name, from)?;
+            from = start + name.chars().count();
+            if (start..from).contains(&x) {
+                return Some(n);
+            }
+        }
+        None
+    }
+
+    /// Handle input while the tab picker's open
+    fn picker_input(&mut self, input: Action) -> Response {
+        let count = self.shown().count();
+        let at = match self.picker {
+            Some(at) => at.min(count - 1),
+            None => return Response::Nothing,
+        };
+        let idx = self.shown().nth(at).map(|(i, _)| i).unwrap_or(self.sel_app);
+        match input {
+            Action::KeyPress { key: Key::Up } => self.picker = Some((at + count - 1) % count),
+            Action::KeyPress { key: Key::Down } => self.picker = Some((at + 1) % count),
+            Action::KeyPress {
+                key: key @ (Key::Left | Key::Right),
+            } => {
+                if let Some(moved) = self.move_tab(idx, key == Key::Left) {
+                    let pos = self.shown().position(|(i, _)| i == moved);
+                    self.picker = pos;
+                }
+            }
+            Action::KeyPress { key: Key::Delete } => {
+                if self.close(idx) {
+                    self.picker = Some(at.min(count.saturating_sub(2)));
+                }
+            }
+            Action::KeyPress { key: Key::Enter } => {
+                self.picker = None;
+                self.select(at);
+            }
+            Action::KeyPress { key: Key::Escape } => self.picker = None,
+            Action::KeyPress {
+                key: Key::Char(c @ '1'..='9'),
+            } => {
+                self.picker = None;
+                self.select(c as usize - '1' as usize);
+            }
+            _ => return Response::Nothing,
+        }
+        Response::Redraw
+    }
+
+    /// Draw the tab picker over the middle of the screen
+    fn render_picker(&self, at: usize, onto: &mut Screen) {
+        let mut lines: Vec<String> = self
+            .shown()
+            .enumerate()
+            .map(|(n, (_, tab))| {
+                let close = if tab.closable { "  (del closes)" } else { "" };
+                format!(" {:>2}  {}{} ", n + 1, tab.app.name(), close)
+            })
+            .collect();
+        lines.push(" enter: switch  left/right: move  esc: cancel ".into());
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let x = onto.size().x().saturating_sub(width) / 2;
+        let y = onto.size().y().saturating_sub(lines.len()) / 2;
+        let hint = lines.len() - 1;
+        for (i, line) in lines.iter().enumerate() {
+            let line = if i == at {
+                text!(black on_white "{:<1$}"(line, width))
+            } else if i == hint {
+                text!(bright_black on_black "{:<1$}"(line, width))
+            } else {
+                text!(bright_white on_black "{:<1$}"(line, width))
+            };
+            onto.textbox(line).pos(x, y + i).width(width).height(1);
+        }
+    }
+
+    /// Where each visible tab is on a screen this big, as its index, region, and whether it's focused
+    fn panes(&self, size: XY) -> Vec<(usize, Region, bool)> {
+        match &self.split {
+            Some(split) => {
+                let (focused, other) = split.focused(size);
+                vec![(self.sel_app, focused, true), (split.other, other, false)]
+            }
+            None => vec![(self.sel_app, Region::below_header(size), true)],
+        }
+    }
+
+    /// Whether a tab's visible, in either pane
+    fn visible(&self, idx: usize) -> bool {
+        idx == self.sel_app || self.split.map(|s| s.other == idx).unwrap_or(false)
+    }
+
+    /// Split the screen, with the next tab over in the other pane, or unsplit it if it's already split that way
+    fn toggle_split(&mut self, dir: Dir) -> Response {
+        match &mut self.split {
+            Some(split) if split.dir == dir => self.split = None,
+            Some(split) => split.dir = dir,
+            None => {
+                let count = self.shown().count();
+                if count < 2 {
+                    return Response::Nothing;
+                }
+                let next = (self.sel_shown() + 1) % count;
+                let other = self.shown().nth(next).map(|(i, _)| i).unwrap_or(0);
+                self.split = Some(Split::new(dir, other));
+            }
+        }
+        Response::Redraw
+    }
+
+    /// Move focus to the other pane, if the screen's split
+    fn swap_focus(&mut self) -> Response {
+        match &mut self.split {
+            Some(split) => {
+                self.sel_app = split.swap_focus(self.sel_app);
+                Response::Redraw
+            }
+            None => Response::Nothing,
         }
     }
+
+    /// Open another terminal session in a new tab and switch to it, if the terminal's unlocked and there's room
+    fn new_session(&mut self) -> Response {
+        let unlocked = |name: &str| self.shown().any(|(_, t)| t.app.name() == name);
+        if !unlocked("terminal") {
+            return Response::Nothing;
+        }
+        let name = match SESSION_NAMES.iter().find(|n| !unlocked(n)) {
+            Some(name) => name,
+            None => return Response::Nothing,
+        };
+        self.add_session(name);
+        self.select(self.shown().count() - 1)
+    }
+
+    /// Add a terminal session's tab at the end, without switching to it
+    fn add_session(&mut self, name: &'static str) {
+        let cli = CliApp::new(self.world.clone()).session(self.next_session, name);
+        self.next_session += 1;
+        let mut tab = Tab::new(Box::new(cli));
+        tab.closable = true;
+        self.tabs.push(tab);
+    }
+
+    /// Pass input on to the selected app
+    fn app_input(&mut self, input: Action, replies: &mut Replies<Event>) -> Response {
+        if self.tabs[self.sel_app].app.input(input, replies) {
+            Response::Redraw
+        } else {
+            Response::Nothing
+        }
+    }
+
+    /// Turn raw mouse input into clicks, scrolling, and selections for whichever app's under the mouse
+    fn mouse_input(&mut self, input: Action, replies: &mut Replies<Event>) -> Response {
+        let size = self.frame.lock().unwrap().size();
+        let under = self
+            .panes(size)
+            .into_iter()
+            .find(|(_, region, _)| region.contains(self.mouse));
+        let tainted = match input {
+            Action::MouseMove { pos } => {
+                self.mouse = pos;
+                match &mut self.selecting {
+                    Some(sel) => {
+                        sel.to = pos;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Action::MousePress {
+                button: MouseButton::Left,
+            } => {
+                self.selecting = Some(Selection::at(self.mouse));
+                // clicking on the other pane focuses it
+                match under {
+                    Some((_, _, false)) => return self.swap_focus(),
+                    _ => false,
+                }
+            }
+            Action::MouseRelease {
+                button: MouseButton::Left,
+            } => match self.selecting.take() {
+                Some(sel) if sel.dragged() => {
+                    let text = sel.text(&self.frame.lock().unwrap());
+                    self.tabs[self.sel_app].app.paste(&text);
+                    // the highlight needs clearing either way
+                    true
+                }
+                Some(_) if self.mouse.y() < HEADER_HEIGHT => match self.tab_at(self.mouse.x()) {
+                    Some(n) => return self.select(n),
+                    None => false,
+                },
+                Some(_) => match under {
+                    Some((idx, region, _)) => {
+                        let click = Mouse::Click(region.local(self.mouse));
+                        self.tabs[idx].app.mouse(click, region.app_size(), replies)
+                    }
+                    None => false,
+                },
+                None => false,
+            },
+            Action::MousePress {
+                button: button @ (MouseButton::ScrollUp | MouseButton::ScrollDown),
+            } => match under {
+                Some((idx, region, _)) => {
+                    let scroll = if button == MouseButton::ScrollUp {
+                        Mouse::ScrollUp
+                    } else {
+                        Mouse::ScrollDown
+                    };
+                    self.tabs[idx].app.mouse(scroll, region.app_size(), replies)
+                }
+                None => false,
+            },
+            _ => false,
+        };
+        if tainted {
+            Response::Redraw
+        } else {
+            Response::Nothing
+        }
+    }
+
+    /// Switch to another tab, by its position among the unlocked ones
+    fn select(&mut self, tab: usize) -> Response {
+        let idx = self.shown().nth(tab).map(|(idx, _)| idx);
+        match idx {
+            Some(idx) if idx != self.sel_app => {
+                // a tab can't be in both panes, so if it's already in the other one, they trade places
+                if let Some(split) = &mut self.split {
+                    if split.other == idx {
+                        split.other = self.sel_app;
+                    }
+                }
+                self.sel_app = idx;
+                Response::Redraw
+            }
+            _ => Response::Nothing,
+        }
+    }
+
+    /// Save the game to [`SAVE_FILE`], showing rather than failing if that doesn't work. Returns whether it worked.
+    fn store(&mut self) -> bool {
+        self.last_save = clock::now();
+        match self.save().store(SAVE_FILE) {
+            Ok(()) => true,
+            Err(e) => {
+                self.fail(format!("couldn't save: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Play a cutscene instead of the game, until it's over, replacing whatever cutscene's already playing
+    pub fn play(&mut self, script: Script) {
+        self.cutscene = Some(Cutscene::new(script));
+    }
+
+    /// Tell the player something went wrong, on the header, since tuig owns the terminal and anything printed would
+    /// just draw over it.
+    fn fail(&mut self, what: String) {
+        self.last_failure = Some((what, clock::now()));
+    }
 }
 
 impl Game for Redshell {
     type Message = Event;
 
     fn input(&mut self, input: Action, replies: &mut Replies<Event>) -> Response {
+        let settings = settings::current();
         match input {
-            Action::KeyPress { key: Key::F(num) } => {
-                if num <= self.apps.len() {
-                    self.sel_app = num as usize - 1;
+            Action::Closed => {
+                self.store();
+                Response::Quit
+            }
+            // nothing else gets input while a cutscene's playing
+            other if self.cutscene.is_some() => {
+                let scene = self.cutscene.as_mut().unwrap();
+                if scene.input(other) {
                     Response::Redraw
                 } else {
                     Response::Nothing
                 }
             }
-            other => {
-                let app_taint = self.apps[self.sel_app].0.input(other, replies);
-                if app_taint {
+            other if self.mods.track(&other) => self.app_input(other, replies),
+            other if self.picker.is_some() => self.picker_input(other),
+            Action::MouseMove { .. } | Action::MousePress { .. } | Action::MouseRelease { .. } => {
+                self.mouse_input(input, replies)
+            }
+            // ctrl and alt combos come before apps get a look in, since they'd never mean anything to an app
+            Action::KeyPress {
+                key: key @ (Key::Tab | Key::BackTab),
+            } if self.mods.ctrl => {
+                let count = self.shown().count();
+                if key == Key::BackTab || self.mods.shift {
+                    self.select((self.sel_shown() + count - 1) % count)
+                } else {
+                    self.select((self.sel_shown() + 1) % count)
+                }
+            }
+            Action::KeyPress { key } if self.mods.ctrl && key == PICKER_KEY => {
+                self.picker = Some(self.sel_shown());
+                Response::Redraw
+            }
+            Action::KeyPress { key } if self.mods.ctrl && key == CLOSE_KEY => {
+                if self.close(self.sel_app) {
                     Response::Redraw
                 } else {
                     Response::Nothing
                 }
             }
+            Action::KeyPress {
+                key: Key::Char(c @ '1'..='9'),
+            } if self.mods.alt => self.select(c as usize - '1' as usize),
+            Action::KeyPress { key } if self.mods.ctrl && key == VSPLIT_KEY => {
+                self.toggle_split(Dir::Vertical)
+            }
+            Action::KeyPress { key } if self.mods.ctrl && key == HSPLIT_KEY => {
+                self.toggle_split(Dir::Horizontal)
+            }
+            Action::KeyPress { key } if self.mods.ctrl && key == FOCUS_KEY => self.swap_focus(),
+            Action::KeyPress { key } if self.mods.ctrl && key == TERMINAL_KEY => self.new_session(),
+            Action::KeyPress {
+                key: key @ (Key::Left | Key::Right | Key::Up | Key::Down),
+            } if self.mods.ctrl && self.split.is_some() => {
+                let split = self.split.as_mut().unwrap();
+                match (split.dir, key) {
+                    (Dir::Vertical, Key::Left) | (Dir::Horizontal, Key::Up) => split.nudge(true),
+                    (Dir::Vertical, Key::Right) | (Dir::Horizontal, Key::Down) => {
+                        split.nudge(false)
+                    }
+                    // arrows across the split don't mean anything
+                    _ => return Response::Nothing,
+                }
+                Response::Redraw
+            }
+            // some apps need keys that would otherwise switch tabs
+            other if self.tabs[self.sel_app].app.capturing() => self.app_input(other, replies),
+            Action::KeyPress { key: Key::F(num) } if num > 0 => self.select(num - 1),
+            Action::KeyPress { key } if key == settings.next_tab => {
+                let count = self.shown().count();
+                self.select((self.sel_shown() + 1) % count)
+            }
+            Action::KeyPress { key } if key == settings.prev_tab => {
+                let count = self.shown().count();
+                self.select((self.sel_shown() + count - 1) % count)
+            }
+            other => self.app_input(other, replies),
         }
     }
 
@@ -185,21 +788,81 @@ impl Game for Redshell {
                 let app = b
                     .take()
                     .expect("app bundle taken before sole consumer got it");
-                let notifs = app.notifs();
-                self.apps.push((app, notifs));
+                let mut tab = Tab::new(app);
+                tab.closable = true;
+                self.tabs.push(tab);
                 Response::Redraw
             }
             event => {
                 let mut tainted = false;
-                for (i, (app, old_notifs)) in self.apps.iter_mut().enumerate() {
-                    let ev_taint = app.on_event(event, i == self.sel_app);
-                    if i == self.sel_app {
-                        tainted |= ev_taint;
+                if let Event::PlayCutscene(script) = event {
+                    self.play(script.clone());
+                    tainted = true;
+                }
+                if let (Event::Tick, Some(scene)) = (event, &mut self.cutscene) {
+                    let size = self.frame.lock().unwrap().size();
+                    tainted |= scene.tick(size);
+                    if scene.done() {
+                        if let Some(name) = scene.answer("name") {
+                            self.state.player_name = name.to_owned();
+                        }
+                        self.cutscene = None;
+                        tainted = true;
+                    }
+                }
+                if let Event::Tick = event {
+                    // only redraw the clock when the displayed time would actually change
+                    let now = clock::now();
+                    if now.0 / 60 != self.shown_time.0 / 60 {
+                        self.shown_time = now;
+                        tainted = true;
                     }
-                    let new_notifs = app.notifs();
-                    if new_notifs != *old_notifs {
+                    let expired = |t: &Transfer| now.0 > t.at.0 + TRANSFER_SHOWN_SECS;
+                    if self.last_transfer.as_ref().map(expired).unwrap_or(false) {
+                        self.last_transfer = None;
                         tainted = true;
-                        *old_notifs = new_notifs;
+                    }
+                    let expired = |(_, at): &(String, GameTime)| now.0 > at.0 + UNLOCK_SHOWN_SECS;
+                    if self.last_unlock.as_ref().map(expired).unwrap_or(false) {
+                        self.last_unlock = None;
+                        tainted = true;
+                    }
+                    let expired = |(_, at): &(String, GameTime)| now.0 > at.0 + FAILURE_SHOWN_SECS;
+                    if self.last_failure.as_ref().map(expired).unwrap_or(false) {
+                        self.last_failure = None;
+                        tainted = true;
+                    }
+                    if now.0 >= self.last_save.0 + AUTOSAVE_SECS {
+                        tainted |= !self.store();
+                    }
+                }
+                if let Event::SettingsChanged(settings) = event {
+                    settings::apply(settings.clone());
+                    if let Err(e) = settings.store(CONFIG_FILE) {
+                        self.fail(format!("couldn't save settings: {}", e));
+                    }
+                    // the theme or clock speed might've changed, and either way the tab needs redrawing
+                    self.shown_time = clock::now();
+                    tainted = true;
+                }
+                if let Event::Transfer(transfer) = event {
+                    self.last_transfer = Some(transfer.clone());
+                    tainted = true;
+                }
+                if let Some(name) = unlocked_by(event) {
+                    tainted |= self.unlock(name);
+                }
+                let visible: Vec<bool> = (0..self.tabs.len()).map(|i| self.visible(i)).collect();
+                for (i, tab) in self.tabs.iter_mut().enumerate() {
+                    let ev_taint = tab.app.on_event(event, visible[i]);
+                    if visible[i] {
+                        tainted |= ev_taint;
+                    }
+                    let new_notifs = tab.app.notifs();
+                    if new_notifs != tab.notifs {
+                        // locked tabs' notifications aren't shown anywhere
+                        tainted |= tab.unlocked;
+                        tab.notifs = new_notifs;
                     }
                 }
                 if tainted {
@@ -212,66 +875,432 @@ impl Game for Redshell {
     }
 
     fn render(&self, onto: &mut Screen) {
-        self.apps[self.sel_app].0.render(&self.state, onto);
-        let mut header = onto
-            .header()
-            .profile(&self.state.player_name)
-            .selected(self.sel_app)
-            .time("right now >:3");
-        for (app, notifs) in &self.apps {
-            header = header.tab(app.name(), *notifs);
+        if let Some(scene) = &self.cutscene {
+            scene.render(onto);
+            *self.frame.lock().unwrap() = Frame::of(onto);
+            return;
+        }
+        for (idx, region, focused) in self.panes(onto.size()) {
+            region.render(&*self.tabs[idx].app, &self.state, focused, onto);
+        }
+        if let Some(split) = &self.split {
+            split.render_divider(onto);
+        }
+        // the header doesn't have a spot for money, so it goes next to the clock
+        let mut status = format!("{}  {}", self.shown_time, self.state.wallet.balance());
+        if let Some(transfer) = &self.last_transfer {
+            status += &format!(" ({:+}tk)", transfer.amount.0);
+        }
+        if let Some((name, _)) = &self.last_unlock {
+            if let Some(num) = self.shown().position(|(_, t)| t.app.name() == name) {
+                status = format!("new tab: {} (F{})  {}", name, num + 1, status);
+            }
+        }
+        if let Some((what, _)) = &self.last_failure {
+            status = format!("{}  {}", what, status);
+        }
+        {
+            let mut header = onto
+                .header()
+                .profile(&self.state.player_name)
+                .selected(self.sel_shown())
+                .time(&status);
+            for (_, tab) in self.shown() {
+                header = header.tab(tab.app.name(), tab.notifs);
+            }
+        }
+        if let Some(at) = self.picker {
+            self.render_picker(at, onto);
+        }
+        // the header's been drawn by now, so the whole screen can be restyled
+        let settings = settings::current();
+        for y in 0..onto.size().y() {
+            for cell in onto[y].iter_mut() {
+                settings.style(cell);
+            }
+        }
+        *self.frame.lock().unwrap() = Frame::of(onto);
+        if let Some(sel) = self.selecting.filter(|s| s.dragged()) {
+            sel.highlight(onto);
         }
     }
 }
 
 pub fn run(mut _args: impl Iterator<Item = String>) {
-    let game = Redshell::new();
-    Runner::new(game)
+    match Settings::load(CONFIG_FILE) {
+        Ok(settings) => settings::apply(settings),
+        Err(e) => eprintln!("couldn't load settings, using defaults: {}", e),
+    }
+    let save = Save::load(SAVE_FILE).unwrap_or_else(|e| {
+        eprintln!("couldn't load save, starting fresh: {}", e);
+        Save::default()
+    });
+    let world = Arc::new(World::new(Default::default()));
+    let mut rng = Rng::from_time();
+    // the same seed makes the same machines, so everything the player's learned about them still holds
+    let seed = save.section("seed").and_then(|s| s.parse().ok());
+    world.populate(seed.unwrap_or_else(|| rng.next_u64()));
+    let mut game = Redshell::new(world.clone());
+    game.load(&save);
+    if game.state.player_name.is_empty() {
+        // a new game, so the player needs introducing, and the tutorial unlocks the rest of the tabs
+        game.play(cutscenes::intro_script(&mut rng));
+        game.lock();
+    }
+    let mut hosts: Vec<String> = world
+        .hosts
+        .iter()
+        .map(|h| h.key().clone())
+        .filter(|h| h != PLAYER_HOST)
+        .collect();
+    hosts.sort();
+    let targets = hosts.join("\n") + "\n";
+    let practice = Job::new("admin-practice", "admin", "Practice run")
+        .objective(Objective::create(PLAYER_HOST, "/stash/ready"))
+        .time_limit(Duration::from_secs(60 * 60))
+        .reward(Reward::tool(crate::tools::Cat))
+        .reward(Reward::tokens(25));
+    let admin = npc!(
+        "admin",
+        [
+            say "hi": 500,
+            hear
+                Matcher::keywords(&["controls", "help", "how"]) => 1,
+                Matcher::keywords(&["work", "job"]) => 2;
+            ask
+                "controls?" => 1,
+                "any work?" => 2,
+                "got anything real?" => 6 [if Gate::trust(10)],
+                "hi" => 0,
+        ],
+        [
+            say "sure!": 250,
+            say "Press F1, F2, etc. to switch to the first tab, the second tab, etc.": 250,
+            say "Chat's where we are now. Plenty of people in the scene will message you here.": 250,
+            say "Pick a reply with the arrows and enter, or just type if you've got something else to say.": 250,
+            say "The terminal is your CLI. There's only, like, four commands, and none of them do anything cool.": 250,
+            say "The jobs tab lists the jobs you've taken on, once you've got some.": 250,
+            say "The notes tab is your notes. Press Insert in chat or the terminal to pin stuff there.": 250,
+            say "The settings tab has text speed, colors, keys, that kind of thing.": 250,
+            say "If anyone joins your team, `team` in your CLI lists them and sends them on jobs.": 250,
+            say "Files people send you land in /inbox, and `send <name> <file>` sends them one back.": 250,
+            say "Your tokens are up top next to the clock. `wallet` shows where they went.": 250,
+            say "And that's it for now!": 250,
+            ask "oh ok. hi." => 0,
+        ],
+        [
+            say "sure, a practice run": 500,
+            say "make a directory called stash, and put a file called ready in it": 500,
+            say "you've got an hour": 250,
+            offer practice => 3 / 4 when "on it",
+            ask "on it" => 5, "not now" => 0,
+        ],
+        [
+            say "nice work": 500,
+            say "have a cat. it reads files. and some tokens.": 250,
+            say "and here's a few places worth poking at. check your inbox.": 250,
+            attach "targets.txt": targets.clone(),
+            ask "thanks" => 0,
+        ],
+        [
+            say "too slow": 500,
+            ask "sorry" => 0,
+        ],
+        [
+            say "ping me when it's done": 250,
+            ask,
+        ],
+        [
+            say "not yet": 500,
+            say "but you're getting there": 250,
+            ask "ok" => 0,
+        ],
+    )
+    .in_world(world.clone())
+    .in_faction("redshell");
+    let mut runner = Runner::new(game)
         .queue(Event::install(crate::tools::Ls))
         .queue(Event::install(crate::tools::Touch))
         .queue(Event::install(crate::tools::Mkdir))
         .queue(Event::install(crate::tools::Cd))
-        .spawn(npc!(
-            "admin",
-            [
-                say "hi": 500,
-                ask "controls?" => 1, "hi" => 0,
-            ],
-            [
-                say "sure!": 250,
-                say "Press F1, F2, etc. to switch to tab 1, tab 2, etc.": 250,
-                say "Tab #1 is chat. There's only two people to chat with and neither is a great conversationalist.": 250,
-                say "Tab #2 is your CLI. There's only, like, four commands, and none of them do anything cool.": 250,
-                say "And that's it for now!": 250,
-                ask "oh ok. hi." => 0,
-            ],
-        ))
-        .spawn(npc!(
-            "yotie",
-            [
-                say "hey": 500,
-                say "hello": 500,
-                say "hi": 500,
-                say "my close personal friend": 1000,
-                say "whose name I do not need to say": 1000,
-                say "because we're so close and all": 1000,
-                say "how you doin?": 1500,
-                ask "good" => 1, "bad" => 2,
-            ],
-            [
-                say "ey that's nice": 2000,
-                say "glad you're doing well": 500,
-                ask "thanks" => 3,
-            ],
-            [
-                say "ey that's bad": 2000,
-                say "sucks you're doing meh": 500,
-                ask "thanks?" => 3,
-            ],
-            [
-                say "anyway bye": 500,
-                ask "uh ok" => 100,
-            ],
-        ))
-        .load_run();
+        .queue(Event::install(crate::tools::Team))
+        .queue(Event::install(crate::tools::Wallet))
+        .queue(Event::install(crate::tools::SendFile))
+        .queue(Event::install(crate::tools::Scan))
+        .queue(Event::install(crate::tools::Ssh))
+        .queue(Event::install(crate::tools::Crack))
+        .queue(Event::install(crate::tools::Hash))
+        .queue(Event::install(crate::tools::Rm))
+        .queue(Event::install(crate::tools::Grep))
+        .queue(Event::install(crate::tools::Logs))
+        .queue(Event::install(crate::tools::Scrub))
+        .queue(Event::install(crate::tools::Wipe))
+        .spawn(JobBoard::new(world.clone()))
+        .spawn(crate::forensics::Forensics::new(world.clone()))
+        .spawn(
+            npc!(
+                "yotie",
+                [
+                    say "hey": 500,
+                    say "hello": 500,
+                    say "hi": 500,
+                    say "my close personal friend": 1000,
+                    say "whose name I do not need to say": 1000,
+                    say "because we're so close and all": 1000,
+                    say "how you doin?": 1500,
+                    ask "good" => 1 [trust 1], "bad" => 2,
+                ],
+                [
+                    say "ey that's nice": 2000,
+                    say "glad you're doing well": 500,
+                    ask "thanks" => 3,
+                ],
+                [
+                    say "ey that's bad": 2000,
+                    say "sucks you're doing meh": 500,
+                    ask "thanks?" => 3,
+                ],
+                [
+                    say "anyway bye": 500,
+                    ask "uh ok" => 100,
+                ],
+            )
+            .in_world(world.clone()),
+        )
+        .spawn(
+            npc!(
+                "n0va",
+                [
+                    say "heard admin's got a new recruit": 3000,
+                    say "you need someone who can get into places?": 1000,
+                    ask "yeah" => 1, "i work alone" => 2 [trust -5],
+                ],
+                [
+                    say "cool. i'm in.": 500,
+                    say "`team send n0va <job>` when you've got something worth stealing": 500,
+                    join team,
+                    ask "will do" => 3 [trust 5],
+                ],
+                [
+                    say "suit yourself": 500,
+                    ask "wait, actually..." => 1,
+                ],
+                [
+                    say "don't keep me waiting": 500,
+                    ask,
+                ],
+            )
+            .in_world(world.clone())
+            .with_skill(Skill::Cracking, 6),
+        )
+        .spawn(
+            npc!(
+                "m4rrow",
+                [
+                    say "psst. you look like someone with things to sell": 5000,
+                    say "or someone who needs things. either way.": 1000,
+                    ask
+                        "selling data" => 1 [trade Deal::BuyData, else 2],
+                        "buying: cat (30tk)" => 3 [trade Deal::sell(crate::tools::Cat, 30), else 4],
+                        "buying: rig upgrade (40tk)" => 7 [trade Deal::upgrade(40), else 8],
+                        "just looking" => 5,
+                ],
+                [
+                    say "pleasure doing business": 500,
+                    ask "anything else?" => 6,
+                ],
+                [
+                    say "nothing there i haven't already got. steal something new.": 500,
+                    ask "ok" => 6,
+                ],
+                [
+                    say "there you go. don't tell anyone where you got it.": 500,
+                    ask "thanks" => 6,
+                ],
+                [
+                    say "either you're broke or you already have one": 500,
+                    ask "fine" => 6,
+                ],
+                [
+                    say "look all you want. touching costs extra.": 500,
+                    ask "ok" => 6,
+                ],
+                [
+                    say "what'll it be?": 500,
+                    ask
+                        "selling data" => 1 [trade Deal::BuyData, else 2],
+                        "buying: cat (30tk)" => 3 [trade Deal::sell(crate::tools::Cat, 30), else 4],
+                        "buying: rig upgrade (40tk)" => 7 [trade Deal::upgrade(40), else 8],
+                        "nothing" => 5,
+                ],
+                [
+                    say "new cards are in. your cracking'll run twice as fast.": 500,
+                    ask "thanks" => 6,
+                ],
+                [
+                    say "either you're broke or your rig's already the best i can get": 500,
+                    ask "fine" => 6,
+                ],
+            )
+            .in_world(world.clone()),
+        );
+    let lesson = world.tutorial.load(Ordering::Relaxed);
+    runner = if !Tutorial::finished(lesson) {
+        // new players get walked through the basics before admin gets on with things, and anyone who quit partway
+        // through picks up where they left off
+        runner.spawn(Tutorial::new(world.clone(), admin).resume(lesson))
+    } else {
+        runner.spawn(admin)
+    };
+    for &exploit in crate::tools::EXPLOITS {
+        runner = runner.queue(Event::install(exploit));
+    }
+    let taken = ["admin", "yotie", "n0va", "m4rrow"];
+    for persona in gen::scene(&mut rng, GENERATED_NPCS, &taken) {
+        runner = runner.spawn(persona.npc(world.clone()));
+    }
+    runner.load_run();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constants::gameplay::{ACCESS_LOG, AUTH_LOG, PLAYER_ADDR},
+        machine::Machine,
+    };
+
+    use super::*;
+
+    fn key(key: Key) -> Action {
+        Action::KeyPress { key }
+    }
+
+    #[test]
+    fn cutscenes_play_over_the_game() {
+        let mut game = Redshell::new(Arc::new(World::new(Default::default())));
+        let mut replies = Replies::default();
+        let script = Script::parse("say admin: who are you?\nchoose name\n  option n30n").unwrap();
+        game.message(&Event::PlayCutscene(script));
+        game.message(&Event::Tick);
+        // skip the line being revealed, then answer
+        game.input(key(Key::Char(' ')), &mut replies);
+        game.message(&Event::Tick);
+        game.input(key(Key::Enter), &mut replies);
+        assert!(game.cutscene.is_some());
+        game.message(&Event::Tick);
+        assert!(game.cutscene.is_none());
+        assert_eq!(game.state.player_name, "n30n");
+        // the cutscene ate all that input, so none of it reached the chat tab
+        assert!(replies._messages().is_empty());
+    }
+
+    #[test]
+    fn closing_tab_before_other_pane_keeps_split() {
+        let mut game = Redshell::new(Arc::new(World::new(Default::default())));
+        game.new_session();
+        game.new_session();
+        let last = game.tabs.len() - 1;
+        let name = game.tabs[last].app.name();
+        game.select(0);
+        game.split = Some(Split::new(Dir::Vertical, last));
+        assert!(game.close(last - 1));
+        let split = game.split.as_ref().expect("split should've been kept");
+        assert_eq!(split.other, last - 1);
+        assert_eq!(game.tabs[split.other].app.name(), name);
+        // closing the one that's actually in the other pane still drops it
+        assert!(game.close(last - 1));
+        assert!(game.split.is_none());
+    }
+
+    #[test]
+    fn world_and_known_hosts_survive_saving() {
+        let world = Arc::new(World::new(Default::default()));
+        world.populate(1234);
+        let mut hosts: Vec<String> = world.hosts.iter().map(|h| h.key().clone()).collect();
+        hosts.sort();
+        let found = hosts.iter().find(|&h| h != PLAYER_HOST).unwrap().clone();
+        world.net.learn(&found);
+        let save = Save::parse(&Redshell::new(world.clone()).save().to_string()).unwrap();
+
+        let seed = save.section("seed").and_then(|s| s.parse().ok()).unwrap();
+        let reloaded = Arc::new(World::new(Default::default()));
+        reloaded.populate(seed);
+        Redshell::new(reloaded.clone()).load(&save);
+        assert_eq!(reloaded.net.addr_of(&found), world.net.addr_of(&found));
+        assert!(reloaded.net.known.contains(&found));
+        assert_eq!(reloaded.net.known.len(), 2);
+    }
+
+    #[test]
+    fn what_the_player_did_to_machines_survives_saving() {
+        let world = Arc::new(World::new(Default::default()));
+        world.populate(1234);
+        let forensics = || crate::forensics::Forensics::new(world.clone());
+        forensics();
+        let corp = world
+            .audits
+            .iter()
+            .map(|a| a.key().clone())
+            .min()
+            .expect("should be corp machines");
+        let machine = world.host(&corp).unwrap();
+        let evidence = format!("wes from {}: read /etc/passwd", PLAYER_ADDR);
+        machine.log(ACCESS_LOG, &evidence);
+        machine.remove(AUTH_LOG).unwrap();
+        let port = machine.ports()[0].0;
+        machine.services.get_mut(&port).unwrap().vulns.clear();
+        world.alert(&corp, 3);
+        let save = Save::parse(&Redshell::new(world.clone()).save().to_string()).unwrap();
+
+        let reloaded = Arc::new(World::new(Default::default()));
+        reloaded.populate(1234);
+        Redshell::new(reloaded.clone()).load(&save);
+        let again = reloaded.host(&corp).unwrap();
+        let files = |m: &Machine| {
+            let mut files = m.files();
+            files.sort_unstable_by(|l, r| l.0.cmp(&r.0));
+            files
+        };
+        assert_eq!(files(&again), files(&machine));
+        assert!(again.services.get(&port).unwrap().vulns.is_empty());
+        assert_eq!(reloaded.alerts.get(&corp).map(|n| *n), Some(3));
+        // the evidence was logged after the last audit, so the next one still finds it
+        let findings = crate::forensics::Forensics::new(reloaded.clone()).audit();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            (findings[0].host.as_str(), findings[0].evidence),
+            (corp.as_str(), 1)
+        );
+    }
+
+    #[test]
+    fn tabs_and_tutorial_survive_saving() {
+        let mut save = Save::default();
+        save.set("player", "n30n".into());
+        // quit partway through the tutorial, with a couple of terminals open
+        save.set(
+            "tabs",
+            "chat\nsettings\nterminal 3\nterminal\nterminal 2".into(),
+        );
+        save.set("tutorial", "2".into());
+        let world = Arc::new(World::new(Default::default()));
+        let mut game = Redshell::new(world.clone());
+        game.load(&save);
+        let shown: Vec<_> = game.shown().map(|(_, t)| t.app.name()).collect();
+        assert_eq!(
+            shown,
+            ["chat", "settings", "terminal 3", "terminal", "terminal 2"]
+        );
+        assert_eq!(world.tutorial.load(Ordering::Relaxed), 2);
+        let resaved = game.save();
+        assert_eq!(resaved.section("tabs"), save.section("tabs"));
+        assert_eq!(resaved.section("tutorial"), Some("2"));
+
+        // but a brand new game still starts with just the basics
+        let mut game = Redshell::new(Arc::new(World::new(Default::default())));
+        game.load(&Save::default());
+        game.lock();
+        let shown: Vec<_> = game.shown().map(|(_, t)| t.app.name()).collect();
+        assert_eq!(shown, STARTING_TABS);
+    }
 }
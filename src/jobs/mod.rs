@@ -0,0 +1,509 @@
+//! Jobs that NPCs hand out to the player, and the agent that keeps track of whether they've been done.
+//!
+//! NPCs offer jobs in chat (see [`crate::npc::Offer`]), and picking the reply that accepts one queues an
+//! [`Event::JobAccepted`]. From there the [`JobBoard`] periodically checks each job's [`Objective`]s against the
+//! [`World`], and announces when it's been finished or the time's run out, so the NPC who offered it can react.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use tuig::{Agent, ControlFlow, Replies};
+
+use crate::{
+    clock::{self, GameTime},
+    economy::Tokens,
+    event::{BundledTool, Event},
+    tools::Tool,
+    world::World,
+};
+
+/// Something the player has to do to finish a job.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Objective {
+    /// Get a copy of a file from some machine onto the player's (anywhere on it)
+    Steal { host: String, path: String },
+    /// Make sure there's nothing at some path on some machine
+    Delete { host: String, path: String },
+    /// Make sure there's something at some path on some machine
+    Create { host: String, path: String },
+}
+
+impl Objective {
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn steal(host: &str, path: &str) -> Self {
+        Self::Steal {
+            host: host.into(),
+            path: path.into(),
+        }
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn delete(host: &str, path: &str) -> Self {
+        Self::Delete {
+            host: host.into(),
+            path: path.into(),
+        }
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn create(host: &str, path: &str) -> Self {
+        Self::Create {
+            host: host.into(),
+            path: path.into(),
+        }
+    }
+
+    /// A short description of what the player needs to do, for the jobs tab.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Steal { host, path } => format!("copy {}:{} to your machine", host, path),
+            Self::Delete { host, path } => format!("delete {} on {}", path, host),
+            Self::Create { host, path } => format!("create {} on {}", path, host),
+        }
+    }
+
+    /// Record whatever this objective needs to remember about the world as of when the job was accepted.
+    ///
+    /// For [`Self::Steal`], that's the contents of the file, so it still counts even if the original is changed or
+    /// deleted after the player copies it, and which of the player's files already had them, so those don't count.
+    /// Empty files can't be stolen, since any empty file would match.
+    fn snapshot(&self, world: &World) -> Option<Snapshot> {
+        match self {
+            Self::Steal { host, path } => {
+                let contents = world.host(host)?.read(path).ok()?.contents;
+                if contents.is_empty() {
+                    return None;
+                }
+                let had = world
+                    .player()
+                    .files()
+                    .into_iter()
+                    .filter(|(_, f)| f.contents == contents)
+                    .map(|(path, _)| path)
+                    .collect();
+                Some(Snapshot { contents, had })
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether the objective is done, given the world and the snapshot from [`Self::snapshot`].
+    fn done(&self, world: &World, snapshot: Option<&Snapshot>) -> bool {
+        let exists = |host: &str, path: &str| {
+            world
+                .host(host)
+                .map(|m| m.entry(path).is_ok())
+                .unwrap_or(false)
+        };
+        match self {
+            Self::Steal { .. } => match snapshot {
+                Some(snap) => world
+                    .player()
+                    .files()
+                    .iter()
+                    .any(|(path, f)| f.contents == snap.contents && !snap.had.contains(path)),
+                None => false,
+            },
+            Self::Delete { host, path } => !exists(host, path),
+            Self::Create { host, path } => exists(host, path),
+        }
+    }
+}
+
+/// What an [`Objective`] remembers about the world from when its job was accepted. See [`Objective::snapshot`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Snapshot {
+    /// What the file to steal had in it
+    contents: String,
+    /// The player's files which already had the same contents, by path
+    had: HashSet<String>,
+}
+
+/// Something the player gets for finishing a job.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reward {
+    /// A new tool on their machine, with the tool's name
+    Tool(&'static str, BundledTool),
+    /// A change in how a faction sees the player (which can be negative, if the job hurt them)
+    Reputation(String, i32),
+    /// Payment, straight into the player's wallet
+    Tokens(Tokens),
+}
+
+impl Reward {
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn tool(tool: impl Tool + 'static) -> Self {
+        Self::Tool(tool.name(), BundledTool::new(tool))
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn reputation(faction: &str, amount: i32) -> Self {
+        Self::Reputation(faction.into(), amount)
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn tokens(amount: i64) -> Self {
+        Self::Tokens(Tokens(amount))
+    }
+
+    /// A short description of the reward, for the jobs tab.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Tool(name, _) => format!("the {} tool", name),
+            Self::Reputation(faction, amount) => {
+                format!("{:+} reputation with {}", amount, faction)
+            }
+            Self::Tokens(amount) => amount.to_string(),
+        }
+    }
+
+    /// Actually give the player the reward.
+    fn grant(&self, world: &World, replies: &mut Replies<Event>) {
+        match self {
+            Self::Tool(_, tool) => {
+                replies.queue(Event::InstallTool(tool.clone()));
+            }
+            Self::Reputation(faction, amount) => {
+                world.relations.adjust_reputation(faction, *amount);
+            }
+            Self::Tokens(amount) => {
+                let transfer = world.wallet.deposit(*amount, "job payment");
+                replies.queue(Event::Transfer(transfer));
+            }
+        }
+    }
+}
+
+/// A single job, as offered by an NPC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    /// Uniquely identifies this job, so outcomes can be matched back up to it
+    pub id: String,
+    /// The name of the NPC who offered it
+    pub giver: String,
+    /// A short summary of the job
+    pub title: String,
+    /// What the player needs to do; all of them have to be done at once to finish the job
+    pub objectives: Vec<Objective>,
+    /// How much in-game time the player has to do it, if there's a limit at all
+    pub time_limit: Option<Duration>,
+    /// What the player gets for doing it
+    pub rewards: Vec<Reward>,
+}
+
+impl Job {
+    /// Create a new job with no objectives, no time limit, and no rewards.
+    pub fn new(id: &str, giver: &str, title: &str) -> Self {
+        Self {
+            id: id.into(),
+            giver: giver.into(),
+            title: title.into(),
+            objectives: vec![],
+            time_limit: None,
+            rewards: vec![],
+        }
+    }
+
+    /// Add an objective to the job
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objectives.push(objective);
+        self
+    }
+
+    /// Give the job a time limit
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Add a reward to the job
+    pub fn reward(mut self, reward: Reward) -> Self {
+        self.rewards.push(reward);
+        self
+    }
+}
+
+/// A job the player's accepted and hasn't finished (or failed) yet.
+struct Active {
+    job: Job,
+    deadline: Option<GameTime>,
+    /// From [`Objective::snapshot`], one per objective
+    snapshots: Vec<Option<Snapshot>>,
+    /// Which objectives were done as of the last check
+    done: Vec<bool>,
+}
+
+/// Keeps track of every job the player has accepted, announcing progress, completion, and failure.
+pub struct JobBoard {
+    world: Arc<World>,
+    active: Vec<Active>,
+    /// The IDs of every job ever accepted, so none of them can be taken twice
+    seen: HashSet<String>,
+}
+
+impl JobBoard {
+    pub fn new(world: Arc<World>) -> Self {
+        Self {
+            world,
+            active: vec![],
+            seen: Default::default(),
+        }
+    }
+
+    /// Start tracking a newly accepted job.
+    fn accept(&mut self, job: &Job, deadline: Option<GameTime>) {
+        if !self.seen.insert(job.id.clone()) {
+            return;
+        }
+        let snapshots = job
+            .objectives
+            .iter()
+            .map(|o| o.snapshot(&self.world))
+            .collect();
+        self.world.jobs.insert(job.id.clone(), job.clone());
+        self.active.push(Active {
+            job: job.clone(),
+            deadline,
+            snapshots,
+            done: vec![false; job.objectives.len()],
+        });
+    }
+
+    /// Check every active job against the world, as of the given time.
+    fn check(&mut self, now: GameTime, replies: &mut Replies<Event>) {
+        let world = &self.world;
+        self.active.retain_mut(|active| {
+            let done: Vec<_> = active
+                .job
+                .objectives
+                .iter()
+                .zip(&active.snapshots)
+                .map(|(o, s)| o.done(world, s.as_ref()))
+                .collect();
+            if done != active.done {
+                replies.queue(Event::JobProgress {
+                    id: active.job.id.clone(),
+                    done: done.clone(),
+                });
+                active.done = done;
+            }
+            if active.done.iter().all(|&d| d) {
+                replies.queue(Event::JobCompleted(active.job.id.clone()));
+                for reward in &active.job.rewards {
+                    reward.grant(world, replies);
+                }
+                world.jobs.remove(&active.job.id);
+                false
+            } else if active.deadline.map(|d| now > d).unwrap_or(false) {
+                replies.queue(Event::JobFailed(active.job.id.clone()));
+                world.jobs.remove(&active.job.id);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Stop tracking a job that something else has sabotaged.
+    fn drop_job(&mut self, id: &str) {
+        self.active.retain(|a| a.job.id != id);
+        self.world.jobs.remove(id);
+    }
+}
+
+impl Agent<Event> for JobBoard {
+    fn react(&mut self, event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        match event {
+            Event::JobAccepted { job, deadline } => self.accept(job, *deadline),
+            Event::Tick => self.check(clock::now(), replies),
+            Event::JobFailed(id) => self.drop_job(id),
+            _ => (),
+        }
+        ControlFlow::Continue
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{constants::gameplay::PLAYER_HOST, machine::Machine};
+
+    use super::*;
+
+    fn world() -> Arc<World> {
+        let world = World::new(Default::default());
+        let corp = Machine::default();
+        corp.mkdir("/corp/", true).expect("failed to make test dir");
+        corp.write("/corp/payroll.db", "everyone: $0".into())
+            .expect("failed to write test file");
+        corp.write("/corp/logs", "player was here".into())
+            .expect("failed to write test file");
+        world.hosts.insert("corp".into(), Arc::new(corp));
+        Arc::new(world)
+    }
+
+    fn job() -> Job {
+        Job::new("heist", "admin", "do crimes")
+            .objective(Objective::steal("corp", "/corp/payroll.db"))
+            .objective(Objective::delete("corp", "/corp/logs"))
+            .reward(Reward::reputation("corp", -10))
+    }
+
+    #[test]
+    fn objectives_check_world() {
+        let world = world();
+        let steal = Objective::steal("corp", "/corp/payroll.db");
+        let snap = steal.snapshot(&world);
+        assert_eq!(
+            snap.as_ref().map(|s| s.contents.as_str()),
+            Some("everyone: $0")
+        );
+        assert!(!steal.done(&world, snap.as_ref()));
+        world
+            .player()
+            .write("/loot", "everyone: $0".into())
+            .expect("failed to write test file");
+        assert!(steal.done(&world, snap.as_ref()));
+
+        let delete = Objective::delete("corp", "/corp/logs");
+        assert!(!delete.done(&world, None));
+        world
+            .host("corp")
+            .unwrap()
+            .remove("/corp/logs")
+            .expect("failed to delete test file");
+        assert!(delete.done(&world, None));
+
+        let create = Objective::create(PLAYER_HOST, "/loot");
+        assert!(create.done(&world, None));
+    }
+
+    #[test]
+    fn steal_survives_original_deleted() {
+        let world = world();
+        let steal = Objective::steal("corp", "/corp/payroll.db");
+        let snap = steal.snapshot(&world);
+        world
+            .player()
+            .write("/loot", "everyone: $0".into())
+            .expect("failed to write test file");
+        world
+            .host("corp")
+            .unwrap()
+            .write("/corp/payroll.db", "everyone: $1".into())
+            .expect("failed to write test file");
+        assert!(steal.done(&world, snap.as_ref()));
+    }
+
+    #[test]
+    fn steal_needs_a_new_copy() {
+        let world = world();
+        // already had it before the job, so that copy doesn't count
+        let player = world.player();
+        player.write("/old", "everyone: $0".into()).unwrap();
+        let steal = Objective::steal("corp", "/corp/payroll.db");
+        let snap = steal.snapshot(&world);
+        assert!(!steal.done(&world, snap.as_ref()));
+        player.write("/new", "everyone: $0".into()).unwrap();
+        assert!(steal.done(&world, snap.as_ref()));
+
+        // and an empty file would match anything `touch`ed
+        let corp = world.host("corp").unwrap();
+        corp.write("/corp/empty", String::new()).unwrap();
+        let steal = Objective::steal("corp", "/corp/empty");
+        let snap = steal.snapshot(&world);
+        assert_eq!(snap, None);
+        player.write("/touched", String::new()).unwrap();
+        assert!(!steal.done(&world, snap.as_ref()));
+    }
+
+    #[test]
+    fn board_reports_progress_and_completion() {
+        let world = world();
+        let mut board = JobBoard::new(world.clone());
+        board.accept(&job(), None);
+
+        let mut replies = Replies::default();
+        board.check(GameTime(0), &mut replies);
+        assert!(replies._messages().is_empty());
+
+        world
+            .player()
+            .write("/loot", "everyone: $0".into())
+            .expect("failed to write test file");
+        let mut replies = Replies::default();
+        board.check(GameTime(0), &mut replies);
+        assert_eq!(
+            replies._messages(),
+            &[Event::JobProgress {
+                id: "heist".into(),
+                done: vec![true, false],
+            }]
+        );
+
+        world
+            .host("corp")
+            .unwrap()
+            .remove("/corp/logs")
+            .expect("failed to delete test file");
+        let mut replies = Replies::default();
+        board.check(GameTime(0), &mut replies);
+        assert_eq!(
+            replies._messages(),
+            &[
+                Event::JobProgress {
+                    id: "heist".into(),
+                    done: vec![true, true],
+                },
+                Event::JobCompleted("heist".into()),
+            ]
+        );
+        assert!(board.active.is_empty());
+        assert!(world.jobs.is_empty());
+        assert_eq!(world.relations.reputation("corp"), -10);
+    }
+
+    #[test]
+    fn board_fails_late_jobs() {
+        let mut board = JobBoard::new(world());
+        board.accept(&job(), Some(GameTime(100)));
+
+        let mut replies = Replies::default();
+        board.check(GameTime(100), &mut replies);
+        assert!(replies._messages().is_empty());
+
+        let mut replies = Replies::default();
+        board.check(GameTime(101), &mut replies);
+        assert_eq!(replies._messages(), &[Event::JobFailed("heist".into())]);
+        assert!(board.active.is_empty());
+    }
+
+    #[test]
+    fn board_ignores_repeated_jobs() {
+        let mut board = JobBoard::new(world());
+        board.accept(&job(), None);
+        board.accept(&job(), None);
+        assert_eq!(board.active.len(), 1);
+    }
+
+    #[test]
+    fn token_rewards_pay_player() {
+        let world = world();
+        let before = world.wallet.balance();
+        let mut replies = Replies::default();
+        Reward::tokens(30).grant(&world, &mut replies);
+        assert_eq!(world.wallet.balance(), Tokens(before.0 + 30));
+        assert!(matches!(replies._messages(), [Event::Transfer(t)] if t.amount == Tokens(30)));
+    }
+
+    #[test]
+    fn board_drops_sabotaged_jobs() {
+        let world = world();
+        let mut board = JobBoard::new(world.clone());
+        board.accept(&job(), None);
+        assert!(world.jobs.contains_key("heist"));
+
+        let mut replies = Replies::default();
+        board.react(&Event::JobFailed("heist".into()), &mut replies);
+        assert!(board.active.is_empty());
+        assert!(world.jobs.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+//! Password hashing, as far as the CyberZone's concerned: salted hashes in `/etc/shadow`, and how to guess at them.
+//!
+//! None of this is real cryptography. Hashes look the part (`$6$salt$digest`, like crypt(3)), and the same password and
+//! salt always hash the same way, but what makes one kind slower to crack than another is just [`HashKind::cost`].
+
+use std::fmt;
+
+use crate::rng::Rng;
+
+/// What a salt can be made of, same as crypt(3)
+const SALT_CHARS: &[u8] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// The biggest number cracking tacks onto the end of each word in a wordlist
+pub const MAX_SUFFIX: usize = 9999;
+
+/// A kind of password hash, as identified by the `$id$` at the start of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HashKind {
+    Md5,
+    Sha256,
+    Sha512,
+}
+
+impl HashKind {
+    /// The kind of hash a machine with this security level uses
+    pub fn for_security(security: u32) -> Self {
+        match security {
+            0..=3 => Self::Md5,
+            4..=6 => Self::Sha256,
+            _ => Self::Sha512,
+        }
+    }
+
+    /// Work out what kind a hash is, if it's any kind at all
+    pub fn identify(hash: &str) -> Option<Self> {
+        let mut parts = hash.split('$');
+        let kind = match (parts.next(), parts.next()) {
+            (Some(""), Some("1")) => Self::Md5,
+            (Some(""), Some("5")) => Self::Sha256,
+            (Some(""), Some("6")) => Self::Sha512,
+            _ => return None,
+        };
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(salt), Some(digest), None)
+                if !salt.is_empty() && digest.len() == kind.words() * 16 =>
+            {
+                Some(kind)
+            }
+            _ => None,
+        }
+    }
+
+    /// The `$id$` this kind's hashes start with
+    fn id(&self) -> &'static str {
+        match self {
+            Self::Md5 => "1",
+            Self::Sha256 => "5",
+            Self::Sha512 => "6",
+        }
+    }
+
+    /// How many 64-bit words long this kind's digests are
+    fn words(&self) -> usize {
+        match self {
+            Self::Md5 => 2,
+            Self::Sha256 => 4,
+            Self::Sha512 => 8,
+        }
+    }
+
+    /// How much work it is to check one guess against a hash of this kind, compared to the cheapest
+    pub fn cost(&self) -> u64 {
+        match self {
+            Self::Md5 => 1,
+            Self::Sha256 => 8,
+            Self::Sha512 => 40,
+        }
+    }
+}
+
+impl fmt::Display for HashKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Md5 => "md5crypt",
+            Self::Sha256 => "sha256crypt",
+            Self::Sha512 => "sha512crypt",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Come up with a random salt
+pub fn salt(rng: &mut Rng) -> String {
+    (0..8).map(|_| *rng.pick(SALT_CHARS) as char).collect()
+}
+
+/// Hash a password with the given salt
+pub fn hash(kind: HashKind, salt: &str, password: &str) -> String {
+    // FNV-1a to squash everything into a seed, then let the rng stretch it out into a digest
+    let mut seed: u64 = 0xcbf29ce484222325;
+    for b in kind
+        .id()
+        .bytes()
+        .chain(salt.bytes())
+        .chain([b'$'])
+        .chain(password.bytes())
+    {
+        seed = (seed ^ b as u64).wrapping_mul(0x100000001b3);
+    }
+    let mut rng = Rng::seeded(seed);
+    let digest: String = (0..kind.words())
+        .map(|_| format!("{:016x}", rng.next_u64()))
+        .collect();
+    format!("${}${}${}", kind.id(), salt, digest)
+}
+
+/// Check whether a password matches a hash
+pub fn verify(hash: &str, password: &str) -> bool {
+    match (HashKind::identify(hash), hash.split('$').nth(2)) {
+        (Some(kind), Some(salt)) => self::hash(kind, salt, password) == hash,
+        _ => false,
+    }
+}
+
+/// Pull every user and their password hash out of a shadow file, skipping any that aren't hashes (e.g. locked
+/// accounts, or lines that aren't shadow entries at all)
+pub fn parse_shadow(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            match (fields.next(), fields.next()) {
+                (Some(user), Some(hash)) if HashKind::identify(hash).is_some() => {
+                    Some((user.to_owned(), hash.to_owned()))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// How many guesses there are to make with a wordlist this long: every word as-is, then every word with every number
+/// up to [`MAX_SUFFIX`] on the end
+pub fn guesses(words: usize) -> usize {
+    words * (1 + MAX_SUFFIX)
+}
+
+/// The `i`th guess to make with a wordlist, in order. See [`guesses`].
+pub fn guess(words: &[String], i: usize) -> Option<String> {
+    if i >= guesses(words.len()) {
+        None
+    } else if i < words.len() {
+        Some(words[i].clone())
+    } else {
+        let i = i - words.len();
+        Some(format!("{}{}", words[i % words.len()], i / words.len() + 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hashes_verify_and_identify() {
+        let mut rng = Rng::seeded(3);
+        for kind in [HashKind::Md5, HashKind::Sha256, HashKind::Sha512] {
+            let salt = salt(&mut rng);
+            let hashed = hash(kind, &salt, "hunter2");
+            assert!(hashed.starts_with(&format!("${}${}$", kind.id(), salt)));
+            assert_eq!(HashKind::identify(&hashed), Some(kind));
+            assert!(verify(&hashed, "hunter2"));
+            assert!(!verify(&hashed, "hunter3"));
+            // same password, different salt, different hash
+            assert_ne!(
+                hash(kind, "saltsalt", "hunter2"),
+                hash(kind, "peppered", "hunter2")
+            );
+        }
+        assert_eq!(HashKind::identify("hunter2"), None);
+        assert_eq!(HashKind::identify("$6$salt$tooshort"), None);
+        assert_eq!(HashKind::identify("!"), None);
+    }
+
+    #[test]
+    fn shadow_skips_locked_accounts() {
+        let hashed = hash(HashKind::Md5, "abcdefgh", "letmein");
+        let shadow = format!(
+            "root:{}:19345:0:99999:7:::\ndaemon:*:19345:0:99999:7:::\nnonsense\n",
+            hashed
+        );
+        assert_eq!(parse_shadow(&shadow), vec![("root".to_owned(), hashed)]);
+    }
+
+    #[test]
+    fn guesses_try_plain_words_first() {
+        let words = vec!["dragon".to_owned(), "monkey".to_owned()];
+        assert_eq!(guesses(2), 2 * 10000);
+        assert_eq!(guess(&words, 0).as_deref(), Some("dragon"));
+        assert_eq!(guess(&words, 1).as_deref(), Some("monkey"));
+        assert_eq!(guess(&words, 2).as_deref(), Some("dragon1"));
+        assert_eq!(guess(&words, 5).as_deref(), Some("monkey2"));
+        assert_eq!(guess(&words, guesses(2) - 1).as_deref(), Some("monkey9999"));
+        assert_eq!(guess(&words, guesses(2)), None);
+        assert_eq!(guess(&[], 0), None);
+    }
+}
@@ -0,0 +1,640 @@
+//! Procedural generation of the machines that fill out the CyberZone.
+//!
+//! Everything here is driven by an [`Rng`], so the same seed always produces exactly the same machine: the same
+//! hostname, users, passwords, files, tools, and security level. That keeps worlds reproducible, and tests honest.
+
+use std::sync::Arc;
+
+use crate::{
+    clock::GameTime,
+    constants::gameplay::{AUTH_LOG, MAX_SECURITY, START_TIME},
+    rng::Rng,
+    tools::{Cat, Cd, Download, Exit, Grep, Logs, Ls, Mkdir, Rm, Tool, Touch, Upload},
+};
+
+use super::{
+    crypt::{self, HashKind},
+    Machine, Service, User, Vuln,
+};
+
+const FIRST_NAMES: &[&str] = &[
+    "alex", "bea", "cam", "dana", "eli", "fran", "gus", "hana", "ivan", "jo", "kai", "lena",
+    "milo", "nina", "omar", "pia", "quinn", "rosa", "sam", "tess", "umar", "vera", "wes", "xena",
+    "yuri", "zoe",
+];
+const LAST_NAMES: &[&str] = &[
+    "adler", "brook", "chen", "diaz", "evans", "faro", "grant", "holt", "ito", "jensen", "kim",
+    "lund", "moss", "novak", "ortiz", "park", "reyes", "stone", "tanaka", "voss", "wolfe", "young",
+];
+const CORP_PREFIXES: &[&str] = &[
+    "hexa", "omni", "neo", "cyber", "quanta", "vertex", "helix", "nova", "apex", "zenith",
+    "stellar", "carbon",
+];
+const CORP_SUFFIXES: &[&str] = &[
+    "corp", "dyne", "tek", "sys", "logic", "works", "labs", "net",
+];
+const CORP_ROLES: &[&str] = &["fs", "mail", "db", "web", "hr", "dev"];
+/// The software each kind of service might be running: the service, the version, and the exploits that work on it.
+///
+/// The last version of each service is always the fully patched one.
+const VERSIONS: &[(&str, &str, &[&str])] = &[
+    ("ssh", "OpenSSH_6.6", &["sshock"]),
+    ("ssh", "OpenSSH_7.2", &["sshock"]),
+    ("ssh", "OpenSSH_9.3", &[]),
+    ("http", "Apache/2.2.14", &["cgibash"]),
+    ("http", "nginx/1.4.0", &["cgibash"]),
+    ("http", "nginx/1.25.1", &[]),
+    ("https", "Apache/2.2.14", &["cgibash"]),
+    ("https", "nginx/1.25.1", &[]),
+    ("smtp", "Exim 4.87", &["smtpwn"]),
+    ("smtp", "Postfix 3.8", &[]),
+    ("imap", "Dovecot 2.3", &[]),
+    ("postgres", "PostgreSQL 9.3", &["pgsqueeze"]),
+    ("postgres", "PostgreSQL 15.4", &[]),
+    ("smb", "Samba 3.5", &["eternalred"]),
+    ("smb", "Samba 4.18", &[]),
+    ("rdp", "ZoneDesk 7", &["keyhole"]),
+    ("rdp", "ZoneDesk 11", &[]),
+];
+const CIVILIAN_DEVICES: &[&str] = &["laptop", "pc", "desktop", "box", "rig"];
+const PROJECTS: &[&str] = &[
+    "aurora",
+    "basilisk",
+    "cerberus",
+    "driftwood",
+    "ember",
+    "foxglove",
+    "gemini",
+    "hydra",
+];
+const PASSWORD_WORDS: &[&str] = &[
+    "password", "letmein", "dragon", "sunshine", "monkey", "shadow", "summer", "qwerty", "hunter",
+    "welcome", "football", "princess", "trustno1", "coffee",
+];
+/// What people call the wordlists they leave lying around
+const WORDLISTS: &[&str] = &["common", "leaked", "top1000", "rockyou-lite", "passwords"];
+const SUBJECTS: &[&str] = &[
+    "Q3 numbers",
+    "lunch?",
+    "server outage",
+    "new hire paperwork",
+    "your password expires soon",
+    "offsite next week",
+    "budget cuts",
+    "the thing we talked about",
+];
+const BODIES: &[&str] = &[
+    "Can you send me the latest figures before the meeting?",
+    "The file server went down again last night. IT says it's fixed, but I don't believe them.",
+    "Reminder that everyone needs to change their password by Friday. Please don't write it down this time.",
+    "Let's keep this between us until the announcement.",
+    "Attached is the draft. Don't forward it outside the team.",
+    "Are we still on for tomorrow?",
+    "Security audit is coming up. Please clean up anything you shouldn't have on your home directory.",
+    "I accidentally deleted the backup. Please don't tell anyone.",
+];
+
+/// What sort of machine to generate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A corporate server, with a bunch of employees, finances, and generally better security
+    Corp,
+    /// Someone's personal computer, with one or two users and not a lot of protection
+    Civilian,
+}
+
+/// Everything about one person with an account, as generated.
+struct Person {
+    first: String,
+    last: String,
+    user: User,
+}
+
+/// Generates a single machine, keeping track of everything it needs along the way.
+struct Builder<'r> {
+    rng: &'r mut Rng,
+    kind: Kind,
+    hostname: String,
+    /// What a corp machine's for (one of [`CORP_ROLES`]), which decides what services it runs
+    role: Option<&'static str>,
+    /// The organization (or household) the machine belongs to, for email addresses and the like
+    org: String,
+    people: Vec<Person>,
+    machine: Machine,
+}
+
+impl<'r> Builder<'r> {
+    fn new(kind: Kind, rng: &'r mut Rng) -> Self {
+        let mut role = None;
+        let (org, hostname) = match kind {
+            Kind::Corp => {
+                let org = format!("{}{}", rng.pick(CORP_PREFIXES), rng.pick(CORP_SUFFIXES));
+                role = Some(*rng.pick(CORP_ROLES));
+                let host = format!("{}-{}{:02}", org, role.unwrap(), rng.between(1, 20));
+                (org, host)
+            }
+            Kind::Civilian => {
+                let last = rng.pick(LAST_NAMES).to_string();
+                let host = format!(
+                    "{}-{}{}",
+                    last,
+                    rng.pick(CIVILIAN_DEVICES),
+                    rng.between(1, 99)
+                );
+                (last, host)
+            }
+        };
+        let security = match kind {
+            Kind::Corp => rng.between(3, MAX_SECURITY as u64),
+            Kind::Civilian => rng.between(1, 5),
+        } as u32;
+        Self {
+            rng,
+            kind,
+            hostname,
+            role,
+            org,
+            people: vec![],
+            machine: Machine {
+                security,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Come up with a password about as strong as the machine's security level implies
+    fn password(&mut self) -> String {
+        let security = self.machine.security;
+        let word = self.rng.pick(PASSWORD_WORDS).to_string();
+        if security <= 3 {
+            word
+        } else if security <= 6 {
+            format!("{}{}", word, self.rng.between(1, 9999))
+        } else {
+            const CHARS: &[u8] = b"abcdefghijkmnpqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789!@#$%";
+            (0..6 + security)
+                .map(|_| *self.rng.pick(CHARS) as char)
+                .collect()
+        }
+    }
+
+    fn people(&mut self) {
+        let count = match self.kind {
+            Kind::Corp => self.rng.between(3, 8),
+            Kind::Civilian => self.rng.between(1, 2),
+        };
+        for i in 0..count {
+            let first = self.rng.pick(FIRST_NAMES).to_string();
+            let last = match self.kind {
+                Kind::Corp => self.rng.pick(LAST_NAMES).to_string(),
+                Kind::Civilian => self.org.clone(),
+            };
+            let mut name = format!("{}{}", &first[..1], last);
+            if self.people.iter().any(|p| p.user.name == name) {
+                name = format!("{}{}", name, i);
+            }
+            let user = User {
+                name,
+                password: self.password(),
+                // the first user on a personal machine is almost always an admin
+                admin: self.kind == Kind::Civilian && i == 0,
+            };
+            self.people.push(Person { first, last, user });
+        }
+        let root = User {
+            name: "root".into(),
+            password: self.password(),
+            admin: true,
+        };
+        for user in self.people.iter().map(|p| &p.user).chain([&root]) {
+            self.machine.users.insert(user.name.clone(), user.clone());
+        }
+    }
+
+    fn write(&self, path: &str, contents: String) {
+        let (dir, _) = path.rsplit_once('/').expect("generated paths are absolute");
+        self.machine
+            .mkdir(&format!("{}/", dir), true)
+            .expect("generated directories never collide with files");
+        self.machine
+            .write(path, contents)
+            .expect("generated files never collide with directories");
+    }
+
+    fn etc(&mut self) {
+        self.write("/etc/hostname", format!("{}\n", self.hostname));
+        let mut passwd = String::from("root:x:0:0:root:/root:/bin/sh\n");
+        for (i, p) in self.people.iter().enumerate() {
+            passwd += &format!(
+                "{0}:x:{1}:{1}:{2} {3}:/home/{0}:/bin/sh\n",
+                p.user.name,
+                1000 + i,
+                p.first,
+                p.last
+            );
+        }
+        self.write("/etc/passwd", passwd);
+        let kind = HashKind::for_security(self.machine.security);
+        let mut shadow = String::new();
+        let mut users: Vec<_> = self
+            .machine
+            .users
+            .iter()
+            .map(|u| u.value().clone())
+            .collect();
+        users.sort_by(|l, r| l.name.cmp(&r.name));
+        for user in users {
+            let salt = crypt::salt(self.rng);
+            let hashed = crypt::hash(kind, &salt, &user.password);
+            shadow += &format!("{}:{}:19345:0:99999:7:::\n", user.name, hashed);
+        }
+        self.write("/etc/shadow", shadow);
+        if self.kind == Kind::Corp {
+            let motd = format!(
+                "Welcome to {}. Unauthorized access is prohibited and will be prosecuted.\n",
+                self.org
+            );
+            self.write("/etc/motd", motd);
+        }
+    }
+
+    fn email(&mut self, to: usize) -> String {
+        let from = self.rng.below(self.people.len() as u64) as usize;
+        let domain = format!("{}.net", self.org);
+        let subject = self.rng.pick(SUBJECTS);
+        let body = self.rng.pick(BODIES);
+        format!(
+            "From: {}@{}\nTo: {}@{}\nSubject: {}\n\nHi {},\n\n{}\n\n- {}\n",
+            self.people[from].user.name,
+            domain,
+            self.people[to].user.name,
+            domain,
+            subject,
+            self.people[to].first,
+            body,
+            self.people[from].first,
+        )
+    }
+
+    fn homes(&mut self) {
+        for i in 0..self.people.len() {
+            let home = format!("/home/{}", self.people[i].user.name);
+            for n in 0..self.rng.between(1, 4) {
+                let email = self.email(i);
+                self.write(&format!("{}/mail/{}.eml", home, n), email);
+            }
+            // the weaker the security, the more likely people are to leave their passwords lying around
+            let careless =
+                (MAX_SECURITY - self.machine.security) as f32 / (MAX_SECURITY as f32 * 2.0);
+            if self.rng.chance(careless) {
+                let note = format!("don't forget!!\n{}\n", self.people[i].user.password);
+                self.write(&format!("{}/notes.txt", home), note);
+            }
+            if self.kind == Kind::Civilian {
+                for n in 0..self.rng.between(2, 6) {
+                    self.write(
+                        &format!("{}/photos/IMG_{:04}.jpg", home, n),
+                        "[binary data]".into(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn logs(&mut self) {
+        let mut times: Vec<_> = (0..self.rng.between(5, 15))
+            .map(|_| GameTime(self.rng.below(START_TIME.0)))
+            .collect();
+        times.sort();
+        let mut auth = String::new();
+        for time in times {
+            let who = &self.rng.pick(&self.people).user.name;
+            let ip = format!("10.0.{}.{}", self.rng.below(256), self.rng.between(1, 254));
+            let result = if self.rng.chance(0.2) {
+                "failed"
+            } else {
+                "accepted"
+            };
+            auth += &format!(
+                "{} sshd: {} password for {} from {}\n",
+                time, result, who, ip
+            );
+        }
+        self.write(AUTH_LOG, auth);
+    }
+
+    /// Maybe leave a wordlist lying around, with some of the passwords people actually use and a lot of chaff
+    fn wordlist(&mut self) {
+        let chance = match self.kind {
+            Kind::Corp => 0.5,
+            Kind::Civilian => 0.3,
+        };
+        if !self.rng.chance(chance) {
+            return;
+        }
+        let mut words: Vec<&str> = PASSWORD_WORDS
+            .iter()
+            .chain(FIRST_NAMES)
+            .chain(PROJECTS)
+            .copied()
+            .filter(|_| self.rng.chance(0.5))
+            .collect();
+        self.rng.shuffle(&mut words);
+        let name = self.rng.pick(WORDLISTS);
+        let path = match self.kind {
+            Kind::Corp => format!("/srv/shared/{}.dict", name),
+            Kind::Civilian => {
+                let owner = &self.rng.pick(&self.people).user.name;
+                format!("/home/{}/downloads/{}.dict", owner, name)
+            }
+        };
+        self.write(&path, words.join("\n") + "\n");
+    }
+
+    fn corp_files(&mut self) {
+        let mut payroll = String::from("name,username,salary\n");
+        for p in &self.people {
+            let salary = self.rng.between(40, 250) * 1000;
+            payroll += &format!("{} {},{},{}\n", p.first, p.last, p.user.name, salary);
+        }
+        self.write("/srv/finance/payroll.csv", payroll);
+        let project = self.rng.pick(PROJECTS);
+        let lead = self.rng.pick(&self.people).first.clone();
+        let readme = format!(
+            "Project {}\n\nLead: {}\nStatus: confidential\n\nDo not discuss outside {}.\n",
+            project, lead, self.org
+        );
+        self.write(&format!("/srv/projects/{}/README", project), readme);
+        // badly secured corps have a shared credentials file, because of course they do
+        if self.machine.security <= 5 {
+            let mut creds = String::from("# shared logins, DO NOT SHARE\n");
+            for p in self.people.iter().filter(|_| self.rng.chance(0.5)) {
+                creds += &format!("{}: {}\n", p.user.name, p.user.password);
+            }
+            self.write("/srv/shared/credentials.txt", creds);
+        }
+    }
+
+    fn tools(&mut self) {
+        let mut tools: Vec<Arc<dyn Tool>> = vec![
+            Arc::new(Ls),
+            Arc::new(Cd),
+            Arc::new(Cat),
+            Arc::new(Grep),
+            Arc::new(Logs),
+            Arc::new(Download),
+            Arc::new(Upload),
+            Arc::new(Exit),
+        ];
+        if self.kind == Kind::Corp {
+            tools.push(Arc::new(Mkdir));
+            tools.push(Arc::new(Touch));
+            tools.push(Arc::new(Rm));
+        }
+        for tool in tools {
+            self.machine.tools.insert(tool.name().into(), tool);
+        }
+    }
+
+    fn services(&mut self) {
+        let mut services = vec![];
+        // civilians mostly don't run anything, but corps always let their admins in
+        if self.kind == Kind::Corp || self.rng.chance(0.3) {
+            services.push((22, "ssh"));
+        }
+        match self.role {
+            Some("web") => services.extend([(80, "http"), (443, "https")]),
+            Some("mail") => services.extend([(25, "smtp"), (143, "imap")]),
+            Some("db") => services.push((5432, "postgres")),
+            Some("fs") => services.push((445, "smb")),
+            Some(_) => services.push((8080, "http")),
+            None if self.rng.chance(0.2) => services.push((3389, "rdp")),
+            None => (),
+        }
+        for (port, name) in services {
+            let service = self.service(name);
+            self.machine.services.insert(port, service);
+        }
+    }
+
+    /// Pick what software a service is running. The better the security, the more likely it's fully patched.
+    fn service(&mut self, name: &str) -> Service {
+        let versions: Vec<_> = VERSIONS.iter().filter(|(s, _, _)| *s == name).collect();
+        let patched = self
+            .rng
+            .chance(self.machine.security as f32 / MAX_SECURITY as f32);
+        let &(_, version, exploits) = if patched {
+            versions[versions.len() - 1]
+        } else {
+            *self.rng.pick(&versions)
+        };
+        // whoever the service runs as is who gets a shell out of it
+        let user = match name {
+            "ssh" => self.rng.pick(&self.people).user.name.clone(),
+            "http" | "https" => "www-data".into(),
+            "smtp" | "imap" => "mail".into(),
+            "postgres" => "postgres".into(),
+            _ => "root".into(),
+        };
+        Service {
+            name: name.into(),
+            version: version.into(),
+            vulns: exploits
+                .iter()
+                .map(|&exploit| Vuln {
+                    exploit: exploit.into(),
+                    user: user.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn build(mut self) -> (String, Machine) {
+        self.people();
+        self.etc();
+        self.homes();
+        self.logs();
+        self.wordlist();
+        if self.kind == Kind::Corp {
+            self.corp_files();
+        }
+        self.tools();
+        self.services();
+        (self.hostname, self.machine)
+    }
+}
+
+/// Generate a machine of the given kind, returning it along with its hostname.
+pub fn machine(kind: Kind, rng: &mut Rng) -> (String, Machine) {
+    Builder::new(kind, rng).build()
+}
+
+/// Generate a machine of the given kind from a seed.
+pub fn seeded(kind: Kind, seed: u64) -> (String, Machine) {
+    machine(kind, &mut Rng::seeded(seed))
+}
+
+/// Generate a whole batch of machines, with unique hostnames.
+pub fn machines(corps: usize, civilians: usize, rng: &mut Rng) -> Vec<(String, Machine)> {
+    let mut kinds = vec![Kind::Corp; corps];
+    kinds.extend(vec![Kind::Civilian; civilians]);
+    let mut made: Vec<(String, Machine)> = vec![];
+    for kind in kinds {
+        let (mut host, machine) = machine(kind, &mut rng.fork());
+        if made.iter().any(|(h, _)| h == &host) {
+            host = format!("{}-{}", host, made.len());
+        }
+        made.push((host, machine));
+    }
+    made
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted_files(machine: &Machine) -> Vec<(String, String)> {
+        let mut files: Vec<_> = machine
+            .files()
+            .into_iter()
+            .map(|(p, f)| (p, f.contents))
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn same_seed_same_machine() {
+        for kind in [Kind::Corp, Kind::Civilian] {
+            let (h1, m1) = seeded(kind, 1337);
+            let (h2, m2) = seeded(kind, 1337);
+            assert_eq!(h1, h2);
+            assert_eq!(sorted_files(&m1), sorted_files(&m2));
+            assert_eq!(m1.security, m2.security);
+            assert_eq!(m1.ports(), m2.ports());
+            for user in m1.users.iter() {
+                assert_eq!(m2.users.get(user.key()).as_deref(), Some(user.value()));
+            }
+        }
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let (_, m1) = seeded(Kind::Corp, 1);
+        let (_, m2) = seeded(Kind::Corp, 2);
+        assert_ne!(sorted_files(&m1), sorted_files(&m2));
+    }
+
+    #[test]
+    fn corp_machines_look_like_corps() {
+        for seed in 0..20 {
+            let (host, m) = seeded(Kind::Corp, seed);
+            assert_eq!(
+                m.read("/etc/hostname").unwrap().contents,
+                format!("{}\n", host)
+            );
+            assert!(m.read("/srv/finance/payroll.csv").is_ok());
+            assert!(m.read("/var/log/auth.log").is_ok());
+            assert!((3..=MAX_SECURITY).contains(&m.security));
+            assert!(m.users.get("root").unwrap().admin);
+            let passwd = m.read("/etc/passwd").unwrap().contents;
+            for user in m.users.iter() {
+                assert!(passwd.contains(&format!("{}:x:", user.key())));
+                if user.key() != "root" {
+                    assert!(m.readdir(&format!("/home/{}/mail/", user.key())).is_ok());
+                }
+            }
+            assert!(m.tools.contains_key("ls"));
+            assert!(m.tools.contains_key("touch"));
+            assert_eq!(m.services.get(&22).unwrap().name, "ssh");
+            assert!(m.services.len() >= 2);
+        }
+    }
+
+    #[test]
+    fn civilian_machines_look_like_civilians() {
+        for seed in 0..20 {
+            let (_, m) = seeded(Kind::Civilian, seed);
+            assert!(m.read("/srv/finance/payroll.csv").is_err());
+            assert!((1..=5).contains(&m.security));
+            assert!(m.users.len() <= 3);
+            assert!(m.tools.contains_key("cat"));
+            assert!(!m.tools.contains_key("touch"));
+        }
+    }
+
+    #[test]
+    fn weak_machines_have_weak_passwords() {
+        for seed in 0..50 {
+            let (_, m) = seeded(Kind::Civilian, seed);
+            if m.security <= 3 {
+                for user in m.users.iter() {
+                    assert!(PASSWORD_WORDS.contains(&user.password.as_str()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn services_have_known_versions_and_exploits() {
+        let mut vulnerable = 0;
+        for seed in 0..50 {
+            for kind in [Kind::Corp, Kind::Civilian] {
+                let (_, m) = seeded(kind, seed);
+                for (_, service) in m.ports() {
+                    assert!(VERSIONS
+                        .iter()
+                        .any(|(s, v, _)| *s == service.name && *v == service.version));
+                    for vuln in &service.vulns {
+                        assert!(crate::tools::EXPLOITS.iter().any(
+                            |e| e.name == vuln.exploit && e.services.contains(&&*service.name)
+                        ));
+                        assert!(!vuln.user.is_empty());
+                    }
+                    if !service.vulns.is_empty() {
+                        vulnerable += 1;
+                    }
+                }
+            }
+        }
+        // most things should be patched, but there has to be something to break into
+        assert!(vulnerable > 10);
+    }
+
+    #[test]
+    fn shadow_hashes_every_password() {
+        for seed in 0..20 {
+            let (_, m) = seeded(Kind::Corp, seed);
+            let shadow = crypt::parse_shadow(&m.read("/etc/shadow").unwrap().contents);
+            assert_eq!(shadow.len(), m.users.len());
+            for (name, hashed) in shadow {
+                let user = m.users.get(&name).unwrap();
+                assert!(crypt::verify(&hashed, &user.password));
+                assert_eq!(
+                    HashKind::identify(&hashed),
+                    Some(HashKind::for_security(m.security))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wordlists_turn_up() {
+        let dicts = (0..20)
+            .flat_map(|seed| [seeded(Kind::Corp, seed).1, seeded(Kind::Civilian, seed).1])
+            .flat_map(|m| m.files())
+            .filter(|(path, _)| path.ends_with(".dict"))
+            .count();
+        assert!(dicts > 5);
+    }
+
+    #[test]
+    fn batches_have_unique_hosts() {
+        let made = machines(10, 10, &mut Rng::seeded(5));
+        assert_eq!(made.len(), 20);
+        let mut hosts: Vec<_> = made.iter().map(|(h, _)| h.clone()).collect();
+        hosts.sort();
+        hosts.dedup();
+        assert_eq!(hosts.len(), 20);
+    }
+}
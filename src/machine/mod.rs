@@ -5,7 +5,10 @@ use std::sync::Arc;
 
 use dashmap::{mapref::entry::Entry as DMEntry, DashMap};
 
-use crate::tools::Tool;
+use crate::{clock, tools::Tool};
+
+pub mod crypt;
+pub mod gen;
 
 /// Represents a file on an in-game machine
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -88,6 +91,37 @@ impl PartialEq for Entry {
 #[cfg(test)]
 impl Eq for Entry {}
 
+/// An account on an in-game machine
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct User {
+    /// The username, as used to log in
+    pub name: String,
+    /// The user's password, in plaintext, because this is a game and nobody's reading it but us
+    pub password: String,
+    /// Whether the user has admin rights on the machine
+    pub admin: bool,
+}
+
+/// A program listening on one of a machine's ports, which might have a hole or two in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Service {
+    /// What the service is, e.g. `ssh`
+    pub name: String,
+    /// What software it's running and which version, as it announces itself, e.g. `OpenSSH_7.4`
+    pub version: String,
+    /// The holes in this version of the software, which exploits can get in through
+    pub vulns: Vec<Vuln>,
+}
+
+/// A hole in a [`Service`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vuln {
+    /// The name of the exploit tool that uses it
+    pub exploit: String,
+    /// Who the service runs as, i.e. who an exploit that gets through it ends up with a shell as
+    pub user: String,
+}
+
 /// A single machine in-game, somewhere in the CyberZone. Possibly even the player's own.
 #[derive(Default, Clone)]
 pub struct Machine {
@@ -95,9 +129,43 @@ pub struct Machine {
     pub root: Arc<DashMap<String, Entry>>,
     /// the tools available at the command line
     pub tools: DashMap<String, Arc<dyn Tool>>,
+    /// The accounts on this machine, by username
+    pub users: DashMap<String, User>,
+    /// How hard the machine is to break into, from 0 (wide open) to [`MAX_SECURITY`](crate::constants::gameplay::MAX_SECURITY)
+    pub security: u32,
+    /// The ports open to the network, with the service listening on each
+    pub services: DashMap<u16, Service>,
 }
 
 impl Machine {
+    /// Whether a user's allowed to see what's at an absolute path.
+    ///
+    /// Admins (and `root`) can go anywhere; everyone else is kept out of `/root`, `/etc/shadow`, and other users' home
+    /// directories.
+    pub fn can_access(&self, user: &str, path: &str) -> bool {
+        let admin = user == "root" || self.users.get(user).map(|u| u.admin).unwrap_or(false);
+        if admin {
+            return true;
+        }
+        let mut comps = path.split('/').filter(|c| !c.is_empty());
+        match (comps.next(), comps.next()) {
+            (Some("root"), _) | (Some("etc"), Some("shadow")) => false,
+            (Some("home"), Some(owner)) => owner == user,
+            _ => true,
+        }
+    }
+
+    /// The ports open to the network, in order, with the service listening on each
+    pub fn ports(&self) -> Vec<(u16, Service)> {
+        let mut ports: Vec<_> = self
+            .services
+            .iter()
+            .map(|s| (*s.key(), s.value().clone()))
+            .collect();
+        ports.sort_unstable_by_key(|(port, _)| *port);
+        ports
+    }
+
     fn dir(&self, path: &str, make: bool) -> Result<Arc<DashMap<String, Entry>>, String> {
         if path.is_empty() {
             return Ok(self.root.clone());
@@ -200,6 +268,46 @@ impl Machine {
         Ok(())
     }
 
+    /// Add a line to the end of a file at the absolute path, creating it (and any directories it needs) if need be.
+    ///
+    /// Returns Ok(()) if everything worked, or Err(msg) if not.
+    pub fn append(&self, path: &str, line: &str) -> Result<(), String> {
+        let (parent, _) = path
+            .rsplit_once('/')
+            .ok_or(format!("absolute path {} doesn't start with /", path))?;
+        self.mkdir(&format!("{}/", parent), true)?;
+        let mut contents = self.read(path).map(|f| f.contents).unwrap_or_default();
+        contents += line;
+        if !line.ends_with('\n') {
+            contents.push('\n');
+        }
+        self.write(path, contents)
+    }
+
+    /// Write a timestamped line to one of the machine's logs. Logging never fails loudly; if it can't be written,
+    /// it's just not logged.
+    pub fn log(&self, path: &str, what: &str) {
+        let _ = self.append(path, &format!("{} {}", clock::now(), what));
+    }
+
+    /// Delete a file or directory (including everything in it) from the machine's disk at the absolute path.
+    ///
+    /// Returns Ok(()) if everything worked, or Err(msg) if not.
+    pub fn remove(&self, path: &str) -> Result<(), String> {
+        let (parent, file) = path
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .ok_or(format!("absolute path {} doesn't start with /", path))?;
+        if file.is_empty() {
+            return Err("cannot remove the root directory".into());
+        }
+        let dir = self.dir(parent, false)?;
+        match dir.remove(file) {
+            Some(_) => Ok(()),
+            None => Err(format!("no such entry: {}", path)),
+        }
+    }
+
     /// Will get any kind of [`Entry`] from the machine's disk at the absolute path.
     ///
     /// Returns Ok(entry) if everything worked, or Err(msg) if not.
@@ -242,6 +350,22 @@ impl Machine {
             .ok_or(format!("cannot readdir non-directory {}", path))?;
         Ok(dir.as_ref().clone().into_iter())
     }
+
+    /// Find every file on the machine, along with its absolute path, in no particular order.
+    pub fn files(&self) -> Vec<(String, File)> {
+        let mut found = vec![];
+        let mut dirs = vec![("/".to_owned(), self.root.clone())];
+        while let Some((path, dir)) = dirs.pop() {
+            for item in dir.iter() {
+                let full = format!("{}{}", path, item.key());
+                match item.value() {
+                    Entry::File(f) => found.push((full, f.clone())),
+                    Entry::Directory(d) => dirs.push((format!("{}/", full), d.clone())),
+                }
+            }
+        }
+        found
+    }
 }
 
 #[cfg(test)]
@@ -350,6 +474,26 @@ mod test {
         assert_eq!(f.contents, "ghost");
     }
 
+    #[test]
+    fn machine_removes_file_and_dir() {
+        let mach = Machine::default();
+        mach.mkdir("/things/", true)
+            .expect("failed to mkdir in empty filesystem");
+        mach.write("/things/spooky", "ghost".into())
+            .expect("failed to write to empty filesystem");
+        mach.write("/cute", "me".into())
+            .expect("failed to write to empty filesystem");
+
+        mach.remove("/cute").expect("failed to remove file");
+        mach.read("/cute").expect_err("removed file still exists");
+        mach.remove("/things/").expect("failed to remove directory");
+        mach.read("/things/spooky")
+            .expect_err("file in removed directory still exists");
+        mach.remove("/things/")
+            .expect_err("removed nonexistent directory");
+        mach.remove("/").expect_err("removed root");
+    }
+
     #[test]
     fn machine_entry_reads_file() {
         let mach = Machine::default();
@@ -415,4 +559,74 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn machine_appends_lines() {
+        let mach = Machine::default();
+        mach.append("/var/log/auth.log", "one").unwrap();
+        mach.append("/var/log/auth.log", "two\n").unwrap();
+        assert_eq!(
+            mach.read("/var/log/auth.log").unwrap().contents,
+            "one\ntwo\n"
+        );
+        mach.write("/file", "".into()).unwrap();
+        mach.append("/file/oops", "three")
+            .expect_err("appended inside a file");
+    }
+
+    #[test]
+    fn users_kept_out_of_other_homes() {
+        let mach = Machine::default();
+        for (name, admin) in [("alice", false), ("bob", false), ("carol", true)] {
+            let user = User {
+                name: name.into(),
+                password: "hunter2".into(),
+                admin,
+            };
+            mach.users.insert(name.into(), user);
+        }
+        assert!(mach.can_access("alice", "/home/alice/mail/0.eml"));
+        assert!(mach.can_access("alice", "/home/"));
+        assert!(mach.can_access("alice", "/etc/passwd"));
+        assert!(!mach.can_access("alice", "/home/bob/notes.txt"));
+        assert!(!mach.can_access("alice", "/root/"));
+        assert!(!mach.can_access("alice", "/etc/shadow"));
+        assert!(mach.can_access("carol", "/etc/shadow"));
+        assert!(mach.can_access("carol", "/home/bob/notes.txt"));
+        assert!(mach.can_access("root", "/root/"));
+        // service accounts don't have homes, and don't get into anyone else's either
+        assert!(!mach.can_access("www-data", "/home/alice/"));
+        assert!(mach.can_access("www-data", "/srv/"));
+    }
+
+    #[test]
+    fn machine_files_finds_nested_files() {
+        let mach = Machine::default();
+        mach.mkdir("/things/stuff/", true)
+            .expect("failed to mkdir in empty filesystem");
+        mach.write("/spooky", "ghost".into())
+            .expect("failed to write to empty filesystem");
+        mach.write("/things/stuff/cute", "me".into())
+            .expect("failed to write to empty filesystem");
+
+        let mut files = mach.files();
+        files.sort_by(|l, r| l.0.cmp(&r.0));
+        assert_eq!(
+            files,
+            vec![
+                (
+                    "/spooky".to_owned(),
+                    File {
+                        contents: "ghost".into()
+                    }
+                ),
+                (
+                    "/things/stuff/cute".to_owned(),
+                    File {
+                        contents: "me".into()
+                    }
+                ),
+            ]
+        );
+    }
 }
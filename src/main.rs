@@ -1,12 +1,23 @@
 pub mod app;
+pub mod clock;
 pub mod concept;
 pub mod constants;
 pub mod cutscenes;
+pub mod economy;
 pub mod event;
+pub mod forensics;
 pub mod game;
+pub mod jobs;
 pub mod machine;
+pub mod net;
+pub mod npc;
+pub mod rng;
+pub mod save;
+pub mod settings;
 pub mod state;
 pub mod tools;
+pub mod tutorial;
+pub mod world;
 
 fn run_game(args: &mut dyn Iterator<Item = String>) -> bool {
     if let Some(bin) = args.next() {
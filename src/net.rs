@@ -0,0 +1,173 @@
+//! The network the CyberZone's machines sit on: who's at which address, and which hosts the player's found so far.
+//!
+//! Every subnet is a /24, i.e. everything sharing the first three parts of an address. The player's machine sits on
+//! [`HOME_SUBNET`] with the civilians around them, and each corp gets a subnet of its own.
+
+use std::{fmt, str::FromStr};
+
+use dashmap::{DashMap, DashSet};
+
+use crate::constants::gameplay::HOME_SUBNET;
+
+/// An IPv4 address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Addr(pub [u8; 4]);
+
+impl fmt::Display for Addr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.0;
+        write!(f, "{}.{}.{}.{}", a, b, c, d)
+    }
+}
+
+impl FromStr for Addr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts: Vec<_> = s.split('.').map(|p| p.parse::<u8>()).collect();
+        match parts.as_slice() {
+            [Ok(a), Ok(b), Ok(c), Ok(d)] => Ok(Self([*a, *b, *c, *d])),
+            _ => Err(format!("{} isn't an address", s)),
+        }
+    }
+}
+
+/// A /24 subnet, i.e. every address starting with the same three parts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Subnet(pub [u8; 3]);
+
+impl Subnet {
+    /// The subnet an address is on
+    pub fn of(addr: Addr) -> Self {
+        let [a, b, c, _] = addr.0;
+        Self([a, b, c])
+    }
+
+    /// The `n`th address on this subnet
+    pub fn addr(&self, n: u8) -> Addr {
+        let [a, b, c] = self.0;
+        Addr([a, b, c, n])
+    }
+
+    /// Every address a machine could have on this subnet, i.e. all but the network and broadcast addresses
+    pub fn addrs(&self) -> impl Iterator<Item = Addr> + '_ {
+        (1..=254).map(|n| self.addr(n))
+    }
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/24", self.addr(0))
+    }
+}
+
+impl FromStr for Subnet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.split_once('/') {
+            Some((addr, "24")) => Ok(Self::of(addr.parse()?)),
+            Some(_) => Err(format!("{} isn't a /24 subnet", s)),
+            None => Err(format!("{} isn't a subnet", s)),
+        }
+    }
+}
+
+/// Which machine's at which address, and which of them the player knows about.
+///
+/// Everything in here uses interior mutability, like the rest of the [`World`](crate::world::World).
+#[derive(Default)]
+pub struct Network {
+    /// Every machine's hostname, by address
+    hosts: DashMap<Addr, String>,
+    /// Every machine's address, by hostname. The CyberZone's DNS never lies.
+    addrs: DashMap<String, Addr>,
+    /// The hostnames the player's found, e.g. by scanning, for autocompleting and the like
+    pub known: DashSet<String>,
+}
+
+impl Network {
+    /// Put a machine on the network at an address
+    pub fn add(&self, host: &str, addr: Addr) {
+        self.hosts.insert(addr, host.into());
+        self.addrs.insert(host.into(), addr);
+    }
+
+    /// The hostname of whatever's at an address, if anything is
+    pub fn host_at(&self, addr: Addr) -> Option<String> {
+        self.hosts.get(&addr).map(|h| h.value().clone())
+    }
+
+    /// A machine's address, if it's on the network
+    pub fn addr_of(&self, host: &str) -> Option<Addr> {
+        self.addrs.get(host).map(|a| *a.value())
+    }
+
+    /// Whether anything's at an address yet
+    pub fn taken(&self, addr: Addr) -> bool {
+        self.hosts.contains_key(&addr)
+    }
+
+    /// Note that the player's found a host. Returns whether it's news to them.
+    pub fn learn(&self, host: &str) -> bool {
+        self.known.insert(host.into())
+    }
+
+    /// Turn whatever the player typed as a target into the addresses it covers: a subnet (`10.0.1.0/24`), a single
+    /// address, or a hostname. Nothing at all means the player's own subnet.
+    pub fn resolve(&self, target: &str) -> Result<Vec<Addr>, String> {
+        if target.is_empty() {
+            Ok(HOME_SUBNET.addrs().collect())
+        } else if target.contains('/') {
+            Ok(target.parse::<Subnet>()?.addrs().collect())
+        } else if let Ok(addr) = target.parse::<Addr>() {
+            Ok(vec![addr])
+        } else {
+            match self.addr_of(target) {
+                Some(addr) => Ok(vec![addr]),
+                None => Err(format!("unknown host {}", target)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn addresses_roundtrip() {
+        let addr: Addr = "10.0.1.17".parse().unwrap();
+        assert_eq!(addr, Addr([10, 0, 1, 17]));
+        assert_eq!(addr.to_string(), "10.0.1.17");
+        assert!("10.0.1".parse::<Addr>().is_err());
+        assert!("10.0.1.300".parse::<Addr>().is_err());
+
+        let subnet: Subnet = "172.16.4.0/24".parse().unwrap();
+        assert_eq!(subnet, Subnet([172, 16, 4]));
+        assert_eq!(subnet.to_string(), "172.16.4.0/24");
+        assert_eq!(Subnet::of(addr), Subnet([10, 0, 1]));
+        assert!("172.16.4.0/16".parse::<Subnet>().is_err());
+        assert_eq!(subnet.addrs().count(), 254);
+    }
+
+    #[test]
+    fn targets_resolve() {
+        let net = Network::default();
+        net.add("hexacorp-db04", Addr([172, 16, 4, 20]));
+        assert_eq!(
+            net.resolve("hexacorp-db04"),
+            Ok(vec![Addr([172, 16, 4, 20])])
+        );
+        assert_eq!(net.resolve("10.0.1.5"), Ok(vec![Addr([10, 0, 1, 5])]));
+        assert_eq!(net.resolve("172.16.4.0/24").unwrap().len(), 254);
+        assert_eq!(net.resolve("").unwrap()[0], HOME_SUBNET.addr(1));
+        assert!(net.resolve("nowhere").is_err());
+        assert_eq!(
+            net.host_at(Addr([172, 16, 4, 20])).as_deref(),
+            Some("hexacorp-db04")
+        );
+        assert!(net.learn("hexacorp-db04"));
+        assert!(!net.learn("hexacorp-db04"));
+    }
+}
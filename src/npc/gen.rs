@@ -0,0 +1,505 @@
+//! Procedural generation of the NPCs that populate the hacker scene.
+//!
+//! Each generated NPC is a [`Persona`]: a handle, a couple of personality [`Trait`]s, maybe a [`Skill`], and maybe a
+//! faction. Its conversation tree is assembled from templates keyed on those, so the same seed always produces the
+//! same person saying the same things, while hand-written NPCs can be mixed in alongside them.
+
+use std::sync::Arc;
+
+use crate::{
+    constants::gameplay::PLAYER_HOST,
+    jobs::{Job, Objective},
+    rng::Rng,
+    world::World,
+};
+
+use super::{ChatState, Gate, Intent, Matcher, Offer, Reply, Skill, NPC};
+
+const HANDLE_ROOTS: &[&str] = &[
+    "ghost", "zero", "cipher", "vex", "glitch", "null", "rook", "spider", "static", "wraith",
+    "pixel", "hex", "byte", "echo", "drift", "viper", "neon", "shade",
+];
+const HANDLE_SUFFIXES: &[&str] = &["", "_", "x", "z", "99", "42", "_0", "777"];
+const FACTIONS: &[&str] = &["redshell", "null sector", "the lattice", "blackwater"];
+const SKILLS: &[Skill] = &[Skill::Cracking, Skill::SocialEngineering, Skill::Forensics];
+
+/// Part of an NPC's personality, which decides how they talk and how quickly they warm up to the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Trait {
+    /// Warm, quick to trust
+    Friendly,
+    /// Suspicious of everyone, needs to trust the player before working with them
+    Paranoid,
+    /// Only in it for the money
+    Greedy,
+    /// Never sends one message when three will do
+    Chatty,
+    /// Never sends three messages when one will do
+    Terse,
+}
+
+const TRAITS: &[Trait] = &[
+    Trait::Friendly,
+    Trait::Paranoid,
+    Trait::Greedy,
+    Trait::Chatty,
+    Trait::Terse,
+];
+
+impl Trait {
+    /// How much the NPC trusts the player when they first meet
+    fn starting_trust(&self) -> i32 {
+        match self {
+            Self::Friendly => 5,
+            Self::Paranoid => -5,
+            _ => 0,
+        }
+    }
+
+    fn greetings(&self) -> &'static [&'static str] {
+        match self {
+            Self::Friendly => &[
+                "hey! {handle} here",
+                "oh hi, new face!",
+                "welcome to the scene, friend",
+            ],
+            Self::Paranoid => &[
+                "who gave you this handle?",
+                "this channel secure?",
+                "...who is this",
+            ],
+            Self::Greedy => &["you buying or selling?", "time is money. what do you want?"],
+            Self::Chatty => &[
+                "heyyy",
+                "oh cool someone new to talk to",
+                "so what's your deal?",
+            ],
+            Self::Terse => &["yeah?", "what."],
+        }
+    }
+
+    fn pitches(&self) -> &'static [&'static str] {
+        match self {
+            Self::Friendly => &["sure, happy to help", "always glad to lend a hand"],
+            Self::Paranoid => &[
+                "maybe. how do i know you're not a fed?",
+                "i don't work with strangers",
+            ],
+            Self::Greedy => &["my rates are steep, but i'm worth it", "i'll want a cut"],
+            Self::Chatty => &[
+                "ooh a job? love a job",
+                "honestly i've been so bored lately",
+            ],
+            Self::Terse => &["maybe.", "depends."],
+        }
+    }
+
+    fn joins(&self) -> &'static [&'static str] {
+        match self {
+            Self::Friendly => &["count me in!", "let's do this"],
+            Self::Paranoid => &["fine. but if this goes sideways, i never knew you"],
+            Self::Greedy => &["deal. don't forget my cut"],
+            Self::Chatty => &["yesss ok i'm in, this is gonna be great"],
+            Self::Terse => &["fine.", "in."],
+        }
+    }
+
+    fn farewells(&self) -> &'static [&'static str] {
+        match self {
+            Self::Friendly => &["later! stay safe out there"],
+            Self::Paranoid => &["don't contact me unless you have to"],
+            Self::Greedy => &["come back when you've got money"],
+            Self::Chatty => &["aw ok, bye! talk soon!"],
+            Self::Terse => &["k.", "bye."],
+        }
+    }
+}
+
+/// Everything that makes up a generated NPC, before it's turned into an actual [`NPC`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Persona {
+    /// The NPC's handle, i.e. their name in chat
+    pub handle: String,
+    /// Their personality; the first one dominates how they talk
+    pub traits: Vec<Trait>,
+    /// What they can do for the player's team, and how well, if anything
+    pub skill: Option<(Skill, u32)>,
+    /// Which faction they run with, if any
+    pub faction: Option<String>,
+    /// Seeds the choice of dialogue, so the same persona always says the same things
+    seed: u64,
+}
+
+impl Persona {
+    /// Generate a random persona, whose handle isn't any of the `taken` ones.
+    pub fn generate(rng: &mut Rng, taken: &[&str]) -> Self {
+        let mut handle = Self::handle(rng);
+        while taken.contains(&handle.as_str()) {
+            handle = format!("{}{}", handle, rng.below(10));
+        }
+        let mut traits = vec![*rng.pick(TRAITS)];
+        if rng.chance(0.5) {
+            let second = *rng.pick(TRAITS);
+            let clashes = matches!(
+                (traits[0], second),
+                (Trait::Chatty, Trait::Terse) | (Trait::Terse, Trait::Chatty)
+            );
+            if second != traits[0] && !clashes {
+                traits.push(second);
+            }
+        }
+        let skill = if rng.chance(0.7) {
+            Some((*rng.pick(SKILLS), rng.between(1, 8) as u32))
+        } else {
+            None
+        };
+        let faction = if rng.chance(0.6) {
+            Some(rng.pick(FACTIONS).to_string())
+        } else {
+            None
+        };
+        Self {
+            handle,
+            traits,
+            skill,
+            faction,
+            seed: rng.next_u64(),
+        }
+    }
+
+    fn handle(rng: &mut Rng) -> String {
+        let mut root = rng.pick(HANDLE_ROOTS).to_string();
+        if rng.chance(0.5) {
+            // leetify one letter, because of course
+            root = match rng.below(3) {
+                0 => root.replacen('o', "0", 1),
+                1 => root.replacen('e', "3", 1),
+                _ => root.replacen('i', "1", 1),
+            };
+        }
+        format!("{}{}", root, rng.pick(HANDLE_SUFFIXES))
+    }
+
+    fn has(&self, t: Trait) -> bool {
+        self.traits.contains(&t)
+    }
+
+    /// Fill in a dialogue template with this persona's details
+    fn fill(&self, template: &str) -> String {
+        let skill = self.skill.map(|(s, _)| s.name()).unwrap_or("nothing much");
+        let faction = self.faction.as_deref().unwrap_or("nobody");
+        template
+            .replace("{handle}", &self.handle)
+            .replace("{skill}", skill)
+            .replace("{faction}", faction)
+    }
+
+    /// Turn a list of templates into messages, with delays to match the persona's personality
+    fn say(&self, rng: &mut Rng, templates: &[&str]) -> Vec<(String, usize)> {
+        let base = if self.has(Trait::Chatty) {
+            300
+        } else if self.has(Trait::Terse) {
+            1500
+        } else {
+            800
+        };
+        templates
+            .iter()
+            .map(|t| (self.fill(t), base + rng.below(400) as usize))
+            .collect()
+    }
+
+    /// Build the conversation tree from the templates.
+    ///
+    /// The states are: 0, greeting; 1, introduction; 2, pitch for joining the team; 3, farewell; 4, joining the team;
+    /// 5, asking a favour to earn their trust; 6, thanks for the favour.
+    fn states(&self) -> Vec<ChatState> {
+        let mut rng = Rng::seeded(self.seed);
+        let main = self.traits[0];
+        let reply = |text: &str, next: usize, trust: i32, gate: Option<Gate>| Reply {
+            text: text.into(),
+            next,
+            trust,
+            gate,
+            trade: None,
+        };
+        let state = |messages, options| ChatState {
+            messages,
+            options,
+            intents: vec![],
+            attachments: vec![],
+            offer: None,
+            recruit: false,
+        };
+
+        let mut greeting = vec![*rng.pick(main.greetings())];
+        if self.has(Trait::Chatty) {
+            greeting.push(*rng.pick(Trait::Chatty.greetings()));
+        }
+        let mut options = vec![reply("who are you?", 1, 0, None)];
+        if self.skill.is_some() {
+            options.push(reply("can you help me out?", 2, 0, None));
+        }
+        options.push(reply("bye", 3, 0, None));
+        let mut greeting = state(self.say(&mut rng, &greeting), options);
+        // players who'd rather type get the same conversation
+        let hear = |words: &[&str], next: usize| Intent {
+            matcher: Matcher::keywords(words),
+            next,
+            trust: 0,
+        };
+        greeting.intents.push(hear(&["who", "name"], 1));
+        if self.skill.is_some() {
+            greeting
+                .intents
+                .push(hear(&["help", "job", "work", "team", "hire"], 2));
+        }
+        greeting.intents.push(hear(&["bye", "later", "cya"], 3));
+
+        let mut intro = vec!["{handle}. i do {skill}."];
+        if self.faction.is_some() {
+            intro.push("i run with {faction}.");
+        } else if !self.has(Trait::Terse) {
+            intro.push("no crew. i work alone.");
+        }
+        // this loops back to the greeting, so it can be picked forever, and mustn't be a way to farm trust
+        let intro = state(self.say(&mut rng, &intro), vec![reply("cool", 0, 0, None)]);
+
+        // paranoid NPCs won't join until they trust the player, and greedy ones are pickier about it
+        let gate = if self.has(Trait::Paranoid) {
+            Some(Gate::trust(10))
+        } else if self.has(Trait::Greedy) {
+            Some(Gate::trust(5))
+        } else {
+            None
+        };
+        let line = *rng.pick(main.pitches());
+        let mut options = vec![reply("deal", 4, 0, gate.clone())];
+        if gate.is_some() {
+            options.push(reply("what would it take?", 5, 0, None));
+        }
+        options.push(reply("nevermind", 0, -1, None));
+        let pitch = state(self.say(&mut rng, &[line]), options);
+
+        let line = *rng.pick(main.farewells());
+        let farewell = state(
+            self.say(&mut rng, &[line]),
+            vec![reply("hey again", 0, 0, None)],
+        );
+
+        let line = *rng.pick(main.joins());
+        let mut join = state(
+            self.say(&mut rng, &[line]),
+            vec![reply("welcome aboard", 0, 2, None)],
+        );
+        join.recruit = true;
+
+        // the one real way to earn their trust: each job can only be taken once, and the thanks only come when it's
+        // done, so none of this can be farmed
+        let drop = format!("/drops/{}", self.handle);
+        let job = Job::new(
+            &format!("{}-favour", self.handle),
+            &self.handle,
+            "set up a dead drop",
+        )
+        .objective(Objective::create(PLAYER_HOST, &drop));
+        let mut favour = state(
+            self.say(
+                &mut rng,
+                &["do me a favour first. set up a dead drop: make /drops/{handle} on your box."],
+            ),
+            vec![reply("on it", 0, 0, None), reply("forget it", 0, 0, None)],
+        );
+        favour.offer = Some(Offer {
+            job,
+            accept: "on it".into(),
+            success: 6,
+            failure: 0,
+        });
+        let thanks = state(
+            self.say(&mut rng, &["got it. maybe you're alright after all."]),
+            vec![reply("anytime", 2, 5, None)],
+        );
+
+        vec![greeting, intro, pitch, farewell, join, favour, thanks]
+    }
+
+    /// Create an NPC that acts out this persona in the given world.
+    ///
+    /// This also sets how much the NPC trusts the player to start with, based on their personality.
+    pub fn npc(&self, world: Arc<World>) -> NPC {
+        let trust: i32 = self.traits.iter().map(|t| t.starting_trust()).sum();
+        if trust != 0 {
+            world.relations.adjust_trust(&self.handle, trust);
+        }
+        NPC {
+            name: self.handle.clone(),
+            all_states: self.states(),
+            faction: self.faction.clone(),
+            skill: self.skill,
+            world,
+            ..Default::default()
+        }
+    }
+}
+
+/// Generate a whole scene of personas, none of which share a handle with each other or any of the `taken` ones (e.g.
+/// hand-written NPCs).
+pub fn scene(rng: &mut Rng, count: usize, taken: &[&str]) -> Vec<Persona> {
+    let mut taken: Vec<String> = taken.iter().map(|&s| s.to_owned()).collect();
+    let mut personas = vec![];
+    for _ in 0..count {
+        let names: Vec<_> = taken.iter().map(|s| s.as_str()).collect();
+        let persona = Persona::generate(&mut rng.fork(), &names);
+        taken.push(persona.handle.clone());
+        personas.push(persona);
+    }
+    personas
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        event::Event,
+        npc::test::{react, start},
+    };
+
+    use super::*;
+
+    #[test]
+    fn same_seed_same_scene() {
+        let a = scene(&mut Rng::seeded(3), 10, &[]);
+        let b = scene(&mut Rng::seeded(3), 10, &[]);
+        assert_eq!(a, b);
+        let a: Vec<_> = a.iter().map(|p| p.states()[0].messages.clone()).collect();
+        let b: Vec<_> = b.iter().map(|p| p.states()[0].messages.clone()).collect();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn scene_handles_are_unique() {
+        let taken = ["admin", "yotie"];
+        let personas = scene(&mut Rng::seeded(0), 50, &taken);
+        let mut handles: Vec<_> = personas.iter().map(|p| p.handle.clone()).collect();
+        assert!(!handles.iter().any(|h| taken.contains(&h.as_str())));
+        handles.sort();
+        handles.dedup();
+        assert_eq!(handles.len(), 50);
+    }
+
+    #[test]
+    fn personas_never_chatty_and_terse() {
+        for persona in scene(&mut Rng::seeded(1), 100, &[]) {
+            assert!(!(persona.has(Trait::Chatty) && persona.has(Trait::Terse)));
+        }
+    }
+
+    #[test]
+    fn templates_filled_in() {
+        for persona in scene(&mut Rng::seeded(2), 30, &[]) {
+            for state in persona.states() {
+                for (msg, _) in state.messages {
+                    assert!(!msg.contains('{'), "unfilled template: {}", msg);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn skilled_npc_can_be_recruited() {
+        let persona = Persona {
+            handle: "gh0st".into(),
+            traits: vec![Trait::Friendly],
+            skill: Some((Skill::Forensics, 3)),
+            faction: None,
+            seed: 0,
+        };
+        let world = Arc::new(World::default());
+        let mut npc = persona.npc(world.clone());
+        assert_eq!(world.relations.trust("gh0st"), 5);
+        start(&mut npc);
+        react(
+            &mut npc,
+            &Event::player_chat("gh0st", "can you help me out?"),
+        );
+        react(&mut npc, &Event::player_chat("gh0st", "deal"));
+        assert!(world.team.contains_key("gh0st"));
+    }
+
+    #[test]
+    fn paranoid_npc_needs_trust() {
+        let persona = Persona {
+            handle: "v3x".into(),
+            traits: vec![Trait::Paranoid],
+            skill: Some((Skill::Cracking, 3)),
+            faction: Some("the lattice".into()),
+            seed: 0,
+        };
+        let world = Arc::new(World::default());
+        let mut npc = persona.npc(world.clone());
+        start(&mut npc);
+        let said = react(&mut npc, &Event::player_chat("v3x", "can you help me out?"));
+        match said.last() {
+            Some(Event::NPCChatMessage { options, .. }) => {
+                assert_eq!(options, &["what would it take?", "nevermind"])
+            }
+            other => panic!("expected chat message, got {:?}", other),
+        }
+        react(&mut npc, &Event::player_chat("v3x", "deal"));
+        assert!(world.team.is_empty());
+    }
+
+    #[test]
+    fn small_talk_doesnt_build_trust() {
+        let persona = Persona {
+            handle: "v3x".into(),
+            traits: vec![Trait::Paranoid],
+            skill: Some((Skill::Cracking, 3)),
+            faction: None,
+            seed: 0,
+        };
+        let world = Arc::new(World::default());
+        let mut npc = persona.npc(world.clone());
+        let trust = world.relations.trust("v3x");
+        start(&mut npc);
+        for _ in 0..20 {
+            react(&mut npc, &Event::player_chat("v3x", "who are you?"));
+            react(&mut npc, &Event::player_chat("v3x", "cool"));
+        }
+        assert_eq!(world.relations.trust("v3x"), trust);
+        react(&mut npc, &Event::player_chat("v3x", "can you help me out?"));
+        react(&mut npc, &Event::player_chat("v3x", "deal"));
+        assert!(world.team.is_empty());
+    }
+
+    #[test]
+    fn paranoid_npc_recruited_after_favour() {
+        let persona = Persona {
+            handle: "v3x".into(),
+            traits: vec![Trait::Paranoid],
+            skill: Some((Skill::Cracking, 3)),
+            faction: None,
+            seed: 0,
+        };
+        let world = Arc::new(World::default());
+        let mut npc = persona.npc(world.clone());
+        start(&mut npc);
+        react(&mut npc, &Event::player_chat("v3x", "can you help me out?"));
+        react(&mut npc, &Event::player_chat("v3x", "what would it take?"));
+        let said = react(&mut npc, &Event::player_chat("v3x", "on it"));
+        let job = said.iter().find_map(|e| match e {
+            Event::JobAccepted { job, .. } => Some(job.clone()),
+            _ => None,
+        });
+        let job = job.expect("favour wasn't offered");
+        assert_eq!(
+            job.objectives,
+            vec![Objective::create(PLAYER_HOST, "/drops/v3x")]
+        );
+
+        react(&mut npc, &Event::JobCompleted(job.id.clone()));
+        react(&mut npc, &Event::player_chat("v3x", "anytime"));
+        react(&mut npc, &Event::player_chat("v3x", "deal"));
+        assert!(world.team.contains_key("v3x"));
+    }
+}
@@ -0,0 +1,120 @@
+//! Matching free text the player types at an NPC, rather than one of the replies on offer, or files they send.
+
+use crate::event::Attachment;
+
+/// Tidy up text for loose matching: lowercase, with punctuation and extra whitespace squashed into single spaces, and
+/// padded with a space on either end so whole words can be found with `contains`.
+fn normalize(text: &str) -> String {
+    let mut out = String::from(" ");
+    for word in text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+    {
+        out.push_str(&word.to_lowercase());
+        out.push(' ');
+    }
+    out
+}
+
+/// How an NPC recognizes what the player typed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Matcher {
+    /// Any of these words or phrases appear in the message, ignoring case and punctuation
+    Keywords(Vec<String>),
+    /// The message is exactly this, give or take surrounding whitespace. Case-sensitive, for passwords and codes.
+    Exactly(String),
+    /// Anything at all; useful as a fallback after more specific matchers
+    Anything,
+    /// A file (rather than a message) whose name or contents match
+    File(Box<Matcher>),
+}
+
+impl Matcher {
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn keywords(words: &[&str]) -> Self {
+        Self::Keywords(words.iter().map(|&w| w.into()).collect())
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn exactly(text: &str) -> Self {
+        Self::Exactly(text.into())
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn file(inner: Matcher) -> Self {
+        Self::File(Box::new(inner))
+    }
+
+    /// Whether the player's message matches. Messages never match [`Matcher::File`].
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::File(_) => false,
+            Self::Keywords(words) => {
+                let text = normalize(text);
+                words.iter().any(|w| text.contains(&normalize(w)))
+            }
+            Self::Exactly(expected) => text.trim() == expected,
+            Self::Anything => true,
+        }
+    }
+
+    /// Whether a file the player sent matches. Only [`Matcher::File`] can match files.
+    pub fn matches_file(&self, file: &Attachment) -> bool {
+        match self {
+            Self::File(inner) => inner.matches(&file.name) || inner.matches(&file.contents),
+            _ => false,
+        }
+    }
+}
+
+/// Something the player can type (or send) to an NPC, and where it leads, like a [`Reply`](super::Reply) they have to
+/// come up with themselves.
+pub struct Intent {
+    /// How to recognize it
+    pub matcher: Matcher,
+    /// Which state it leads to
+    pub next: usize,
+    /// How much saying it changes the NPC's trust in the player
+    pub trust: i32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keywords_match_whole_words() {
+        let m = Matcher::keywords(&["price", "how much"]);
+        assert!(m.matches("What's the PRICE?"));
+        assert!(m.matches("ok, how   much is it"));
+        assert!(!m.matches("priceless"));
+        assert!(!m.matches("how is it so much"));
+    }
+
+    #[test]
+    fn exactly_is_case_sensitive() {
+        let m = Matcher::exactly("hunter2");
+        assert!(m.matches("  hunter2 "));
+        assert!(!m.matches("Hunter2"));
+        assert!(!m.matches("it's hunter2"));
+    }
+
+    #[test]
+    fn anything_matches() {
+        assert!(Matcher::Anything.matches(""));
+        assert!(Matcher::Anything.matches("literally anything"));
+    }
+
+    #[test]
+    fn files_only_match_file_matchers() {
+        let file = Attachment {
+            name: "payroll.csv".into(),
+            contents: "name,salary\n".into(),
+        };
+        assert!(Matcher::file(Matcher::keywords(&["payroll"])).matches_file(&file));
+        assert!(Matcher::file(Matcher::keywords(&["salary"])).matches_file(&file));
+        assert!(!Matcher::file(Matcher::keywords(&["passwords"])).matches_file(&file));
+        assert!(!Matcher::Anything.matches_file(&file));
+        assert!(!Matcher::file(Matcher::Anything).matches("payroll.csv"));
+    }
+}
@@ -0,0 +1,850 @@
+//! The NPCs the player can chat with, and the conversation trees that drive them.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tuig::{Agent, ControlFlow, Replies};
+
+use crate::{
+    clock,
+    constants::gameplay::{INBOX_DIR, JOB_REPUTATION, JOB_TRUST, TYPING_MS_PER_CHAR},
+    economy::Deal,
+    event::{Attachment, Event},
+    jobs::Job,
+    settings,
+    world::World,
+};
+
+pub mod gen;
+mod intent;
+pub use intent::{Intent, Matcher};
+mod relations;
+pub use relations::{Gate, Relations};
+mod team;
+pub use team::{Member, Operation, Skill};
+
+/// A job an NPC offers when their conversation reaches a certain [`ChatState`], which the player takes on by picking one
+/// of that state's replies.
+pub struct Offer {
+    /// The job itself
+    pub job: Job,
+    /// The reply which accepts the job; any other reply turns it down
+    pub accept: String,
+    /// Which state to jump to when the player finishes the job
+    pub success: usize,
+    /// Which state to jump to when the player fails the job
+    pub failure: usize,
+}
+
+/// One of the replies the player can pick from in a [`ChatState`]
+pub struct Reply {
+    /// What the player says
+    pub text: String,
+    /// Which state it leads to
+    pub next: usize,
+    /// How much picking it changes the NPC's trust in the player
+    pub trust: i32,
+    /// What the player needs to be offered this reply at all, if anything
+    pub gate: Option<Gate>,
+    /// A trade picking this reply makes, and the state to go to instead of `next` if it falls through
+    pub trade: Option<(Deal, usize)>,
+}
+
+/// A single step in the conversation tree of an [`NPC`]
+pub struct ChatState {
+    /// The messages the NPC sends when entering the state, with how long it pauses before starting to type each
+    pub messages: Vec<(String, usize)>,
+    /// The replies the player can pick from
+    pub options: Vec<Reply>,
+    /// What the player can type instead of picking a reply, checked in order
+    pub intents: Vec<Intent>,
+    /// The files this NPC sends the player when entering this state
+    pub attachments: Vec<Attachment>,
+    /// The job this NPC offers when entering this state, if any
+    pub offer: Option<Offer>,
+    /// Whether the NPC joins the player's team when entering this state (if it has a [`Skill`] to offer)
+    pub recruit: bool,
+}
+
+/// Extremely temporary NPC implementation. Very simplistic, can only do basic conversation trees.
+#[derive(Default)]
+pub struct NPC {
+    /// The name of the NPC
+    pub name: String,
+    /// All of the states it could possibly be in
+    pub all_states: Vec<ChatState>,
+    /// Which state it's currently in
+    pub state: usize,
+    /// Which message in the state it's currently in
+    pub message: usize,
+    /// When the NPC will be done typing the current message, if it's typing one
+    pub typing: Option<Instant>,
+    /// The jobs this NPC has handed out that haven't finished yet, as `(id, success, failure)`
+    pub jobs: Vec<(String, usize, usize)>,
+    /// The faction this NPC belongs to, if any
+    pub faction: Option<String>,
+    /// What this NPC can do for the player's team, and how well, if anything
+    pub skill: Option<(Skill, u32)>,
+    /// The world this NPC lives in, for keeping track of how it feels about the player
+    pub world: Arc<World>,
+}
+
+impl NPC {
+    /// Put this NPC into the given world
+    pub fn in_world(mut self, world: Arc<World>) -> Self {
+        self.world = world;
+        self
+    }
+
+    /// Make this NPC part of the given faction
+    pub fn in_faction(mut self, faction: &str) -> Self {
+        self.faction = Some(faction.into());
+        self
+    }
+
+    /// Give this NPC a skill, so it can be recruited onto the player's team
+    pub fn with_skill(mut self, skill: Skill, level: u32) -> Self {
+        self.skill = Some((skill, level));
+        self
+    }
+
+    /// Get the current state
+    fn state(&self) -> &ChatState {
+        &self.all_states[*&self.state]
+    }
+
+    /// Get the current message/delay tuple
+    fn message(&self) -> &(String, usize) {
+        &self.state().messages[*&self.message]
+    }
+
+    /// Get the replies in the current state that the player is allowed to see
+    fn options(&self) -> impl Iterator<Item = &Reply> {
+        self.state().options.iter().filter(|r| match &r.gate {
+            Some(gate) => gate.met(&self.name, &self.world.relations),
+            None => true,
+        })
+    }
+
+    /// Change how the NPC and its faction feel about the player, after a job is done or failed
+    fn job_outcome(&self, success: bool) {
+        let sign = if success { 1 } else { -1 };
+        let relations = &self.world.relations;
+        relations.adjust_trust(&self.name, sign * JOB_TRUST);
+        if let Some(faction) = &self.faction {
+            relations.adjust_reputation(faction, sign * JOB_REPUTATION);
+        }
+    }
+
+    /// Hand out the current state's job, if it has one and it isn't already out. Called when the player accepts it.
+    fn offer(&mut self, replies: &mut Replies<Event>) {
+        let offer = match &self.state().offer {
+            Some(o) => o,
+            None => return,
+        };
+        if self.jobs.iter().any(|(id, _, _)| id == &offer.job.id) {
+            return;
+        }
+        let deadline = offer.job.time_limit.map(|l| clock::now() + l);
+        replies.queue(Event::JobAccepted {
+            job: offer.job.clone(),
+            deadline,
+        });
+        let pending = (offer.job.id.clone(), offer.success, offer.failure);
+        self.jobs.push(pending);
+    }
+
+    /// Send the current state's files to the player, dropping them straight into their inbox.
+    fn attach(&self, replies: &mut Replies<Event>) {
+        if self.state().attachments.is_empty() {
+            return;
+        }
+        let player = self.world.player();
+        let dir = format!("{}{}/", INBOX_DIR, self.name);
+        for file in &self.state().attachments {
+            let path = format!("{}{}", dir, file.name);
+            // if the player's made that impossible (e.g. with a file named `inbox`) the file just gets lost
+            let saved = player
+                .mkdir(&dir, true)
+                .and_then(|_| player.write(&path, file.contents.clone()));
+            if saved.is_ok() {
+                replies.queue(Event::NPCSentFile {
+                    from: self.name.clone(),
+                    file: file.clone(),
+                    path,
+                });
+            }
+        }
+    }
+
+    /// Respond to something the player typed or sent, rather than one of the offered replies.
+    ///
+    /// If none of the current state's intents match, the NPC says it didn't understand, and offers the same replies
+    /// again.
+    fn hear(
+        &mut self,
+        heard: impl Fn(&Matcher) -> bool,
+        replies: &mut Replies<Event>,
+    ) -> ControlFlow {
+        let intent = match self.state().intents.iter().find(|i| heard(&i.matcher)) {
+            Some(i) => i,
+            None => {
+                replies.queue(Event::NPCChatMessage {
+                    from: self.name.clone(),
+                    text: "?".into(),
+                    options: self.options().map(|r| r.text.clone()).collect(),
+                });
+                return ControlFlow::Continue;
+            }
+        };
+        if intent.trust != 0 {
+            self.world.relations.adjust_trust(&self.name, intent.trust);
+        }
+        let next = intent.next;
+        self.jump(next, replies)
+    }
+
+    /// Join the player's team, if the current state says to and this NPC has something to offer.
+    fn recruit(&self, replies: &mut Replies<Event>) {
+        let (skill, level) = match self.skill {
+            Some(s) if self.state().recruit => s,
+            _ => return,
+        };
+        if self.world.team.contains_key(&self.name) {
+            return;
+        }
+        let member = Member::new(&self.name, skill, level);
+        self.world.team.insert(self.name.clone(), member);
+        replies.queue(Event::TeamChanged {
+            name: self.name.clone(),
+            joined: true,
+        });
+    }
+
+    /// How long it takes to type a message, including the pause before starting, adjusted for the text speed setting
+    pub(crate) fn pace(text: &str, pause: usize) -> Duration {
+        settings::current().delay(pause + text.chars().count() * TYPING_MS_PER_CHAR)
+    }
+
+    /// Advance to the next message/state.
+    ///
+    /// Each message is "typed" first: the NPC says it's typing, then sends the message once it's had time to type it,
+    /// which [`NPC::react`] checks for on every event. It doesn't sleep, so the player can interrupt it.
+    fn advance(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        if self.state >= self.all_states.len() {
+            // walked off the end of the conversation, so there's nothing more to say
+            replies.queue(Event::NPCClosedChat {
+                from: self.name.clone(),
+            });
+            return ControlFlow::Kill;
+        }
+        if self.message == 0 && self.typing.is_none() {
+            self.attach(replies);
+            self.recruit(replies);
+        }
+        let (text, pause) = self.message().clone();
+        match self.typing {
+            None => {
+                let wait = Self::pace(&text, pause);
+                if !wait.is_zero() {
+                    self.typing = Some(Instant::now() + wait);
+                    replies.queue(Event::NPCTyping {
+                        from: self.name.clone(),
+                    });
+                    return ControlFlow::Continue;
+                }
+            }
+            Some(done) if Instant::now() < done => return ControlFlow::Continue,
+            Some(_) => (),
+        }
+        self.typing = None;
+        // advance to the next message (or beyond the end, to indicate to wait for replies)
+        self.message += 1;
+        if self.message != self.state().messages.len() {
+            // if it's not the last message, send it and start typing the next one
+            replies.queue(Event::NPCChatMessage {
+                from: self.name.clone(),
+                text,
+                options: vec![],
+            });
+            self.advance(replies)
+        } else {
+            // otherwise we send the replies and `Continue`, to make sure we don't miss a thing
+            let options = self.options().map(|r| r.text.clone()).collect();
+
+            replies.queue(Event::NPCChatMessage {
+                from: self.name.clone(),
+                text,
+                options,
+            });
+            ControlFlow::Continue
+        }
+    }
+
+    /// Jump to a different state, starting it from the first message.
+    fn jump(&mut self, state: usize, replies: &mut Replies<Event>) -> ControlFlow {
+        self.state = state;
+        self.message = 0;
+        self.typing = None;
+        self.advance(replies)
+    }
+}
+
+impl Agent<Event> for NPC {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        self.advance(replies)
+    }
+
+    fn react(&mut self, event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        if self.state >= self.all_states.len() {
+            // reached the end of the conversation tree
+            return ControlFlow::Kill;
+        }
+        if let Event::JobCompleted(id) | Event::JobFailed(id) = event {
+            if let Some(idx) = self.jobs.iter().position(|(j, _, _)| j == id) {
+                let (_, success, failure) = self.jobs.remove(idx);
+                let succeeded = matches!(event, Event::JobCompleted(_));
+                self.job_outcome(succeeded);
+                let next = if succeeded { success } else { failure };
+                return self.jump(next, replies);
+            }
+        }
+        if self.typing.is_some() {
+            let to_me = match event {
+                Event::PlayerChatMessage { to, .. } | Event::PlayerSentFile { to, .. } => {
+                    to == &self.name
+                }
+                _ => false,
+            };
+            if !to_me {
+                // keep typing, sending the message once it's done
+                return self.advance(replies);
+            }
+            // the player's interrupted, so drop whatever else was going to be said and answer them instead
+            self.typing = None;
+            self.message = self.state().messages.len();
+        }
+        if self.message >= self.all_states[self.state].messages.len() {
+            // look for a reply
+            let (dest, text) = match event {
+                Event::PlayerChatMessage { to, text } => (to, text),
+                Event::PlayerSentFile { to, file } if to == &self.name => {
+                    return self.hear(|m| m.matches_file(file), replies);
+                }
+                _ => return ControlFlow::Continue,
+            };
+            if dest != &self.name {
+                return ControlFlow::Continue;
+            }
+            let reply = self.options().find(|r| &r.text == text);
+            let reply = match reply {
+                Some(r) => r,
+                None => return self.hear(|m| m.matches(text), replies),
+            };
+            if reply.trust != 0 {
+                self.world.relations.adjust_trust(&self.name, reply.trust);
+            }
+            let next = match &reply.trade {
+                Some((deal, failure)) => match deal.make(&self.name, &self.world, replies) {
+                    Ok(()) => reply.next,
+                    Err(_) => *failure,
+                },
+                None => reply.next,
+            };
+            if matches!(&self.state().offer, Some(o) if &o.accept == text) {
+                self.offer(replies);
+            }
+            self.jump(next, replies)
+        } else {
+            // send the next message
+            self.advance(replies)
+        }
+    }
+}
+
+/// Create an NPC with kinda grody but mostly functional syntax.
+///
+/// Each state is a list of messages to `say`, any files to `attach` (e.g. `attach "notes.txt": "contents",`),
+/// optionally a job to `offer` (with the states to go to on success and failure, and the reply which accepts it, e.g.
+/// `offer job => 2 / 3 when "deal",`), optionally `join team` to recruit the
+/// NPC onto the player's team, and then the options the player can reply with. Each option can optionally change the
+/// NPC's trust in the player, with e.g. `[trust -5]`, can be hidden unless the player meets a [`Gate`], with e.g.
+/// `[if Gate::trust(10)]`, and can make a [`Deal`], going to a different state if it falls through, with e.g.
+/// `[trade Deal::BuyData, else 3]`.
+///
+/// Before the options, a state can also list what the player can `hear` the NPC react to when they type freely (or
+/// send files), e.g. `hear Matcher::exactly("hunter2") => 4, Matcher::Anything => 5 [trust -1];`. These are checked
+/// in order, only if what the player said isn't one of the options.
+macro_rules! npc {
+    (@trust) => { 0 };
+    (@trust $trust:literal) => { $trust };
+    (@gate) => { None };
+    (@gate $gate:expr) => { Some($gate) };
+    (@trade) => { None };
+    (@trade $deal:expr, $fail:literal) => { Some(($deal, $fail)) };
+    (@recruit) => { false };
+    (@recruit $team:ident) => { true };
+    (@offer) => { None };
+    (@offer $job:expr => $success:literal / $failure:literal when $accept:literal) => {
+        Some($crate::npc::Offer {
+            job: $job,
+            accept: $accept.into(),
+            success: $success,
+            failure: $failure,
+        })
+    };
+    ( $name:literal, $(
+        [
+            $( say $msg:literal : $delay:literal ),* ,
+            $( attach $fname:literal : $fcontents:expr , )*
+            $( offer $job:expr => $success:literal / $failure:literal when $accept:literal , )?
+            $( join $team:ident , )?
+            $( hear $(
+                $matcher:expr => $hstate:literal $( [ trust $htrust:literal ] )?
+            ),+ ; )?
+            ask $(
+                $option:literal => $state:literal
+                $( [ trust $trust:literal ] )?
+                $( [ if $gate:expr ] )?
+                $( [ trade $deal:expr , else $fail:literal ] )?
+            ),* $(,)?
+        ]
+    ),* $(,)? ) => {
+        $crate::npc::NPC {
+            name: $name.into(),
+            all_states: vec![ $(
+                $crate::npc::ChatState {
+                    messages: vec![ $(
+                        ( $msg.into(), $delay )
+                    ),* ],
+                    options: vec![ $(
+                        $crate::npc::Reply {
+                            text: $option.into(),
+                            next: $state,
+                            trust: $crate::npc::npc!(@trust $( $trust )?),
+                            gate: $crate::npc::npc!(@gate $( $gate )?),
+                            trade: $crate::npc::npc!(@trade $( $deal, $fail )?),
+                        }
+                    ),* ],
+                    intents: vec![ $( $(
+                        $crate::npc::Intent {
+                            matcher: $matcher,
+                            next: $hstate,
+                            trust: $crate::npc::npc!(@trust $( $htrust )?),
+                        }
+                    ),+ )? ],
+                    attachments: vec![ $(
+                        $crate::event::Attachment {
+                            name: $fname.into(),
+                            contents: $fcontents.into(),
+                        }
+                    ),* ],
+                    offer: $crate::npc::npc!(@offer $( $job => $success / $failure when $accept )?),
+                    recruit: $crate::npc::npc!(@recruit $( $team )?),
+                }
+            ),* ],
+            ..Default::default()
+        }
+    };
+}
+pub(crate) use npc;
+
+#[cfg(test)]
+mod test {
+    use crate::jobs::Objective;
+
+    use super::*;
+
+    fn npc() -> NPC {
+        npc!(
+            "boss",
+            [
+                say "want a job?": 0,
+                ask "yes" => 1, "no" => 0 [trust -5], "big job?" => 1 [if Gate::trust(10)],
+            ],
+            [
+                say "it's a big one": 0,
+                offer Job::new("job", "boss", "a job").objective(Objective::create("localhost", "/x")) => 2 / 3 when "deal",
+                ask "deal" => 4, "pass" => 0,
+            ],
+            [
+                say "nice": 0,
+                ask,
+            ],
+            [
+                say "too bad": 0,
+                ask,
+            ],
+            [
+                say "go do it": 0,
+                ask,
+            ],
+        )
+        .in_faction("bosses")
+    }
+
+    /// Let the NPC finish typing whatever it's typing, as if the player waited for it
+    fn finish_typing(npc: &mut NPC, replies: &mut Replies<Event>) {
+        while npc.typing.is_some() {
+            npc.typing = Some(Instant::now());
+            npc.react(&Event::Tick, replies);
+        }
+    }
+
+    /// Everything the NPC did, leaving out when it started typing
+    fn said(replies: Replies<Event>) -> Vec<Event> {
+        let typing = |e: &&Event| matches!(e, Event::NPCTyping { .. });
+        replies
+            ._messages()
+            .iter()
+            .filter(|e| !typing(e))
+            .cloned()
+            .collect()
+    }
+
+    /// Start the NPC up, and wait for it to finish saying whatever it says first
+    pub(super) fn start(npc: &mut NPC) -> Vec<Event> {
+        let mut replies = Replies::default();
+        npc.start(&mut replies);
+        finish_typing(npc, &mut replies);
+        said(replies)
+    }
+
+    /// Have the NPC react to something, and wait for it to finish saying whatever it says in response
+    pub(super) fn react(npc: &mut NPC, event: &Event) -> Vec<Event> {
+        let mut replies = Replies::default();
+        npc.react(event, &mut replies);
+        finish_typing(npc, &mut replies);
+        said(replies)
+    }
+
+    #[test]
+    fn npc_offers_job_through_replies() {
+        let mut npc = npc();
+        start(&mut npc);
+        let said = react(&mut npc, &Event::player_chat("boss", "yes"));
+        assert_eq!(
+            said,
+            &[Event::npc_chat("boss", "it's a big one", &["deal", "pass"])]
+        );
+        // turning it down hands nothing out
+        let said = react(&mut npc, &Event::player_chat("boss", "pass"));
+        assert!(!said.iter().any(|e| matches!(e, Event::JobAccepted { .. })));
+        assert!(npc.jobs.is_empty());
+
+        react(&mut npc, &Event::player_chat("boss", "yes"));
+        let said = react(&mut npc, &Event::player_chat("boss", "deal"));
+        assert_eq!(said.len(), 2);
+        assert!(matches!(&said[0], Event::JobAccepted { job, deadline: None } if job.id == "job"));
+        assert_eq!(said[1], Event::npc_chat("boss", "go do it", &[]));
+    }
+
+    #[test]
+    fn npc_reacts_to_job_completion() {
+        let mut npc = npc();
+        start(&mut npc);
+        react(&mut npc, &Event::player_chat("boss", "yes"));
+        react(&mut npc, &Event::player_chat("boss", "deal"));
+        let said = react(&mut npc, &Event::JobCompleted("job".into()));
+        assert_eq!(said, &[Event::npc_chat("boss", "nice", &[])]);
+        assert_eq!(npc.world.relations.trust("boss"), JOB_TRUST);
+        assert_eq!(npc.world.relations.reputation("bosses"), JOB_REPUTATION);
+    }
+
+    #[test]
+    fn npc_reacts_to_job_failure() {
+        let mut npc = npc();
+        start(&mut npc);
+        react(&mut npc, &Event::player_chat("boss", "yes"));
+        react(&mut npc, &Event::player_chat("boss", "deal"));
+        let said = react(&mut npc, &Event::JobFailed("job".into()));
+        assert_eq!(said, &[Event::npc_chat("boss", "too bad", &[])]);
+        assert_eq!(npc.world.relations.trust("boss"), -JOB_TRUST);
+        assert_eq!(npc.world.relations.reputation("bosses"), -JOB_REPUTATION);
+    }
+
+    #[test]
+    fn npc_closes_chat_at_end() {
+        let mut npc = npc!("bye", [say "later": 0, ask "ok" => 1,]);
+        start(&mut npc);
+        let mut replies = Replies::default();
+        let flow = npc.react(&Event::player_chat("bye", "ok"), &mut replies);
+        assert!(matches!(flow, ControlFlow::Kill));
+        assert_eq!(
+            replies._messages(),
+            &[Event::NPCClosedChat { from: "bye".into() }]
+        );
+    }
+
+    #[test]
+    fn npc_hides_gated_options() {
+        let mut npc = npc();
+        let said = start(&mut npc);
+        assert_eq!(
+            said,
+            &[Event::npc_chat("boss", "want a job?", &["yes", "no"])]
+        );
+        let said = react(&mut npc, &Event::player_chat("boss", "big job?"));
+        // typing a hidden option doesn't pick it; it's just not understood
+        assert_eq!(said, &[Event::npc_chat("boss", "?", &["yes", "no"])]);
+
+        npc.world.relations.adjust_trust("boss", 10);
+        let said = react(&mut npc, &Event::player_chat("boss", "no"));
+        assert_eq!(
+            said,
+            &[Event::npc_chat("boss", "want a job?", &["yes", "no"])]
+        );
+        assert_eq!(npc.world.relations.trust("boss"), 5);
+        // picking "no" again drops trust before the options are shown, so account for that too
+        npc.world.relations.adjust_trust("boss", 10);
+        let said = react(&mut npc, &Event::player_chat("boss", "no"));
+        assert_eq!(
+            said,
+            &[Event::npc_chat(
+                "boss",
+                "want a job?",
+                &["yes", "no", "big job?"]
+            )]
+        );
+    }
+
+    #[test]
+    fn npc_joins_team() {
+        let mut npc = npc!(
+            "n0va",
+            [
+                say "need a hand?": 0,
+                ask "yes" => 1,
+            ],
+            [
+                say "i'm in": 0,
+                join team,
+                ask,
+            ],
+        )
+        .with_skill(Skill::Cracking, 4);
+        start(&mut npc);
+        assert!(npc.world.team.is_empty());
+        let said = react(&mut npc, &Event::player_chat("n0va", "yes"));
+        assert_eq!(
+            said[0],
+            Event::TeamChanged {
+                name: "n0va".into(),
+                joined: true,
+            }
+        );
+        let member = npc.world.team.get("n0va").unwrap().clone();
+        assert_eq!(member, Member::new("n0va", Skill::Cracking, 4));
+    }
+
+    #[test]
+    fn npc_trades_through_replies() {
+        let mut npc = npc!(
+            "fence",
+            [
+                say "buying or selling?": 0,
+                ask "selling" => 1 [trade Deal::BuyData, else 2],
+            ],
+            [
+                say "pleasure doing business": 0,
+                ask,
+            ],
+            [
+                say "you've got nothing i want": 0,
+                ask,
+            ],
+        )
+        .in_world(Arc::new(World::new(Default::default())));
+        start(&mut npc);
+        let said = react(&mut npc, &Event::player_chat("fence", "selling"));
+        assert_eq!(
+            said,
+            &[Event::npc_chat("fence", "you've got nothing i want", &[])]
+        );
+    }
+
+    #[test]
+    fn npc_ignores_other_jobs() {
+        let mut npc = npc();
+        start(&mut npc);
+        react(&mut npc, &Event::player_chat("boss", "yes"));
+        react(&mut npc, &Event::player_chat("boss", "deal"));
+        let said = react(&mut npc, &Event::JobCompleted("other".into()));
+        assert!(said.is_empty());
+    }
+
+    #[test]
+    fn npc_hears_typed_text() {
+        let mut npc = npc!(
+            "guard",
+            [
+                say "password?": 0,
+                hear
+                    Matcher::exactly("hunter2") => 1 [trust 5],
+                    Matcher::keywords(&["forgot", "don't know"]) => 2;
+                ask "bye" => 2,
+            ],
+            [
+                say "come on in": 0,
+                ask,
+            ],
+            [
+                say "get lost": 0,
+                ask,
+            ],
+        );
+        start(&mut npc);
+        let said = react(&mut npc, &Event::player_chat("guard", "Hunter2"));
+        assert_eq!(said, &[Event::npc_chat("guard", "?", &["bye"])]);
+        let said = react(&mut npc, &Event::player_chat("guard", " hunter2"));
+        assert_eq!(said, &[Event::npc_chat("guard", "come on in", &[])]);
+        assert_eq!(npc.world.relations.trust("guard"), 5);
+    }
+
+    #[test]
+    fn npc_hears_keywords() {
+        let mut npc = npc!(
+            "guard",
+            [
+                say "password?": 0,
+                hear
+                    Matcher::keywords(&["forgot", "don't know"]) => 1,
+                    Matcher::Anything => 2 [trust -1];
+                ask,
+            ],
+            [
+                say "then scram": 0,
+                ask,
+            ],
+            [
+                say "wrong": 0,
+                ask,
+            ],
+        );
+        start(&mut npc);
+        let said = react(&mut npc, &Event::player_chat("guard", "uh, I forgot it"));
+        assert_eq!(said, &[Event::npc_chat("guard", "then scram", &[])]);
+
+        // back to asking for the password
+        npc.state = 0;
+        let said = react(&mut npc, &Event::player_chat("guard", "swordfish"));
+        assert_eq!(said, &[Event::npc_chat("guard", "wrong", &[])]);
+        assert_eq!(npc.world.relations.trust("guard"), -1);
+    }
+
+    #[test]
+    fn npc_sends_files_to_inbox() {
+        let mut npc = npc!(
+            "courier",
+            [
+                say "here": 0,
+                attach "drop.txt": "the goods",
+                ask,
+            ],
+        )
+        .in_world(Arc::new(World::new(Default::default())));
+        let said = start(&mut npc);
+        let file = Attachment {
+            name: "drop.txt".into(),
+            contents: "the goods".into(),
+        };
+        assert_eq!(
+            said[0],
+            Event::NPCSentFile {
+                from: "courier".into(),
+                file,
+                path: "/inbox/courier/drop.txt".into(),
+            }
+        );
+        let saved = npc.world.player().read("/inbox/courier/drop.txt").unwrap();
+        assert_eq!(saved.contents, "the goods");
+    }
+
+    #[test]
+    fn npc_reacts_to_files() {
+        let mut npc = npc!(
+            "fence",
+            [
+                say "show me what you've got": 0,
+                hear Matcher::file(Matcher::keywords(&["payroll"])) => 1;
+                ask,
+            ],
+            [
+                say "now we're talking": 0,
+                ask,
+            ],
+        );
+        start(&mut npc);
+        let send = |name: &str| Event::PlayerSentFile {
+            to: "fence".into(),
+            file: Attachment {
+                name: name.into(),
+                contents: String::new(),
+            },
+        };
+        let said = react(&mut npc, &send("cat.jpg"));
+        assert_eq!(said, &[Event::npc_chat("fence", "?", &[])]);
+        let said = react(&mut npc, &send("payroll.csv"));
+        assert_eq!(said, &[Event::npc_chat("fence", "now we're talking", &[])]);
+    }
+
+    #[test]
+    fn npc_types_before_each_message() {
+        let mut npc = npc!("slow", [say "one": 0, say "two": 0, ask "ok" => 0,]);
+        let mut replies = Replies::default();
+        npc.start(&mut replies);
+        let typing = || Event::NPCTyping {
+            from: "slow".into(),
+        };
+        assert_eq!(replies._messages(), &[typing()]);
+        // nothing gets sent until it's done typing
+        npc.typing = Some(Instant::now() + Duration::from_secs(60));
+        let mut replies = Replies::default();
+        npc.react(&Event::Tick, &mut replies);
+        assert!(replies._messages().is_empty());
+        npc.typing = Some(Instant::now());
+        let mut replies = Replies::default();
+        npc.react(&Event::Tick, &mut replies);
+        assert_eq!(
+            replies._messages(),
+            &[Event::npc_chat("slow", "one", &[]), typing()]
+        );
+    }
+
+    #[test]
+    fn npc_typing_scales_with_length() {
+        let short = NPC::pace("hi", 100);
+        let long = NPC::pace("a much, much longer message", 100);
+        assert!(short < long);
+        assert!(short >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn npc_interrupted_while_typing() {
+        let mut npc = npc!(
+            "chatty",
+            [
+                say "so": 0,
+                say "anyway": 0,
+                say "long story short": 0,
+                ask "yes" => 1,
+            ],
+            [
+                say "great": 0,
+                ask,
+            ],
+        );
+        npc.start(&mut Replies::default());
+        npc.typing = Some(Instant::now() + Duration::from_secs(60));
+        // talking to someone else doesn't interrupt
+        let mut replies = Replies::default();
+        npc.react(&Event::player_chat("someone", "yes"), &mut replies);
+        assert!(replies._messages().is_empty());
+        assert!(npc.typing.is_some());
+        // but talking to them does: the rest of what they were saying is dropped, and they answer straight away
+        let said = react(&mut npc, &Event::player_chat("chatty", "yes"));
+        assert_eq!(said, &[Event::npc_chat("chatty", "great", &[])]);
+    }
+}
@@ -0,0 +1,121 @@
+//! How the NPCs of the CyberZone, and the factions they belong to, feel about the player.
+
+use dashmap::DashMap;
+
+use crate::constants::gameplay::MAX_STANDING;
+
+/// Every NPC's trust in the player, and every faction's reputation of them.
+///
+/// Both range from `-MAX_STANDING` (hatred) to `MAX_STANDING` (devotion), starting at 0.
+#[derive(Default)]
+pub struct Relations {
+    /// How much each NPC trusts the player, by NPC name
+    trust: DashMap<String, i32>,
+    /// How each faction sees the player, by faction name
+    reputation: DashMap<String, i32>,
+}
+
+/// Add to a standing, keeping it within the allowed range
+fn adjust(map: &DashMap<String, i32>, key: &str, delta: i32) -> i32 {
+    let mut value = map.entry(key.to_owned()).or_insert(0);
+    *value = (*value + delta).clamp(-MAX_STANDING, MAX_STANDING);
+    *value
+}
+
+impl Relations {
+    /// How much an NPC trusts the player
+    pub fn trust(&self, npc: &str) -> i32 {
+        self.trust.get(npc).map(|t| *t).unwrap_or(0)
+    }
+
+    /// Change how much an NPC trusts the player, returning the new value
+    pub fn adjust_trust(&self, npc: &str, delta: i32) -> i32 {
+        adjust(&self.trust, npc, delta)
+    }
+
+    /// What a faction thinks of the player
+    pub fn reputation(&self, faction: &str) -> i32 {
+        self.reputation.get(faction).map(|r| *r).unwrap_or(0)
+    }
+
+    /// Change what a faction thinks of the player, returning the new value
+    pub fn adjust_reputation(&self, faction: &str, delta: i32) -> i32 {
+        adjust(&self.reputation, faction, delta)
+    }
+}
+
+/// A requirement on the player's standing, e.g. to see a chat option at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Gate {
+    /// The NPC in question has to trust the player at least this much
+    Trust(i32),
+    /// The player's reputation with the named faction has to be at least this much
+    Reputation(String, i32),
+}
+
+impl Gate {
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn trust(min: i32) -> Self {
+        Self::Trust(min)
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    pub fn reputation(faction: &str, min: i32) -> Self {
+        Self::Reputation(faction.into(), min)
+    }
+
+    /// Check whether the player meets the requirement, when talking to the given NPC
+    pub fn met(&self, npc: &str, relations: &Relations) -> bool {
+        match self {
+            Self::Trust(min) => relations.trust(npc) >= *min,
+            Self::Reputation(faction, min) => relations.reputation(faction) >= *min,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn standings_start_neutral() {
+        let rel = Relations::default();
+        assert_eq!(rel.trust("admin"), 0);
+        assert_eq!(rel.reputation("redshell"), 0);
+    }
+
+    #[test]
+    fn standings_adjust_independently() {
+        let rel = Relations::default();
+        assert_eq!(rel.adjust_trust("admin", 5), 5);
+        assert_eq!(rel.adjust_trust("admin", -2), 3);
+        assert_eq!(rel.adjust_reputation("redshell", 7), 7);
+        assert_eq!(rel.trust("admin"), 3);
+        assert_eq!(rel.trust("yotie"), 0);
+        assert_eq!(rel.reputation("redshell"), 7);
+        assert_eq!(rel.reputation("admin"), 0);
+    }
+
+    #[test]
+    fn standings_clamp() {
+        let rel = Relations::default();
+        assert_eq!(rel.adjust_trust("admin", MAX_STANDING * 3), MAX_STANDING);
+        assert_eq!(
+            rel.adjust_reputation("corp", -MAX_STANDING * 3),
+            -MAX_STANDING
+        );
+    }
+
+    #[test]
+    fn gates_check_standings() {
+        let rel = Relations::default();
+        rel.adjust_trust("admin", 10);
+        rel.adjust_reputation("redshell", -10);
+        assert!(Gate::trust(10).met("admin", &rel));
+        assert!(!Gate::trust(11).met("admin", &rel));
+        assert!(!Gate::trust(10).met("yotie", &rel));
+        assert!(Gate::reputation("redshell", -10).met("admin", &rel));
+        assert!(!Gate::reputation("redshell", 0).met("admin", &rel));
+        assert!(Gate::reputation("corp", 0).met("admin", &rel));
+    }
+}
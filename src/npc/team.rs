@@ -0,0 +1,388 @@
+//! The NPCs who've joined the player's team, and the agents that carry out their part of a job.
+//!
+//! NPCs with a [`Skill`] can be recruited through chat, which puts them in [`World::team`]. From there, the player can
+//! send them on an active job with the `team` tool, which spawns an [`Operation`]: the member works through whichever
+//! of the job's objectives their skill covers, reporting in over chat, until they finish, fail, get caught, or sell
+//! the player out.
+
+use std::{sync::Arc, time::Duration};
+
+use tuig::{Agent, ControlFlow, Replies};
+
+use crate::{
+    constants::gameplay::{LOYAL_TRUST, MAX_SKILL, MAX_STANDING, MEMBER_WORK_SECS},
+    event::Event,
+    jobs::{Job, Objective},
+    rng::Rng,
+    world::World,
+};
+
+/// Something an NPC hacker specializes in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Skill {
+    /// Breaking into machines and walking out with their files
+    Cracking,
+    /// Talking people into doing things on the hacker's behalf
+    SocialEngineering,
+    /// Making things (especially evidence) disappear
+    Forensics,
+}
+
+impl Skill {
+    /// The skill's name, as shown to the player
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cracking => "cracking",
+            Self::SocialEngineering => "social engineering",
+            Self::Forensics => "forensics cleanup",
+        }
+    }
+
+    /// Whether someone with this skill can take care of the given objective
+    pub fn handles(&self, objective: &Objective) -> bool {
+        matches!(
+            (self, objective),
+            (Self::Cracking, Objective::Steal { .. })
+                | (Self::SocialEngineering, Objective::Create { .. })
+                | (Self::Forensics, Objective::Delete { .. })
+        )
+    }
+}
+
+/// An NPC on the player's team.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    /// The NPC's name, which is also who their reports come from
+    pub name: String,
+    /// What they're good at
+    pub skill: Skill,
+    /// How good they are at it, from 1 to [`MAX_SKILL`]
+    pub level: u32,
+    /// Whether they're out on a job right now
+    pub busy: bool,
+}
+
+impl Member {
+    pub fn new(name: &str, skill: Skill, level: u32) -> Self {
+        Self {
+            name: name.into(),
+            skill,
+            level: level.clamp(1, MAX_SKILL),
+            busy: false,
+        }
+    }
+}
+
+/// How a single attempt at an objective went.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    /// The objective's done
+    Success,
+    /// It didn't work, but nobody noticed
+    Failure,
+    /// It didn't work, and the member got caught trying
+    Caught,
+    /// The member sold the player out instead
+    Betrayal,
+}
+
+/// A team member working through their part of a job.
+pub struct Operation {
+    member: Member,
+    job: Job,
+    world: Arc<World>,
+    rng: Rng,
+    /// The objectives the member can take care of and hasn't gotten to yet
+    todo: Vec<Objective>,
+}
+
+impl Operation {
+    /// Send a member on a job. They'll only touch the objectives their skill [`handles`](Skill::handles).
+    pub fn new(member: Member, job: Job, world: Arc<World>, rng: Rng) -> Self {
+        let todo = job
+            .objectives
+            .iter()
+            .filter(|o| member.skill.handles(o))
+            .cloned()
+            .collect();
+        Self {
+            member,
+            job,
+            world,
+            rng,
+            todo,
+        }
+    }
+
+    fn report(&self, text: String, replies: &mut Replies<Event>) {
+        replies.queue(Event::TeamReport {
+            from: self.member.name.clone(),
+            text,
+        });
+    }
+
+    /// How long the member spends on the next objective
+    fn work_time(&mut self) -> Duration {
+        let (min, max) = MEMBER_WORK_SECS;
+        Duration::from_secs(self.rng.between(min, max))
+    }
+
+    /// Decide how the next attempt goes, based on the member's skill and how much they trust the player
+    fn roll(&mut self) -> Outcome {
+        let trust = self.world.relations.trust(&self.member.name);
+        let level = self.member.level as f32;
+        let betray = ((LOYAL_TRUST - trust) as f32 / 400.0).clamp(0.0, 0.25);
+        let caught = (MAX_SKILL as f32 - level) * 0.02;
+        let success = 0.4 + level * 0.06;
+        if self.rng.chance(betray) {
+            Outcome::Betrayal
+        } else if self.rng.chance(caught) {
+            Outcome::Caught
+        } else if self.rng.chance(success) {
+            Outcome::Success
+        } else {
+            Outcome::Failure
+        }
+    }
+
+    /// Actually make the objective happen in the world
+    fn perform(&self, objective: &Objective) -> Result<(), String> {
+        let host = |name: &str| {
+            self.world
+                .host(name)
+                .ok_or_else(|| format!("{} isn't a real host", name))
+        };
+        match objective {
+            Objective::Steal { host: from, path } => {
+                let file = host(from)?.read(path)?;
+                let name = path.rsplit('/').next().unwrap_or(path);
+                let player = self.world.player();
+                player.mkdir("/loot/", true)?;
+                player.write(&format!("/loot/{}", name), file.contents)
+            }
+            Objective::Delete { host: on, path } => host(on)?.remove(path),
+            Objective::Create { host: on, path } => {
+                let machine = host(on)?;
+                if path.ends_with('/') {
+                    machine.mkdir(path, true)
+                } else {
+                    if let Some((dir, _)) = path.rsplit_once('/') {
+                        machine.mkdir(&format!("{}/", dir), true)?;
+                    }
+                    machine.write(path, String::new())
+                }
+            }
+        }
+    }
+
+    /// Take the member off the job, and possibly off the team entirely
+    fn finish(&self, leaving: bool, replies: &mut Replies<Event>) -> ControlFlow {
+        if leaving {
+            self.world.team.remove(&self.member.name);
+            replies.queue(Event::TeamChanged {
+                name: self.member.name.clone(),
+                joined: false,
+            });
+        } else if let Some(mut member) = self.world.team.get_mut(&self.member.name) {
+            member.busy = false;
+        }
+        ControlFlow::Kill
+    }
+
+    /// Apply the outcome of an attempt at the next objective
+    fn resolve(&mut self, outcome: Outcome, replies: &mut Replies<Event>) -> ControlFlow {
+        let objective = self.todo.remove(0);
+        let task = objective.describe();
+        match outcome {
+            Outcome::Success => match self.perform(&objective) {
+                Ok(()) => self.report(format!("done: {}", task), replies),
+                Err(e) => {
+                    self.report(format!("couldn't {}: {}. heading out.", task, e), replies);
+                    return self.finish(false, replies);
+                }
+            },
+            Outcome::Failure => {
+                self.report(format!("couldn't {}. sorry, heading out.", task), replies);
+                return self.finish(false, replies);
+            }
+            Outcome::Caught => {
+                self.report(
+                    "they caught me. i'm lying low, don't call me.".into(),
+                    replies,
+                );
+                return self.finish(true, replies);
+            }
+            Outcome::Betrayal => {
+                self.report("nothing personal. they just paid better.".into(), replies);
+                let name = &self.member.name;
+                self.world.relations.adjust_trust(name, -2 * MAX_STANDING);
+                replies.queue(Event::JobFailed(self.job.id.clone()));
+                return self.finish(true, replies);
+            }
+        }
+        if self.todo.is_empty() {
+            self.report(
+                format!("that's my part of {} done", self.job.title),
+                replies,
+            );
+            self.finish(false, replies)
+        } else {
+            ControlFlow::sleep_for(self.work_time())
+        }
+    }
+}
+
+impl Agent<Event> for Operation {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        if self.todo.is_empty() {
+            self.report(
+                format!("nothing on {} i can help with", self.job.title),
+                replies,
+            );
+            return self.finish(false, replies);
+        }
+        self.report(format!("on it: {}", self.job.title), replies);
+        ControlFlow::sleep_for(self.work_time())
+    }
+
+    fn react(&mut self, _event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        // any event after waking up means the work time is over
+        if !self.world.jobs.contains_key(&self.job.id) {
+            self.report(format!("{} is off, heading out", self.job.title), replies);
+            return self.finish(false, replies);
+        }
+        let outcome = self.roll();
+        self.resolve(outcome, replies)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::machine::Machine;
+
+    use super::*;
+
+    fn world() -> Arc<World> {
+        let world = World::new(Default::default());
+        let corp = Machine::default();
+        corp.write("/payroll.db", "everyone: $0".into())
+            .expect("failed to write test file");
+        corp.write("/logs", "player was here".into())
+            .expect("failed to write test file");
+        world.hosts.insert("corp".into(), Arc::new(corp));
+        world
+            .team
+            .insert("n0va".into(), Member::new("n0va", Skill::Cracking, 5));
+        let job = Job::new("heist", "admin", "the heist")
+            .objective(Objective::steal("corp", "/payroll.db"))
+            .objective(Objective::delete("corp", "/logs"));
+        world.jobs.insert("heist".into(), job);
+        Arc::new(world)
+    }
+
+    fn op(world: &Arc<World>, skill: Skill) -> Operation {
+        let member = Member::new("n0va", skill, 5);
+        let job = world.jobs.get("heist").unwrap().clone();
+        Operation::new(member, job, world.clone(), Rng::seeded(0))
+    }
+
+    #[test]
+    fn skills_handle_objectives() {
+        let steal = Objective::steal("corp", "/payroll.db");
+        let delete = Objective::delete("corp", "/logs");
+        let create = Objective::create("corp", "/backdoor");
+        assert!(Skill::Cracking.handles(&steal));
+        assert!(!Skill::Cracking.handles(&delete));
+        assert!(Skill::Forensics.handles(&delete));
+        assert!(Skill::SocialEngineering.handles(&create));
+        assert!(!Skill::SocialEngineering.handles(&steal));
+    }
+
+    #[test]
+    fn member_only_takes_their_objectives() {
+        let world = world();
+        assert_eq!(op(&world, Skill::Cracking).todo.len(), 1);
+        assert_eq!(op(&world, Skill::Forensics).todo.len(), 1);
+        let mut op = op(&world, Skill::SocialEngineering);
+        let mut replies = Replies::default();
+        assert!(matches!(op.start(&mut replies), ControlFlow::Kill));
+        assert_eq!(
+            replies._messages(),
+            &[Event::TeamReport {
+                from: "n0va".into(),
+                text: "nothing on the heist i can help with".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn success_changes_world() {
+        let world = world();
+        let mut op = op(&world, Skill::Cracking);
+        op.start(&mut Replies::default());
+        let mut replies = Replies::default();
+        assert!(matches!(
+            op.resolve(Outcome::Success, &mut replies),
+            ControlFlow::Kill
+        ));
+        assert_eq!(replies._messages().len(), 2);
+        let loot = world.player().read("/loot/payroll.db").unwrap();
+        assert_eq!(loot.contents, "everyone: $0");
+        assert!(world.team.contains_key("n0va"));
+    }
+
+    #[test]
+    fn getting_caught_leaves_team() {
+        let world = world();
+        let mut op = op(&world, Skill::Forensics);
+        op.start(&mut Replies::default());
+        let mut replies = Replies::default();
+        op.resolve(Outcome::Caught, &mut replies);
+        assert!(replies._messages().contains(&Event::TeamChanged {
+            name: "n0va".into(),
+            joined: false,
+        }));
+        assert!(!world.team.contains_key("n0va"));
+        assert!(world.host("corp").unwrap().entry("/logs").is_ok());
+    }
+
+    #[test]
+    fn betrayal_sabotages_job() {
+        let world = world();
+        let mut op = op(&world, Skill::Forensics);
+        op.start(&mut Replies::default());
+        let mut replies = Replies::default();
+        op.resolve(Outcome::Betrayal, &mut replies);
+        assert!(replies
+            ._messages()
+            .contains(&Event::JobFailed("heist".into())));
+        assert!(!world.team.contains_key("n0va"));
+        assert_eq!(world.relations.trust("n0va"), -MAX_STANDING);
+    }
+
+    #[test]
+    fn loyal_experts_always_succeed() {
+        let world = world();
+        world.relations.adjust_trust("n0va", LOYAL_TRUST);
+        let member = Member::new("n0va", Skill::Cracking, MAX_SKILL);
+        let job = world.jobs.get("heist").unwrap().clone();
+        let mut op = Operation::new(member, job, world.clone(), Rng::seeded(7));
+        for _ in 0..100 {
+            assert_eq!(op.roll(), Outcome::Success);
+        }
+    }
+
+    #[test]
+    fn finished_job_stops_operation() {
+        let world = world();
+        let mut op = op(&world, Skill::Cracking);
+        op.start(&mut Replies::default());
+        world.jobs.remove("heist");
+        let mut replies = Replies::default();
+        assert!(matches!(
+            op.react(&Event::Tick, &mut replies),
+            ControlFlow::Kill
+        ));
+        assert!(world.player().entry("/loot/payroll.db").is_err());
+    }
+}
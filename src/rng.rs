@@ -0,0 +1,129 @@
+//! A small, seedable random number generator.
+//!
+//! This is used instead of an external crate so anything generated from a seed comes out exactly the same on every
+//! platform and with every version of every dependency, which keeps worlds reproducible and tests deterministic.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A SplitMix64 generator. Not remotely cryptographically secure, but fast, tiny, and good enough for games.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Create a generator which will always produce the same sequence for the same seed.
+    pub fn seeded(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Create a generator seeded from the current time, for when reproducibility doesn't matter.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self(nanos)
+    }
+
+    /// Create a new, independent generator seeded from this one, e.g. to generate sub-parts of something without
+    /// later parts depending on how much randomness earlier ones used.
+    pub fn fork(&mut self) -> Self {
+        Self(self.next_u64())
+    }
+
+    /// Get the next raw random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A random number in `0..n`. Panics if `n` is 0.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "can't pick a number below 0");
+        self.next_u64() % n
+    }
+
+    /// A random number in `lo..=hi`.
+    pub fn between(&mut self, lo: u64, hi: u64) -> u64 {
+        lo + self.below(hi - lo + 1)
+    }
+
+    /// A random float in `0.0..1.0`.
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns true with probability `p`.
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.unit() < p
+    }
+
+    /// Pick a random item from a slice. Panics if it's empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    /// Shuffle a slice in-place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Rng::seeded(1234);
+        let mut b = Rng::seeded(1234);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn different_seed_different_sequence() {
+        let mut a = Rng::seeded(1234);
+        let mut b = Rng::seeded(1235);
+        let a: Vec<_> = (0..10).map(|_| a.next_u64()).collect();
+        let b: Vec<_> = (0..10).map(|_| b.next_u64()).collect();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn ranges_stay_in_range() {
+        let mut rng = Rng::seeded(0);
+        for _ in 0..1000 {
+            assert!(rng.below(7) < 7);
+            let b = rng.between(3, 5);
+            assert!((3..=5).contains(&b));
+            let u = rng.unit();
+            assert!((0.0..1.0).contains(&u));
+        }
+    }
+
+    #[test]
+    fn chance_extremes() {
+        let mut rng = Rng::seeded(0);
+        for _ in 0..1000 {
+            assert!(!rng.chance(0.0));
+            assert!(rng.chance(1.0));
+        }
+    }
+
+    #[test]
+    fn shuffle_keeps_items() {
+        let mut rng = Rng::seeded(42);
+        let mut items: Vec<_> = (0..20).collect();
+        rng.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,117 @@
+//! Saving and loading the game.
+//!
+//! Save files are plain text, split into named sections, one per thing that wants to be saved (e.g. one per
+//! [`App`](crate::app::App) that implements [`App::save`](crate::app::App::save)). Each section starts with a header
+//! line of `[name]`, and everything up to the next header is its contents. Lines in the contents which would look like
+//! headers, or which start with a backslash, are escaped by prefixing them with a backslash.
+
+use std::{collections::BTreeMap, fmt, fs, io::ErrorKind};
+
+/// The contents of a save file, as sections by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Save {
+    sections: BTreeMap<String, String>,
+}
+
+impl Save {
+    /// Get a section's contents, if it was saved
+    pub fn section(&self, name: &str) -> Option<&str> {
+        self.sections.get(name).map(|s| s.as_str())
+    }
+
+    /// Set a section's contents, replacing anything that was there
+    pub fn set(&mut self, name: &str, contents: String) {
+        self.sections.insert(name.into(), contents);
+    }
+
+    /// Parse a save from its text format.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut save = Self::default();
+        let mut current: Option<(String, Vec<&str>)> = None;
+        for (num, line) in text.lines().enumerate() {
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if let Some((name, lines)) = current.take() {
+                    save.set(&name, lines.join("\n"));
+                }
+                current = Some((name.into(), vec![]));
+                continue;
+            }
+            let lines = match &mut current {
+                Some((_, lines)) => lines,
+                None if line.is_empty() => continue,
+                None => return Err(format!("line {}: contents outside of any section", num + 1)),
+            };
+            lines.push(line.strip_prefix('\\').unwrap_or(line));
+        }
+        if let Some((name, lines)) = current {
+            save.set(&name, lines.join("\n"));
+        }
+        Ok(save)
+    }
+
+    /// Load a save from a file. If there's no file there, that's an empty save, not an error.
+    pub fn load(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("failed to read {}: {}", path, e)),
+        }
+    }
+
+    /// Write the save to a file, overwriting whatever was there.
+    pub fn store(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|e| format!("failed to write {}: {}", path, e))
+    }
+}
+
+impl fmt::Display for Save {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, contents) in &self.sections {
+            writeln!(f, "[{}]", name)?;
+            if contents.is_empty() {
+                continue;
+            }
+            for line in contents.split('\n') {
+                if line.starts_with('[') || line.starts_with('\\') {
+                    write!(f, "\\")?;
+                }
+                writeln!(f, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut save = Save::default();
+        save.set("notes", "first\nsecond\n[not a header]\n\\backslash".into());
+        save.set("empty", String::new());
+        save.set("other", "stuff".into());
+        let text = save.to_string();
+        assert_eq!(Save::parse(&text), Ok(save));
+    }
+
+    #[test]
+    fn parse_sections() {
+        let save = Save::parse("[a]\none\ntwo\n[b]\nthree\n").unwrap();
+        assert_eq!(save.section("a"), Some("one\ntwo"));
+        assert_eq!(save.section("b"), Some("three"));
+        assert_eq!(save.section("c"), None);
+    }
+
+    #[test]
+    fn parse_rejects_orphan_contents() {
+        assert!(Save::parse("orphan\n[a]\n").is_err());
+    }
+
+    #[test]
+    fn missing_file_is_empty_save() {
+        let save = Save::load("/this/path/really/should/not/exist.save").unwrap();
+        assert_eq!(save, Save::default());
+    }
+}
@@ -0,0 +1,358 @@
+//! The player's preferences, which persist between games in a config file.
+//!
+//! Like the [clock](crate::clock), there's only ever one set of settings in use, so they're global. Anything that
+//! cares can just ask for [`current`] ones, and [`apply`] changes them everywhere at once.
+//!
+//! The config file is just `name = value` lines; blank lines and lines starting with `#` are ignored.
+
+use std::{fmt, fs, io::ErrorKind, sync::RwLock, time::Duration};
+
+use tuig::io::{
+    fmt::{Cell, Color},
+    Key,
+};
+
+use crate::{clock, constants::gameplay::CLOCK_SPEED};
+
+/// How the game's colors are adjusted before they're drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Theme {
+    /// The colors as the game picks them
+    Default,
+    /// Dim colors brightened up, so everything stands out against the background
+    HighContrast,
+    /// No colors at all, just the terminal's default foreground and background
+    Monochrome,
+}
+
+impl Theme {
+    /// Every theme, in the order the settings tab cycles through them
+    pub const ALL: [Theme; 3] = [Theme::Default, Theme::HighContrast, Theme::Monochrome];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Default => "default",
+            Theme::HighContrast => "high-contrast",
+            Theme::Monochrome => "monochrome",
+        }
+    }
+
+    fn brighten(color: Color) -> Color {
+        match color {
+            Color::Black | Color::BrightBlack => Color::White,
+            Color::Red => Color::BrightRed,
+            Color::Green => Color::BrightGreen,
+            Color::Yellow => Color::BrightYellow,
+            Color::Blue => Color::BrightBlue,
+            Color::Magenta => Color::BrightMagenta,
+            Color::Cyan => Color::BrightCyan,
+            Color::White => Color::BrightWhite,
+            other => other,
+        }
+    }
+
+    /// Recolor a single cell according to the theme
+    pub fn apply(&self, cell: &mut Cell) {
+        match self {
+            Theme::Default => (),
+            Theme::HighContrast => cell.fmt.fg = Self::brighten(cell.fmt.fg),
+            Theme::Monochrome => {
+                cell.fmt.fg = Color::Default;
+                cell.fmt.bg = Color::Default;
+            }
+        }
+    }
+}
+
+/// Everything the player can configure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// Switch to the next tab (on top of the F keys, which always work)
+    pub next_tab: Key,
+    /// Switch to the previous tab
+    pub prev_tab: Key,
+    /// Pin whatever's current in chat or the terminal to the notes tab
+    pub pin: Key,
+    /// Search through everything said in chat
+    pub search: Key,
+    /// How fast NPCs type, as a percentage of normal speed
+    pub text_speed: u32,
+    /// How many in-game seconds pass per real second
+    pub clock_speed: u32,
+    pub theme: Theme,
+    /// Accessibility: NPC messages all arrive at once, without any typing delays
+    pub instant_text: bool,
+    /// Accessibility: everything is drawn in bold
+    pub bold_text: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            next_tab: Key::PageDown,
+            prev_tab: Key::PageUp,
+            pin: Key::Insert,
+            search: Key::Home,
+            text_speed: 100,
+            clock_speed: CLOCK_SPEED as u32,
+            theme: Theme::Default,
+            instant_text: false,
+            bold_text: false,
+        }
+    }
+}
+
+/// The name a key is written as in the config file.
+pub fn key_name(key: Key) -> String {
+    let name = match key {
+        Key::Char(' ') => "space",
+        Key::Char(c) => return c.to_string(),
+        Key::F(n) => return format!("f{}", n),
+        Key::Backspace => "backspace",
+        Key::Delete => "delete",
+        Key::Up => "up",
+        Key::Down => "down",
+        Key::Left => "left",
+        Key::Right => "right",
+        Key::Enter => "enter",
+        Key::Tab => "tab",
+        Key::BackTab => "backtab",
+        Key::Escape => "escape",
+        Key::Home => "home",
+        Key::End => "end",
+        Key::PageUp => "pageup",
+        Key::PageDown => "pagedown",
+        Key::Insert => "insert",
+        Key::LeftShift => "lshift",
+        Key::RightShift => "rshift",
+        Key::LeftCtrl => "lctrl",
+        Key::RightCtrl => "rctrl",
+        Key::LeftAlt => "lalt",
+        Key::RightAlt => "ralt",
+        Key::LeftSuper => "lsuper",
+        Key::RightSuper => "rsuper",
+    };
+    name.into()
+}
+
+/// Parse a key from its [`key_name`].
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(c));
+    }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        return Some(Key::F(n));
+    }
+    let key = match name {
+        "space" => Key::Char(' '),
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "enter" => Key::Enter,
+        "tab" => Key::Tab,
+        "backtab" => Key::BackTab,
+        "escape" => Key::Escape,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "insert" => Key::Insert,
+        "lshift" => Key::LeftShift,
+        "rshift" => Key::RightShift,
+        "lctrl" => Key::LeftCtrl,
+        "rctrl" => Key::RightCtrl,
+        "lalt" => Key::LeftAlt,
+        "ralt" => Key::RightAlt,
+        "lsuper" => Key::LeftSuper,
+        "rsuper" => Key::RightSuper,
+        _ => return None,
+    };
+    Some(key)
+}
+
+impl Settings {
+    /// How long an NPC should actually wait, given how long they'd wait at normal text speed
+    pub fn delay(&self, millis: usize) -> Duration {
+        if self.instant_text {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(millis as u64 * 100 / self.text_speed.max(1) as u64)
+        }
+    }
+
+    /// Apply the theme and accessibility options to a cell that's about to be drawn
+    pub fn style(&self, cell: &mut Cell) {
+        self.theme.apply(cell);
+        if self.bold_text {
+            cell.fmt.bold = true;
+        }
+    }
+
+    /// Parse settings from the config file format. Anything not mentioned keeps its default.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut settings = Self::default();
+        for (num, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |msg: &str| format!("line {}: {}", num + 1, msg);
+            let (name, value) = match line.split_once('=') {
+                Some((n, v)) => (n.trim(), v.trim()),
+                None => return Err(err("expected `name = value`")),
+            };
+            let key = || parse_key(value).ok_or_else(|| err("unknown key"));
+            let number = || value.parse::<u32>().map_err(|_| err("expected a number"));
+            let flag = || {
+                value
+                    .parse::<bool>()
+                    .map_err(|_| err("expected true or false"))
+            };
+            match name {
+                "next_tab" => settings.next_tab = key()?,
+                "prev_tab" => settings.prev_tab = key()?,
+                "pin" => settings.pin = key()?,
+                "search" => settings.search = key()?,
+                "text_speed" => settings.text_speed = number()?.max(1),
+                "clock_speed" => settings.clock_speed = number()?.max(1),
+                "theme" => {
+                    settings.theme = *Theme::ALL
+                        .iter()
+                        .find(|t| t.name() == value)
+                        .ok_or_else(|| err("unknown theme"))?
+                }
+                "instant_text" => settings.instant_text = flag()?,
+                "bold_text" => settings.bold_text = flag()?,
+                // probably from a newer version; better to keep going than throw out everything else
+                _ => continue,
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Load settings from a file. If there's no file there, that's the defaults, not an error.
+    pub fn load(path: &str) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("failed to read {}: {}", path, e)),
+        }
+    }
+
+    /// Write the settings to a file, overwriting whatever was there.
+    pub fn store(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|e| format!("failed to write {}: {}", path, e))
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "next_tab = {}", key_name(self.next_tab))?;
+        writeln!(f, "prev_tab = {}", key_name(self.prev_tab))?;
+        writeln!(f, "pin = {}", key_name(self.pin))?;
+        writeln!(f, "search = {}", key_name(self.search))?;
+        writeln!(f, "text_speed = {}", self.text_speed)?;
+        writeln!(f, "clock_speed = {}", self.clock_speed)?;
+        writeln!(f, "theme = {}", self.theme.name())?;
+        writeln!(f, "instant_text = {}", self.instant_text)?;
+        writeln!(f, "bold_text = {}", self.bold_text)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SETTINGS: RwLock<Settings> = RwLock::new(Settings::default());
+}
+
+/// The settings currently in use.
+pub fn current() -> Settings {
+    SETTINGS.read().unwrap().clone()
+}
+
+/// Start using new settings, including updating anything that doesn't just check [`current`] when it needs to.
+pub fn apply(settings: Settings) {
+    clock::set_speed(settings.clock_speed as f32);
+    *SETTINGS.write().unwrap() = settings;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let settings = Settings {
+            next_tab: Key::Char(' '),
+            prev_tab: Key::F(12),
+            pin: Key::Char('p'),
+            search: Key::F(11),
+            text_speed: 250,
+            clock_speed: 120,
+            theme: Theme::Monochrome,
+            instant_text: true,
+            bold_text: true,
+        };
+        assert_eq!(Settings::parse(&settings.to_string()), Ok(settings));
+    }
+
+    #[test]
+    fn every_key_roundtrips() {
+        let keys = [
+            Key::Char('x'),
+            Key::F(3),
+            Key::PageUp,
+            Key::BackTab,
+            Key::RightSuper,
+        ];
+        for key in keys {
+            assert_eq!(parse_key(&key_name(key)), Some(key));
+        }
+    }
+
+    #[test]
+    fn parse_keeps_defaults() {
+        let settings =
+            Settings::parse("# comment\n\ntheme = high-contrast\nfuture = thing\n").unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                theme: Theme::HighContrast,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_bad_values() {
+        assert!(Settings::parse("text_speed = fast").is_err());
+        assert!(Settings::parse("pin = hyper").is_err());
+        assert!(Settings::parse("just words").is_err());
+    }
+
+    #[test]
+    fn text_speed_scales_delays() {
+        let fast = Settings {
+            text_speed: 200,
+            ..Default::default()
+        };
+        assert_eq!(fast.delay(500), Duration::from_millis(250));
+        let instant = Settings {
+            instant_text: true,
+            ..Default::default()
+        };
+        assert_eq!(instant.delay(500), Duration::ZERO);
+    }
+
+    #[test]
+    fn themes_recolor() {
+        let mut cell = Cell::of('x');
+        cell.fmt.fg = Color::BrightBlack;
+        Theme::HighContrast.apply(&mut cell);
+        assert_eq!(cell.fmt.fg, Color::White);
+        Theme::Monochrome.apply(&mut cell);
+        assert_eq!(cell.fmt.fg, Color::Default);
+    }
+}
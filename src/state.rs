@@ -1,4 +1,6 @@
-use crate::machine::Machine;
+use std::sync::Arc;
+
+use crate::{economy::Wallet, machine::Machine};
 
 /// The current state of the game, including the state of the UI.
 #[derive(Default)]
@@ -7,4 +9,6 @@ pub struct GameState {
     pub player_name: String,
     /// The player's computer
     pub machine: Machine,
+    /// The player's wallet, shared with the [`World`](crate::world::World)
+    pub wallet: Arc<Wallet>,
 }
@@ -138,6 +138,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         assert_eq!(completer.complete("", &clis), "");
         assert_eq!(completer.complete("m", &clis), "");
@@ -168,6 +169,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         assert_eq!(completer.complete("", &clis), "");
         assert_eq!(completer.complete("v", &clis), "");
@@ -197,6 +199,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         assert_eq!(completer.complete("", &clis), "");
         assert_eq!(completer.complete("f", &clis), "");
@@ -229,6 +232,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         assert_eq!(completer.complete("qf", &clis), "");
         assert_eq!(completer.complete("fv ", &clis), "");
@@ -271,6 +275,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/stuff/".into(),
+            ..Default::default()
         };
         assert_eq!(completer.complete("qf", &clis), "");
         assert_eq!(completer.complete("fv ", &clis), "");
@@ -82,6 +82,10 @@ impl AutocompleteType {
                     .map(|s| format!("{}/", s))
                     .collect::<String>();
                 let prefix = format!("{}{}", state.cwd, cmd_dir);
+                // no peeking at what `ls` wouldn't show either
+                if !state.machine.can_access(&state.user, &prefix) {
+                    return String::new();
+                }
                 let files = match state.machine.readdir(&prefix) {
                     Ok(f) => f,
                     Err(_) => return String::new(),
@@ -97,6 +101,10 @@ impl AutocompleteType {
                     }),
                 )
             }
+            Self::Hostname => autocomplete(
+                prefix,
+                state.world.net.known.iter().map(|h| h.key().clone()),
+            ),
             _ => todo!(),
         }
     }
@@ -106,7 +114,7 @@ impl AutocompleteType {
 mod test {
     use std::sync::Arc;
 
-    use crate::machine::Machine;
+    use crate::{machine::Machine, world::World};
 
     use super::*;
 
@@ -137,6 +145,34 @@ mod test {
         assert_eq!(autocomplete("g", opts), "orgonzola");
     }
 
+    #[test]
+    fn local_file_respects_permissions() {
+        let machine = Machine::default();
+        machine.mkdir("/root/", false).unwrap();
+        machine.mkdir("/home/wes/", true).unwrap();
+        machine
+            .write("/root/secrets", "".into())
+            .expect("Failed to write test file");
+        machine
+            .write("/home/wes/diary", "".into())
+            .expect("Failed to write test file");
+        let mut clis = CliState {
+            machine: Arc::new(machine),
+            cwd: "/".into(),
+            user: "www-data".into(),
+            ..Default::default()
+        };
+        let ac = AutocompleteType::LocalFile;
+        assert_eq!(ac.complete("ro", &clis), "ot/");
+        assert_eq!(ac.complete("root/s", &clis), "");
+        assert_eq!(ac.complete("home/w", &clis), "es/");
+        assert_eq!(ac.complete("home/wes/d", &clis), "");
+        clis.user = "wes".into();
+        assert_eq!(ac.complete("home/wes/d", &clis), "iary");
+        clis.user = "root".into();
+        assert_eq!(ac.complete("root/s", &clis), "ecrets");
+    }
+
     #[test]
     fn none_doesnt_autocomplete() {
         let machine = Machine::default();
@@ -149,6 +185,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         let ac = AutocompleteType::None;
         assert_eq!(ac.complete("", &clis), "");
@@ -169,6 +206,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         let ac = AutocompleteType::choices(&["mass", "help", "gorgonzola"]);
         assert_eq!(ac.complete("", &clis), "");
@@ -192,6 +230,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         let ac = AutocompleteType::LocalFile;
         assert_eq!(ac.complete("", &clis), "");
@@ -226,6 +265,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/stuff/".into(),
+            ..Default::default()
         };
         let ac = AutocompleteType::LocalFile;
         assert_eq!(ac.complete("", &clis), "");
@@ -255,6 +295,7 @@ mod test {
         let clis = CliState {
             machine: Arc::new(machine),
             cwd: "/".into(),
+            ..Default::default()
         };
         let ac = AutocompleteType::LocalFile;
         assert_eq!(ac.complete("", &clis), "");
@@ -263,4 +304,27 @@ mod test {
         assert_eq!(ac.complete("stuff/", &clis), "bongos");
         assert_eq!(ac.complete("stuff/bo", &clis), "ngos");
     }
+
+    #[test]
+    fn hostname_autocompletes_known_hosts() {
+        let world = World::default();
+        world
+            .net
+            .add("hexacorp-db04", "172.16.4.20".parse().unwrap());
+        world
+            .net
+            .add("hexacorp-web02", "172.16.4.21".parse().unwrap());
+        let clis = CliState {
+            world: Arc::new(world),
+            ..Default::default()
+        };
+        let ac = AutocompleteType::Hostname;
+        // hosts the player hasn't found yet don't give themselves away
+        assert_eq!(ac.complete("hexa", &clis), "");
+        clis.world.net.learn("hexacorp-db04");
+        assert_eq!(ac.complete("hexa", &clis), "corp-db04");
+        clis.world.net.learn("hexacorp-web02");
+        assert_eq!(ac.complete("hexa", &clis), "corp-");
+        assert_eq!(ac.complete("hexacorp-w", &clis), "eb02");
+    }
 }
@@ -0,0 +1,42 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, event::Event};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `cat` command, to print out the contents of files.
+pub struct Cat;
+
+impl Tool for Cat {
+    fn name(&self) -> &'static str {
+        "cat"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        if line.ends_with(char::is_whitespace) {
+            AutocompleteType::LocalFile.complete("", state)
+        } else if let Some(last) = line.rsplit(char::is_whitespace).next() {
+            AutocompleteType::LocalFile.complete(last, state)
+        } else {
+            String::new()
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let mut lines = vec![];
+        for file in line.split_whitespace() {
+            let path = if file.starts_with('/') {
+                file.into()
+            } else {
+                format!("{}{}", state.cwd, file)
+            };
+            match state.read(&path) {
+                Ok(f) => lines.extend(f.contents.lines().map(|l| text!["{}\n"(l)])),
+                Err(e) => {
+                    lines.push(text![bright_red "ERROR", ": failed to read {}: {}\n"(file, e)])
+                }
+            }
+        }
+        Box::new(FixedOutput(state.pid, lines))
+    }
+}
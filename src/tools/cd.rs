@@ -2,16 +2,22 @@ use std::mem;
 
 use tuig::{io::text, Agent, ControlFlow, Replies};
 
-use crate::{app::CliState, event::Event};
+use crate::{
+    app::CliState,
+    event::{Event, Pid},
+};
 
 use super::{AutocompleteType, FixedOutput, Tool};
 
 /// The agent which actually does the changing of directories
-struct CdAgent(String);
+struct CdAgent(Pid, String);
 impl Agent<Event> for CdAgent {
     fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
-        replies.queue(Event::ChangeDir(mem::take(&mut self.0)));
-        replies.queue(Event::CommandDone);
+        replies.queue(Event::ChangeDir {
+            pid: self.0,
+            dir: mem::take(&mut self.1),
+        });
+        replies.queue(Event::CommandDone(self.0));
         ControlFlow::Kill
     }
 }
@@ -49,9 +55,12 @@ impl Tool for Cd {
         } else {
             format!("/{}/", target_comps.join("/"))
         };
-        match state.machine.readdir(&res) {
-            Ok(_) => Box::new(CdAgent(res)),
-            Err(e) => Box::new(FixedOutput(vec![text![bright_red "ERROR", ": {}\n"(e)]])),
+        match state.enter(&res) {
+            Ok(()) => Box::new(CdAgent(state.pid, res)),
+            Err(e) => Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": {}\n"(e)]],
+            )),
         }
     }
 }
@@ -0,0 +1,268 @@
+use std::{
+    collections::HashSet,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+use tuig::{io::text, Agent, ControlFlow, Replies};
+
+use crate::{
+    app::CliState,
+    constants::gameplay::{CRACK_RATE, CRACK_STEP_MS, WORDLIST_EXT},
+    event::{Event, Pid},
+    machine::crypt::{self, HashKind},
+    world::World,
+};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// The agent which does the cracking, a batch of guesses at a time, in the background
+struct Cracker {
+    pid: Pid,
+    world: Arc<World>,
+    /// Every word from every wordlist, without repeats
+    words: Vec<String>,
+    /// How many wordlists the words came from
+    lists: usize,
+    /// The users whose passwords haven't been cracked yet, with their hashes
+    hashes: Vec<(String, String)>,
+    /// How many users there were to begin with
+    total: usize,
+    /// The index of the next guess to make, as in [`crypt::guess`]
+    next: usize,
+}
+
+impl Cracker {
+    /// Make one batch of guesses, as big as the player's hardware allows, reporting anything that's cracked
+    fn step(&mut self, replies: &mut Replies<Event>) {
+        let work = CRACK_RATE << self.world.hardware.load(Ordering::Relaxed);
+        // every guess has to be checked against every hash, since they're all salted differently
+        let cost: u64 = self
+            .hashes
+            .iter()
+            .filter_map(|(_, h)| HashKind::identify(h))
+            .map(|k| k.cost())
+            .sum();
+        let batch = (work / cost.max(1)).max(1) as usize;
+        let count = crypt::guesses(self.words.len());
+        let end = (self.next + batch).min(count);
+        let quarter = self.next * 4 / count;
+        for i in self.next..end {
+            let guess = match crypt::guess(&self.words, i) {
+                Some(guess) => guess,
+                None => break,
+            };
+            let pid = self.pid;
+            self.hashes.retain(|(user, hashed)| {
+                if crypt::verify(hashed, &guess) {
+                    let line = text![bright_green "Cracked", ": ", bright_white "{}"(user), ":{}\n"(guess)];
+                    replies.queue(Event::output(pid, line));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        self.next = end;
+        if self.next * 4 / count > quarter && !self.done() {
+            let line = text![bright_black "{}% of guesses made\n"(self.next * 100 / count)];
+            replies.queue(Event::output(self.pid, line));
+        }
+    }
+
+    /// Whether there's nothing left to crack, or nothing left to try
+    fn done(&self) -> bool {
+        self.hashes.is_empty() || self.next >= crypt::guesses(self.words.len())
+    }
+}
+
+impl Agent<Event> for Cracker {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        let mut kinds: Vec<_> = self
+            .hashes
+            .iter()
+            .filter_map(|(_, h)| HashKind::identify(h))
+            .map(|k| k.to_string())
+            .collect();
+        kinds.sort();
+        kinds.dedup();
+        let header = text![
+            "Cracking {} hashes ({}) with {} words from {} wordlists, in the background.\n"(
+                self.total,
+                kinds.join(", "),
+                self.words.len(),
+                self.lists
+            )
+        ];
+        replies.queue(Event::output(self.pid, header));
+        // it'll be a while, so the player can get on with other things
+        replies.queue(Event::CommandDone(self.pid));
+        ControlFlow::sleep_for(Duration::from_millis(CRACK_STEP_MS))
+    }
+
+    fn react(&mut self, _event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        self.step(replies);
+        if !self.done() {
+            return ControlFlow::sleep_for(Duration::from_millis(CRACK_STEP_MS));
+        }
+        let cracked = self.total - self.hashes.len();
+        let summary = text!["Finished cracking: {} of {} passwords found\n"(
+            cracked, self.total
+        )];
+        replies.queue(Event::output(self.pid, summary));
+        ControlFlow::Kill
+    }
+}
+
+/// Implementation of [`Tool`] for the `crack` command, to guess the passwords behind a shadow file's hashes.
+///
+/// `crack <shadow> [wordlist...]` tries every word in the wordlists, then every word with a number on the end, against
+/// every hash. With no wordlists given, it uses every file on the machine ending in [`WORDLIST_EXT`]. It runs in the
+/// background, and how fast depends on the player's hardware and what kind of hashes they are.
+pub struct Crack;
+
+impl Crack {
+    /// Read the file at a path, relative to the CLI's working directory, if the CLI's user is allowed to
+    fn read(path: &str, state: &CliState) -> Result<String, String> {
+        state
+            .read(&state.path(path))
+            .map(|f| f.contents)
+            .map_err(|e| format!("failed to read {}: {}", path, e))
+    }
+
+    /// Set up the cracking, from the arguments to the command
+    fn prepare(&self, line: &str, state: &CliState) -> Result<Cracker, String> {
+        let mut args = line.split_whitespace();
+        let shadow = args.next().ok_or("usage: crack <shadow> [wordlist...]")?;
+        let hashes = crypt::parse_shadow(&Self::read(shadow, state)?);
+        if hashes.is_empty() {
+            return Err(format!("no password hashes in {}", shadow));
+        }
+        let mut lists = args
+            .map(|path| Self::read(path, state))
+            .collect::<Result<Vec<_>, _>>()?;
+        if lists.is_empty() {
+            // read like any other file, so it's logged, and ones the user can't read are left out
+            lists = state
+                .machine
+                .files()
+                .into_iter()
+                .filter(|(path, _)| path.ends_with(WORDLIST_EXT))
+                .filter_map(|(path, _)| state.read(&path).ok())
+                .map(|f| f.contents)
+                .collect();
+        }
+        if lists.is_empty() {
+            return Err(format!("no wordlists; find some {} files", WORDLIST_EXT));
+        }
+        let mut seen = HashSet::new();
+        let words: Vec<String> = lists
+            .iter()
+            .flat_map(|l| l.lines())
+            .map(str::trim)
+            .filter(|w| !w.is_empty() && !w.starts_with('#'))
+            .filter(|w| seen.insert(w.to_string()))
+            .map(String::from)
+            .collect();
+        if words.is_empty() {
+            return Err("no words in the wordlists".into());
+        }
+        Ok(Cracker {
+            pid: state.pid,
+            world: state.world.clone(),
+            words,
+            lists: lists.len(),
+            total: hashes.len(),
+            hashes,
+            next: 0,
+        })
+    }
+}
+
+impl Tool for Crack {
+    fn name(&self) -> &'static str {
+        "crack"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        match line.rsplit(char::is_whitespace).next() {
+            Some(last) => AutocompleteType::LocalFile.complete(last, state),
+            None => String::new(),
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        match self.prepare(line, state) {
+            Ok(cracker) => Box::new(cracker),
+            Err(e) => Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": {}\n"(e)]],
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constants::gameplay::ACCESS_LOG,
+        machine::{crypt::hash, Machine},
+    };
+
+    use super::*;
+
+    #[test]
+    fn empty_wordlists_rejected() {
+        let machine = Machine::default();
+        let shadow = format!(
+            "wes:{}:19345:0:99999:7:::\n",
+            hash(HashKind::Md5, "saltsalt", "hunter2")
+        );
+        machine.write("/shadow", shadow).unwrap();
+        machine.write("/empty.dict", String::new()).unwrap();
+        machine
+            .write("/comments.dict", "# nothing but comments\n\n".into())
+            .unwrap();
+        machine.write("/real.dict", "hunter\n".into()).unwrap();
+        let state = CliState {
+            machine: Arc::new(machine),
+            cwd: "/".into(),
+            ..Default::default()
+        };
+        let err = |line| Crack.prepare(line, &state).err();
+        let none = Some("no words in the wordlists".to_owned());
+        assert_eq!(err("shadow empty.dict"), none);
+        assert_eq!(err("shadow empty.dict comments.dict"), none);
+        assert_eq!(err("shadow empty.dict real.dict"), None);
+    }
+
+    #[test]
+    fn default_wordlists_read_as_the_user() {
+        let machine = Machine::default();
+        machine.mkdir("/root/", false).unwrap();
+        machine.mkdir("/home/wes/", true).unwrap();
+        let shadow = format!(
+            "wes:{}:19345:0:99999:7:::\n",
+            hash(HashKind::Md5, "saltsalt", "hunter2")
+        );
+        machine.write("/home/wes/shadow", shadow).unwrap();
+        machine
+            .write("/root/secret.dict", "hunter2\n".into())
+            .unwrap();
+        machine
+            .write("/home/wes/mine.dict", "hunter\n".into())
+            .unwrap();
+        let state = CliState {
+            machine: Arc::new(machine),
+            host: "fox-pc4".into(),
+            user: "wes".into(),
+            cwd: "/home/wes/".into(),
+            ..Default::default()
+        };
+        let cracker = Crack.prepare("shadow", &state).unwrap();
+        assert_eq!(cracker.words, vec!["hunter"]);
+        let log = state.machine.read(ACCESS_LOG).unwrap().contents;
+        assert!(log.contains("read /home/wes/mine.dict"));
+        assert!(log.contains("read /root/secret.dict"));
+    }
+}
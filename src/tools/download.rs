@@ -0,0 +1,38 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, constants::gameplay::DOWNLOAD_DIR, event::Event};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `download` command, to copy a file from whatever machine the CLI's on back to
+/// the player's own.
+///
+/// `download <path>` puts the file in [`DOWNLOAD_DIR`], in a directory named after the machine it came from.
+pub struct Download;
+
+impl Tool for Download {
+    fn name(&self) -> &'static str {
+        "download"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        AutocompleteType::LocalFile.complete(line, state)
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let file = line.trim();
+        let path = state.path(file);
+        let dir = format!("{}{}/", DOWNLOAD_DIR, state.host);
+        let dest = format!("{}{}", dir, path.rsplit('/').next().unwrap_or(&path));
+        let player = state.world.player();
+        let res = state
+            .read(&path)
+            .and_then(|f| player.mkdir(&dir, true).map(|_| f))
+            .and_then(|f| player.write(&dest, f.contents));
+        let line = match res {
+            Ok(()) => text!["Downloaded {} to "(path), bright_white "{}"(dest), "\n"],
+            Err(e) => text![bright_red "ERROR", ": failed to download {}: {}\n"(file, e)],
+        };
+        Box::new(FixedOutput(state.pid, vec![line]))
+    }
+}
@@ -0,0 +1,51 @@
+use tuig::{io::text, Agent, ControlFlow, Replies};
+
+use crate::{
+    app::CliState,
+    constants::gameplay::{PLAYER_HOST, PLAYER_USER},
+    event::{Event, Pid},
+};
+
+use super::{FixedOutput, Tool};
+
+/// The agent which actually logs the session back into the player's machine
+struct Logout(Pid, String);
+impl Agent<Event> for Logout {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        replies.queue(Event::output(
+            self.0,
+            text!["Connection to {} closed.\n"(self.1)],
+        ));
+        replies.queue(Event::Login {
+            pid: self.0,
+            host: PLAYER_HOST.into(),
+            user: PLAYER_USER.into(),
+        });
+        replies.queue(Event::CommandDone(self.0));
+        ControlFlow::Kill
+    }
+}
+
+/// Implementation of [`Tool`] for the `exit` command, to log out of a remote machine and go back to the player's own.
+pub struct Exit;
+
+impl Tool for Exit {
+    fn name(&self) -> &'static str {
+        "exit"
+    }
+
+    fn autocomplete(&self, _line: &str, _state: &CliState) -> String {
+        String::new()
+    }
+
+    fn run(&self, _line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        if state.host == PLAYER_HOST {
+            Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": already home\n"]],
+            ))
+        } else {
+            Box::new(Logout(state.pid, state.host.clone()))
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::{sync::Arc, time::Duration};
+
+use tuig::{io::text, Agent, ControlFlow, Replies};
+
+use crate::{
+    app::CliState,
+    constants::gameplay::{ALERT_LIMIT, AUTH_LOG, EXPLOIT_MS, LOG_DIR, MAX_SECURITY, PLAYER_ADDR},
+    event::{Event, Pid},
+    net::Addr,
+    rng::Rng,
+    world::World,
+};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for every exploit, i.e. every command that breaks into a machine through a hole in one
+/// of its services.
+///
+/// `<exploit> <host> [port]` attacks a host (or address), on the given port or else the first one running a service
+/// the exploit works against. Every attempt makes noise on the target, whether it works or not, and once a machine's
+/// admins have heard enough they patch the hole. If it does work, the session's logged in as whoever the service runs
+/// as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exploit {
+    /// The exploit's name, which is also its command
+    pub name: &'static str,
+    /// The services it works against, by name
+    pub services: &'static [&'static str],
+    /// How likely it is to work against a vulnerable service on a machine with no security at all
+    pub chance: f32,
+    /// How much noise each attempt makes on the target machine
+    pub noise: u32,
+}
+
+/// Every exploit in the game. Which services have holes each of them can use is decided when machines are generated.
+pub const EXPLOITS: &[Exploit] = &[
+    Exploit {
+        name: "sshock",
+        services: &["ssh"],
+        chance: 0.5,
+        noise: 4,
+    },
+    Exploit {
+        name: "cgibash",
+        services: &["http", "https"],
+        chance: 0.7,
+        noise: 2,
+    },
+    Exploit {
+        name: "smtpwn",
+        services: &["smtp"],
+        chance: 0.6,
+        noise: 3,
+    },
+    Exploit {
+        name: "pgsqueeze",
+        services: &["postgres"],
+        chance: 0.6,
+        noise: 3,
+    },
+    Exploit {
+        name: "eternalred",
+        services: &["smb"],
+        chance: 0.4,
+        noise: 6,
+    },
+    Exploit {
+        name: "keyhole",
+        services: &["rdp"],
+        chance: 0.5,
+        noise: 5,
+    },
+];
+
+/// The agent which runs the exploit, waits for it to go through, and reports how it went
+struct Attempt {
+    pid: Pid,
+    world: Arc<World>,
+    exploit: Exploit,
+    host: String,
+    port: u16,
+    /// What's running on the port, as it announced itself when the exploit connected
+    service: String,
+    rng: Rng,
+}
+
+impl Attempt {
+    /// Roll for whether the exploit worked, make its noise, and deal with the consequences
+    fn resolve(&mut self, replies: &mut Replies<Event>) {
+        let machine = match self.world.host(&self.host) {
+            Some(machine) => machine,
+            None => return,
+        };
+        let vuln = machine.services.get(&self.port).and_then(|s| {
+            s.vulns
+                .iter()
+                .find(|v| v.exploit == self.exploit.name)
+                .cloned()
+        });
+        let odds =
+            self.exploit.chance * (1.0 - machine.security as f32 / (MAX_SECURITY * 2) as f32);
+        let worked = vuln.is_some() && self.rng.chance(odds);
+        // getting in first try is quieter than hammering away at it
+        let noise = if worked {
+            self.exploit.noise / 2
+        } else {
+            self.exploit.noise
+        };
+        let alert = self.world.alert(&self.host, noise);
+        // whatever's listening notices the garbage it's been sent, even if it falls for it
+        if let Some(service) = machine.services.get(&self.port).map(|s| s.name.clone()) {
+            let entry = format!("{}: malformed request from {}", service, PLAYER_ADDR);
+            machine.log(&format!("{}{}.log", LOG_DIR, service), &entry);
+        }
+
+        let line = match &vuln {
+            Some(vuln) if worked => {
+                let entry = format!("session opened for {} from {}", vuln.user, PLAYER_ADDR);
+                machine.log(AUTH_LOG, &entry);
+                replies.queue(Event::Login {
+                    pid: self.pid,
+                    host: self.host.clone(),
+                    user: vuln.user.clone(),
+                });
+                text![bright_green "Success", ": shell on {} as "(self.host), bright_white "{}"(vuln.user), "\n"]
+            }
+            Some(_) => text![bright_red "Failed", ": the exploit didn't take. Try again?\n"],
+            None => {
+                text![bright_red "Failed", ": port {} isn't vulnerable to {}\n"(self.port, self.exploit.name)]
+            }
+        };
+        replies.queue(Event::output(self.pid, line));
+
+        if alert >= ALERT_LIMIT {
+            if let Some(mut service) = machine.services.get_mut(&self.port) {
+                if !service.vulns.is_empty() {
+                    service.vulns.clear();
+                    let warning = text![yellow "WARNING", ": {}'s admins noticed, and patched {}\n"(self.host, service.name)];
+                    replies.queue(Event::output(self.pid, warning));
+                }
+            }
+        }
+    }
+}
+
+impl Agent<Event> for Attempt {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        let header = text!["Running {} against {}:{} ({})...\n"(
+            self.exploit.name,
+            self.host,
+            self.port,
+            self.service
+        )];
+        replies.queue(Event::output(self.pid, header));
+        ControlFlow::sleep_for(Duration::from_millis(EXPLOIT_MS))
+    }
+
+    fn react(&mut self, _event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        self.resolve(replies);
+        replies.queue(Event::CommandDone(self.pid));
+        ControlFlow::Kill
+    }
+}
+
+impl Exploit {
+    /// Work out which machine and port the player's aiming at, given the arguments to the command
+    fn target(&self, line: &str, state: &CliState) -> Result<(String, u16, String), String> {
+        let mut args = line.split_whitespace();
+        let target = args
+            .next()
+            .ok_or(format!("usage: {} <host> [port]", self.name))?;
+        let host = match target.parse::<Addr>() {
+            Ok(addr) => state.world.net.host_at(addr),
+            Err(_) => state.world.net.addr_of(target).map(|_| target.to_owned()),
+        };
+        let host = host.ok_or(format!("unknown host {}", target))?;
+        let machine = state
+            .world
+            .host(&host)
+            .ok_or(format!("{} isn't responding", host))?;
+        let ports = machine.ports();
+        let (port, service) = match args.next() {
+            Some(port) => {
+                let port: u16 = port.parse().map_err(|_| format!("{} isn't a port", port))?;
+                ports
+                    .into_iter()
+                    .find(|(p, _)| *p == port)
+                    .ok_or(format!("nothing's listening on {}:{}", host, port))?
+            }
+            None => ports
+                .into_iter()
+                .find(|(_, s)| self.services.contains(&&*s.name))
+                .ok_or(format!(
+                    "{} isn't running anything {} works on",
+                    host, self.name
+                ))?,
+        };
+        Ok((host, port, format!("{} {}", service.name, service.version)))
+    }
+}
+
+impl Tool for Exploit {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        if line.contains(char::is_whitespace) {
+            String::new()
+        } else {
+            AutocompleteType::Hostname.complete(line, state)
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        match self.target(line, state) {
+            Ok((host, port, service)) => Box::new(Attempt {
+                pid: state.pid,
+                world: state.world.clone(),
+                exploit: *self,
+                host,
+                port,
+                service,
+                rng: Rng::from_time(),
+            }),
+            Err(e) => Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": {}\n"(e)]],
+            )),
+        }
+    }
+}
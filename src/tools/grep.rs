@@ -0,0 +1,55 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, event::Event};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `grep` command, to find the lines in files which contain some text.
+///
+/// `grep <pattern> <file...>` prints every line containing `pattern`, prefixed with which file it's from if there's
+/// more than one.
+pub struct Grep;
+
+impl Tool for Grep {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        // the pattern's first, so only complete files after it
+        match line.rsplit_once(char::is_whitespace) {
+            Some((_, last)) => AutocompleteType::LocalFile.complete(last, state),
+            None => String::new(),
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let mut args = line.split_whitespace();
+        let (pattern, files): (_, Vec<_>) = match args.next() {
+            Some(pattern) => (pattern, args.collect()),
+            None => ("", vec![]),
+        };
+        if files.is_empty() {
+            let usage = text![bright_red "ERROR", ": usage: grep <pattern> <file...>\n"];
+            return Box::new(FixedOutput(state.pid, vec![usage]));
+        }
+        let mut lines = vec![];
+        for file in &files {
+            match state.read(&state.path(file)) {
+                Ok(f) => {
+                    for l in f.contents.lines().filter(|l| l.contains(pattern)) {
+                        if files.len() > 1 {
+                            lines.push(text![bright_black "{}: "(file), "{}\n"(l)]);
+                        } else {
+                            lines.push(text!["{}\n"(l)]);
+                        }
+                    }
+                }
+                Err(e) => {
+                    lines.push(text![bright_red "ERROR", ": failed to read {}: {}\n"(file, e)])
+                }
+            }
+        }
+        Box::new(FixedOutput(state.pid, lines))
+    }
+}
@@ -0,0 +1,50 @@
+use tuig::{io::text, Agent};
+
+use crate::{
+    app::CliState,
+    event::Event,
+    machine::crypt::{parse_shadow, HashKind},
+};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `hash` command, to work out what kind of password hashes something has in it.
+///
+/// `hash <hash>` identifies a single hash; `hash <path>` identifies every hash in a shadow file.
+pub struct Hash;
+
+impl Tool for Hash {
+    fn name(&self) -> &'static str {
+        "hash"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        AutocompleteType::LocalFile.complete(line, state)
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let arg = line.trim();
+        if let Some(kind) = HashKind::identify(arg) {
+            let line = text![bright_white "{}"(kind), " (cracking cost {}x)\n"(kind.cost())];
+            return Box::new(FixedOutput(state.pid, vec![line]));
+        }
+        let file = match state.read(&state.path(arg)) {
+            Ok(file) => file,
+            Err(e) => {
+                let line =
+                    text![bright_red "ERROR", ": {} isn't a hash or a readable file: {}\n"(arg, e)];
+                return Box::new(FixedOutput(state.pid, vec![line]));
+            }
+        };
+        let mut lines = vec![];
+        for (user, hashed) in parse_shadow(&file.contents) {
+            if let Some(kind) = HashKind::identify(&hashed) {
+                lines.push(text!["{:<12}"(user), bright_white "{}"(kind), " (cracking cost {}x)\n"(kind.cost())]);
+            }
+        }
+        if lines.is_empty() {
+            lines.push(text!["No hashes found in {}\n"(arg)]);
+        }
+        Box::new(FixedOutput(state.pid, lines))
+    }
+}
@@ -0,0 +1,48 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, constants::gameplay::LOG_DIR, event::Event};
+
+use super::{autocomplete, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `logs` command, to see what a machine's been keeping track of.
+///
+/// `logs` lists every log on the machine and how long it is; `logs <name>` shows one of them, e.g. `logs auth.log`.
+pub struct Logs;
+
+impl Tool for Logs {
+    fn name(&self) -> &'static str {
+        "logs"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        match state.machine.readdir(LOG_DIR) {
+            Ok(entries) => autocomplete(line, entries.map(|(name, _)| name)),
+            Err(_) => String::new(),
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let name = line.trim();
+        let lines = if name.is_empty() {
+            match state.readdir(LOG_DIR) {
+                Ok(entries) => {
+                    let mut entries: Vec<_> =
+                        entries.filter_map(|(n, e)| Some((n, e.file()?))).collect();
+                    entries.sort_unstable_by(|l, r| l.0.cmp(&r.0));
+                    entries
+                        .into_iter()
+                        .map(|(n, f)| text![bright_white "{:<16}"(n), "{} lines\n"(f.contents.lines().count())])
+                        .collect()
+                }
+                Err(e) => vec![text![bright_red "ERROR", ": {}\n"(e)]],
+            }
+        } else {
+            match state.read(&format!("{}{}", LOG_DIR, name.trim_start_matches(LOG_DIR))) {
+                Ok(f) if f.contents.is_empty() => vec![text![bright_black "(empty)\n"]],
+                Ok(f) => f.contents.lines().map(|l| text!["{}\n"(l)]).collect(),
+                Err(e) => vec![text![bright_red "ERROR", ": failed to read {}: {}\n"(name, e)]],
+            }
+        };
+        Box::new(FixedOutput(state.pid, lines))
+    }
+}
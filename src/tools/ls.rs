@@ -25,7 +25,7 @@ fn entries<'cs>(dir: &str, state: &'cs CliState) -> Result<Vec<(String, Entry)>,
     } else {
         format!("{}{}/", state.cwd, dir)
     };
-    let entries = match state.machine.readdir(&prefix) {
+    let entries = match state.readdir(&prefix) {
         Ok(e) => e,
         Err(e) => return Err(e),
     };
@@ -99,7 +99,10 @@ impl Tool for Ls {
         let args = match COMPLETER.parse(line) {
             Ok(v) => v,
             Err(msg) => {
-                return Box::new(FixedOutput(vec![text![bright_red "ERROR", ": {}\n"(msg)]]))
+                return Box::new(FixedOutput(
+                    state.pid,
+                    vec![text![bright_red "ERROR", ": {}\n"(msg)]],
+                ))
             }
         };
         let dir = args
@@ -109,7 +112,10 @@ impl Tool for Ls {
         let entries = match entries(dir, state) {
             Ok(e) => e,
             Err(e) => {
-                return Box::new(FixedOutput(vec![text![bright_red "ERROR", ": {}\n"(e)]]));
+                return Box::new(FixedOutput(
+                    state.pid,
+                    vec![text![bright_red "ERROR", ": {}\n"(e)]],
+                ));
             }
         };
         let rows = if args.get(&'l').is_some() {
@@ -117,6 +123,6 @@ impl Tool for Ls {
         } else {
             list_short(entries)
         };
-        Box::new(FixedOutput(rows))
+        Box::new(FixedOutput(state.pid, rows))
     }
 }
@@ -25,16 +25,20 @@ impl Tool for Mkdir {
         let args = match COMPLETER.parse(line) {
             Ok(v) => v,
             Err(msg) => {
-                return Box::new(FixedOutput(vec![text![bright_red "ERROR", ": {}\n"(msg)]]))
+                return Box::new(FixedOutput(
+                    state.pid,
+                    vec![text![bright_red "ERROR", ": {}\n"(msg)]],
+                ))
             }
         };
         let with_parents = args.contains_key(&'p');
         let file = match args.get(&'d') {
             Some(path) => path.expect("no value to option with value"),
             None => {
-                return Box::new(FixedOutput(vec![
-                    text![bright_red "ERROR", ": provide a directory to make\n"],
-                ]))
+                return Box::new(FixedOutput(
+                    state.pid,
+                    vec![text![bright_red "ERROR", ": provide a directory to make\n"]],
+                ))
             }
         };
         let path = if file.starts_with('/') {
@@ -42,12 +46,13 @@ impl Tool for Mkdir {
         } else {
             format!("{}{}", state.cwd, file)
         };
-        if let Err(e) = state.machine.mkdir(&path, with_parents) {
-            Box::new(FixedOutput(vec![
-                text![bright_red "ERROR", ": failed to write {}: {}\n"(file, e)],
-            ]))
+        if let Err(e) = state.mkdir(&path, with_parents) {
+            Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": failed to write {}: {}\n"(file, e)]],
+            ))
         } else {
-            Box::new(NoOutput)
+            Box::new(NoOutput(state.pid))
         }
     }
 }
@@ -2,20 +2,57 @@
 
 use std::mem;
 
-use crate::{app::CliState, event::Event};
+use crate::{
+    app::CliState,
+    event::{Event, Pid},
+};
 
 mod args;
 pub use args::{autocomplete, AutocompleteType, BsdArgs};
 
 mod ls;
 pub use ls::Ls;
+mod rm;
+pub use rm::Rm;
 mod touch;
 pub use touch::Touch;
+mod upload;
+pub use upload::Upload;
+mod wipe;
+pub use wipe::Wipe;
 mod mkdir;
 pub use mkdir::Mkdir;
 mod cd;
 pub use cd::Cd;
+mod crack;
+pub use crack::Crack;
+mod download;
+pub use download::Download;
+mod cat;
+pub use cat::Cat;
+mod exit;
+pub use exit::Exit;
+mod exploit;
+pub use exploit::{Exploit, EXPLOITS};
+mod grep;
+pub use grep::Grep;
+mod hash;
+pub use hash::Hash;
+mod logs;
+pub use logs::Logs;
+mod scan;
+pub use scan::Scan;
+mod scrub;
+pub use scrub::Scrub;
+mod send;
+pub use send::SendFile;
+mod ssh;
+pub use ssh::Ssh;
+mod team;
+pub use team::Team;
+mod wallet;
 use tuig::{io::fmt::Text, Agent, ControlFlow, Replies};
+pub use wallet::Wallet;
 
 /// Common interface for all CLI tool.
 pub trait Tool: Send + Sync {
@@ -29,23 +66,24 @@ pub trait Tool: Send + Sync {
     fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event> + 'static>;
 }
 
-/// [`Agent`] implementation that outputs some pre-given text, signals the CLI it's done, and dies.
-struct FixedOutput(Vec<Vec<Text>>);
+/// [`Agent`] implementation that outputs some pre-given text for a command, signals it's done, and dies.
+struct FixedOutput(Pid, Vec<Vec<Text>>);
 
 impl Agent<Event> for FixedOutput {
     fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
-        let lines = mem::take(&mut self.0);
-        replies.queue_all(lines.into_iter().map(|l| Event::CommandOutput(l)));
-        replies.queue(Event::CommandDone);
+        let pid = self.0;
+        let lines = mem::take(&mut self.1);
+        replies.queue_all(lines.into_iter().map(|l| Event::output(pid, l)));
+        replies.queue(Event::CommandDone(pid));
         ControlFlow::Kill
     }
 }
 
-/// An agent which tells the CLI it's done and immediately dies.
-pub struct NoOutput;
+/// An agent which tells the CLI a command's done and immediately dies.
+pub struct NoOutput(pub Pid);
 impl Agent<Event> for NoOutput {
     fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
-        replies.queue(Event::CommandDone);
+        replies.queue(Event::CommandDone(self.0));
         ControlFlow::Kill
     }
 }
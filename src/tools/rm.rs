@@ -0,0 +1,34 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, event::Event};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `rm` command, to delete files and directories.
+pub struct Rm;
+
+impl Tool for Rm {
+    fn name(&self) -> &'static str {
+        "rm"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        if line.ends_with(char::is_whitespace) {
+            AutocompleteType::LocalFile.complete("", state)
+        } else if let Some(last) = line.rsplit(char::is_whitespace).next() {
+            AutocompleteType::LocalFile.complete(last, state)
+        } else {
+            String::new()
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let mut lines = vec![];
+        for file in line.split_whitespace() {
+            if let Err(e) = state.remove(&state.path(file)) {
+                lines.push(text![bright_red "ERROR", ": failed to delete {}: {}\n"(file, e)]);
+            }
+        }
+        Box::new(FixedOutput(state.pid, lines))
+    }
+}
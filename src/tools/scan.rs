@@ -0,0 +1,133 @@
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+use tuig::{io::text, Agent, ControlFlow, Replies};
+
+use crate::{
+    app::CliState,
+    constants::gameplay::{SCAN_EMPTY_MS, SCAN_HOST_MS},
+    event::{Event, Pid},
+    net::Addr,
+    world::World,
+};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// The agent which does the actual scanning, one address at a time, reporting each host as it's found
+struct Scanner {
+    pid: Pid,
+    world: Arc<World>,
+    /// What the player asked to scan, as they put it
+    target: String,
+    /// The addresses still to be scanned
+    todo: VecDeque<Addr>,
+    /// How many addresses there were to begin with
+    total: usize,
+    /// How many hosts have turned up so far
+    up: usize,
+}
+
+impl Scanner {
+    /// How long scanning an address takes
+    fn cost(&self, addr: Addr) -> Duration {
+        if self.world.net.taken(addr) {
+            Duration::from_millis(SCAN_HOST_MS)
+        } else {
+            Duration::from_millis(SCAN_EMPTY_MS)
+        }
+    }
+
+    /// Wait for the next address to be scanned, or wrap up if there aren't any left
+    fn next(&self, replies: &mut Replies<Event>) -> ControlFlow {
+        match self.todo.front() {
+            Some(&addr) => ControlFlow::sleep_for(self.cost(addr)),
+            None => {
+                let summary = text!["{} of {} addresses up\n"(self.up, self.total)];
+                replies.queue(Event::output(self.pid, summary));
+                replies.queue(Event::CommandDone(self.pid));
+                ControlFlow::Kill
+            }
+        }
+    }
+
+    /// Report on the address that's just been scanned, if there's anything there
+    fn report(&mut self, replies: &mut Replies<Event>) {
+        let addr = match self.todo.pop_front() {
+            Some(addr) => addr,
+            None => return,
+        };
+        let host = match self.world.net.host_at(addr) {
+            Some(host) => host,
+            None => return,
+        };
+        self.up += 1;
+        let mut line = text![bright_white "{:<15}"(addr.to_string()), "  {}"(host)];
+        if self.world.net.learn(&host) {
+            line.extend(text![bright_green "  (new)"]);
+            replies.queue(Event::UnlockTab("map".into()));
+        }
+        line.extend(text!["\n"]);
+        let ports = self
+            .world
+            .host(&host)
+            .map(|m| m.ports())
+            .unwrap_or_default();
+        for (port, service) in ports {
+            line.extend(text![
+                "    {:<6}"(port), "{:<10}"(service.name), bright_black "{}\n"(service.version)
+            ]);
+        }
+        replies.queue(Event::output(self.pid, line));
+    }
+}
+
+impl Agent<Event> for Scanner {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        let header = text!["Scanning {} ({} addresses)...\n"(self.target, self.total)];
+        replies.queue(Event::output(self.pid, header));
+        self.next(replies)
+    }
+
+    fn react(&mut self, _event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        // any event after waking up means the address has been scanned
+        self.report(replies);
+        self.next(replies)
+    }
+}
+
+/// Implementation of [`Tool`] for the `scan` command, to find machines on the network and what they're running.
+///
+/// `scan` on its own scans the player's own subnet; `scan <target>` scans a host, an address, or a whole subnet like
+/// `10.0.1.0/24`. Every host found is added to the known hosts.
+pub struct Scan;
+
+impl Tool for Scan {
+    fn name(&self) -> &'static str {
+        "scan"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        AutocompleteType::Hostname.complete(line, state)
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let target = line.trim();
+        match state.world.net.resolve(target) {
+            Ok(addrs) => Box::new(Scanner {
+                pid: state.pid,
+                world: state.world.clone(),
+                target: if target.is_empty() {
+                    "local subnet".into()
+                } else {
+                    target.into()
+                },
+                total: addrs.len(),
+                todo: addrs.into(),
+                up: 0,
+            }),
+            Err(e) => Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": {}\n"(e)]],
+            )),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, event::Event};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `scrub` command, to surgically take lines out of a file, e.g. a log.
+///
+/// `scrub <pattern> <file>` deletes every line containing `pattern`, and nothing else. Editing a file gets logged like
+/// anything else, so whatever's in the access log has to be scrubbed last.
+pub struct Scrub;
+
+impl Tool for Scrub {
+    fn name(&self) -> &'static str {
+        "scrub"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        match line.rsplit_once(char::is_whitespace) {
+            Some((_, last)) => AutocompleteType::LocalFile.complete(last, state),
+            None => String::new(),
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let (pattern, file) = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            &[pattern, file] => (pattern.to_owned(), file.to_owned()),
+            _ => {
+                let usage = text![bright_red "ERROR", ": usage: scrub <pattern> <file>\n"];
+                return Box::new(FixedOutput(state.pid, vec![usage]));
+            }
+        };
+        let path = state.path(&file);
+        let res = state.read(&path).and_then(|f| {
+            let (gone, kept): (Vec<_>, Vec<_>) =
+                f.contents.lines().partition(|l| l.contains(&pattern));
+            let mut contents = kept.join("\n");
+            if !kept.is_empty() {
+                contents.push('\n');
+            }
+            state.write(&path, contents).map(|_| gone.len())
+        });
+        let line = match res {
+            Ok(gone) => text!["Scrubbed {} lines from {}\n"(gone, file)],
+            Err(e) => text![bright_red "ERROR", ": failed to scrub {}: {}\n"(file, e)],
+        };
+        Box::new(FixedOutput(state.pid, vec![line]))
+    }
+}
@@ -0,0 +1,72 @@
+use tuig::{io::text, Agent, ControlFlow, Replies};
+
+use crate::{
+    app::CliState,
+    event::{Attachment, Event, Pid},
+};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// The agent which actually sends the file, once it's been read
+struct Deliver(Pid, Option<(String, Attachment)>);
+impl Agent<Event> for Deliver {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        if let Some((to, file)) = self.1.take() {
+            let line = text!["Sent {} to {}.\n"(file.name, to)];
+            replies.queue(Event::output(self.0, line));
+            replies.queue(Event::PlayerSentFile { to, file });
+        }
+        replies.queue(Event::CommandDone(self.0));
+        ControlFlow::Kill
+    }
+}
+
+/// Implementation of [`Tool`] for the `send` command, to send a file to an NPC over chat.
+///
+/// `send <npc> <path>` sends the file at `path` (on whatever machine the CLI is on) to `npc`.
+///
+/// (Not just `Send`, so it doesn't shadow the marker trait.)
+pub struct SendFile;
+
+impl Tool for SendFile {
+    fn name(&self) -> &'static str {
+        "send"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        // only the file can be completed; there's no telling who the player knows
+        match line.split_once(char::is_whitespace) {
+            Some((_, path)) => AutocompleteType::LocalFile.complete(path.trim_start(), state),
+            None => String::new(),
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let (to, file) = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            &[to, file] => (to.to_owned(), file.to_owned()),
+            _ => {
+                let usage = text![bright_red "ERROR", ": usage: send <npc> <path>\n"];
+                return Box::new(FixedOutput(state.pid, vec![usage]));
+            }
+        };
+        let path = if file.starts_with('/') {
+            file
+        } else {
+            format!("{}{}", state.cwd, file)
+        };
+        match state.read(&path) {
+            Ok(f) => {
+                let name = path.rsplit('/').next().unwrap_or(&path).to_owned();
+                let attachment = Attachment {
+                    name,
+                    contents: f.contents,
+                };
+                Box::new(Deliver(state.pid, Some((to, attachment))))
+            }
+            Err(e) => Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": failed to read {}: {}\n"(path, e)]],
+            )),
+        }
+    }
+}
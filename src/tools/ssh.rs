@@ -0,0 +1,92 @@
+use tuig::{io::text, Agent, ControlFlow, Replies};
+
+use crate::{
+    app::CliState,
+    constants::gameplay::{AUTH_LOG, LOGIN_NOISE, PLAYER_ADDR},
+    event::{Event, Pid},
+};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// The agent which actually logs the session in, once the password's been checked
+struct Connect(Pid, String, String);
+impl Agent<Event> for Connect {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        let line = text!["Logged into {} as "(self.1), bright_white "{}"(self.2), ".\n"];
+        replies.queue(Event::output(self.0, line));
+        replies.queue(Event::Login {
+            pid: self.0,
+            host: self.1.clone(),
+            user: self.2.clone(),
+        });
+        replies.queue(Event::CommandDone(self.0));
+        ControlFlow::Kill
+    }
+}
+
+/// Implementation of [`Tool`] for the `ssh` command, to log into another machine with a username and password.
+///
+/// `ssh <user>@<host> <password>` only works on machines running ssh. Getting the password wrong makes a little noise.
+pub struct Ssh;
+
+impl Ssh {
+    /// Check the login, returning the host and user to log in as if it's good
+    fn login(&self, line: &str, state: &CliState) -> Result<(String, String), String> {
+        let (target, password) = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            &[target, password] => (target.to_owned(), password.to_owned()),
+            _ => return Err("usage: ssh <user>@<host> <password>".into()),
+        };
+        let (user, host) = target
+            .split_once('@')
+            .ok_or(format!("{} isn't <user>@<host>", target))?;
+        let machine = state
+            .world
+            .host(host)
+            .ok_or(format!("unknown host {}", host))?;
+        if !machine.services.iter().any(|s| s.name == "ssh") {
+            return Err(format!("{} isn't running ssh", host));
+        }
+        let right = machine
+            .users
+            .get(user)
+            .map(|u| u.password == password)
+            .unwrap_or(false);
+        let result = if right { "accepted" } else { "failed" };
+        let entry = format!(
+            "sshd: {} password for {} from {}",
+            result, user, PLAYER_ADDR
+        );
+        machine.log(AUTH_LOG, &entry);
+        if right {
+            Ok((host.into(), user.into()))
+        } else {
+            state.world.alert(host, LOGIN_NOISE);
+            Err("permission denied".into())
+        }
+    }
+}
+
+impl Tool for Ssh {
+    fn name(&self) -> &'static str {
+        "ssh"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        match line.split_once('@') {
+            Some((_, host)) if !host.contains(char::is_whitespace) => {
+                AutocompleteType::Hostname.complete(host, state)
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        match self.login(line, state) {
+            Ok((host, user)) => Box::new(Connect(state.pid, host, user)),
+            Err(e) => Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": {}\n"(e)]],
+            )),
+        }
+    }
+}
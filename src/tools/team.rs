@@ -0,0 +1,114 @@
+use std::mem;
+
+use tuig::{io::text, Agent, ControlFlow, Replies};
+
+use crate::{
+    app::CliState,
+    event::{Event, Pid},
+    npc::{Member, Operation},
+    rng::Rng,
+};
+
+use super::{autocomplete, FixedOutput, Tool};
+
+/// The agent which sends a team member off on a job
+struct SendAgent(Pid, Option<Operation>, String);
+impl Agent<Event> for SendAgent {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        let line = text!["{}\n"(mem::take(&mut self.2))];
+        replies.queue(Event::output(self.0, line));
+        if let Some(op) = self.1.take() {
+            replies.spawn_boxed(Box::new(op));
+        }
+        replies.queue(Event::CommandDone(self.0));
+        ControlFlow::Kill
+    }
+}
+
+/// Implementation of [`Tool`] for the `team` command, to list the player's team and send members on jobs.
+///
+/// `team` on its own lists everyone on the team; `team send <member> <job id>` sends one of them on a job.
+pub struct Team;
+
+impl Team {
+    fn list(state: &CliState) -> Box<dyn Agent<Event>> {
+        let mut members: Vec<Member> = state.world.team.iter().map(|m| m.clone()).collect();
+        if members.is_empty() {
+            return Box::new(FixedOutput(
+                state.pid,
+                vec![text!["Nobody's on your team yet.\n"]],
+            ));
+        }
+        members.sort_by(|a, b| a.name.cmp(&b.name));
+        let lines = members
+            .into_iter()
+            .map(|m| {
+                let trust = state.world.relations.trust(&m.name);
+                let status = if m.busy { "on a job" } else { "available" };
+                text![
+                    bright_white "{}"(m.name),
+                    ": {} {}, trust {}, {}\n"(m.skill.name(), m.level, trust, status),
+                ]
+            })
+            .collect();
+        Box::new(FixedOutput(state.pid, lines))
+    }
+
+    fn send(name: &str, job: &str, state: &CliState) -> Result<Box<dyn Agent<Event>>, String> {
+        let job = match state.world.jobs.get(job) {
+            Some(j) => j.clone(),
+            None => return Err(format!("no active job {}", job)),
+        };
+        let member = match state.world.team.get_mut(name) {
+            Some(mut m) if !m.busy => {
+                m.busy = true;
+                m.clone()
+            }
+            Some(_) => return Err(format!("{} is already on a job", name)),
+            None => return Err(format!("{} isn't on your team", name)),
+        };
+        let message = format!("Sent {} on {}.", name, job.title);
+        let op = Operation::new(member, job, state.world.clone(), Rng::from_time());
+        Ok(Box::new(SendAgent(state.pid, Some(op), message)))
+    }
+}
+
+impl Tool for Team {
+    fn name(&self) -> &'static str {
+        "team"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        let words: Vec<_> = line.split_whitespace().collect();
+        let (done, last) = if line.is_empty() || line.ends_with(char::is_whitespace) {
+            (words.len(), "")
+        } else {
+            (words.len() - 1, words[words.len() - 1])
+        };
+        match done {
+            0 => autocomplete(last, ["send"]),
+            1 if words[0] == "send" => {
+                autocomplete(last, state.world.team.iter().map(|m| m.key().clone()))
+            }
+            2 if words[0] == "send" => {
+                autocomplete(last, state.world.jobs.iter().map(|j| j.key().clone()))
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let words: Vec<_> = line.split_whitespace().collect();
+        let res = match words.as_slice() {
+            [] => Ok(Self::list(state)),
+            ["send", name, job] => Self::send(name, job, state),
+            _ => Err("usage: team [send <member> <job id>]".into()),
+        };
+        res.unwrap_or_else(|e| {
+            Box::new(FixedOutput(
+                state.pid,
+                vec![text![bright_red "ERROR", ": {}\n"(e)]],
+            ))
+        })
+    }
+}
@@ -30,10 +30,10 @@ impl Tool for Touch {
             } else {
                 format!("{}{}", state.cwd, file)
             };
-            if let Err(e) = state.machine.write(&path, String::new()) {
+            if let Err(e) = state.write(&path, String::new()) {
                 lines.push(text![bright_red "ERROR", ": failed to write {}: {}\n"(file, e)]);
             }
         }
-        Box::new(FixedOutput(lines))
+        Box::new(FixedOutput(state.pid, lines))
     }
 }
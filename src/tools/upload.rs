@@ -0,0 +1,45 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, event::Event};
+
+use super::{autocomplete, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `upload` command, to copy one of the player's own tools onto whatever machine
+/// the CLI's on, so it can be run there.
+///
+/// Installing things is writing to the machine like anything else, so it shows up in the access log.
+pub struct Upload;
+
+impl Tool for Upload {
+    fn name(&self) -> &'static str {
+        "upload"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        let player = state.world.player();
+        let names: Vec<String> = player.tools.iter().map(|t| t.key().clone()).collect();
+        autocomplete(line, names)
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let name = line.trim();
+        let tool = state
+            .world
+            .player()
+            .tools
+            .get(name)
+            .map(|t| t.value().clone());
+        let line = match tool {
+            _ if state.machine.tools.contains_key(name) => {
+                text![bright_red "ERROR", ": {} is already here\n"(name)]
+            }
+            Some(tool) => {
+                state.log(&format!("install {}", name));
+                state.machine.tools.insert(name.into(), tool);
+                text!["Uploaded ", bright_white "{}"(name), " to {}\n"(state.host)]
+            }
+            None => text![bright_red "ERROR", ": you don't have {}\n"(name)],
+        };
+        Box::new(FixedOutput(state.pid, vec![line]))
+    }
+}
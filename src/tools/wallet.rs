@@ -0,0 +1,38 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, event::Event};
+
+use super::{FixedOutput, Tool};
+
+/// How many of the most recent transfers `wallet` lists
+const SHOWN_TRANSFERS: usize = 10;
+
+/// Implementation of [`Tool`] for the `wallet` command, to check the player's balance and recent transfers.
+pub struct Wallet;
+
+impl Tool for Wallet {
+    fn name(&self) -> &'static str {
+        "wallet"
+    }
+
+    fn autocomplete(&self, _line: &str, _state: &CliState) -> String {
+        String::new()
+    }
+
+    fn run(&self, _line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let wallet = &state.world.wallet;
+        let mut lines = vec![text!["balance: ", bright_white "{}\n"(wallet.balance())]];
+        let history = wallet.history();
+        let skip = history.len().saturating_sub(SHOWN_TRANSFERS);
+        for transfer in history.into_iter().skip(skip).rev() {
+            let amount = format!("{:>8}", transfer.amount.to_string());
+            let line = if transfer.amount.0 < 0 {
+                text!["{}  "(transfer.at), red "{}"(amount), "  {}\n"(transfer.memo)]
+            } else {
+                text!["{}  "(transfer.at), green "{}"(amount), "  {}\n"(transfer.memo)]
+            };
+            lines.push(line);
+        }
+        Box::new(FixedOutput(state.pid, lines))
+    }
+}
@@ -0,0 +1,44 @@
+use tuig::{io::text, Agent};
+
+use crate::{app::CliState, event::Event};
+
+use super::{AutocompleteType, FixedOutput, Tool};
+
+/// Implementation of [`Tool`] for the `wipe` command, to empty files out completely, e.g. logs.
+///
+/// Quick, and thorough, and about as subtle as a brick: an empty log is evidence of its own.
+pub struct Wipe;
+
+impl Tool for Wipe {
+    fn name(&self) -> &'static str {
+        "wipe"
+    }
+
+    fn autocomplete(&self, line: &str, state: &CliState) -> String {
+        if line.ends_with(char::is_whitespace) {
+            AutocompleteType::LocalFile.complete("", state)
+        } else if let Some(last) = line.rsplit(char::is_whitespace).next() {
+            AutocompleteType::LocalFile.complete(last, state)
+        } else {
+            String::new()
+        }
+    }
+
+    fn run(&self, line: &str, state: &CliState) -> Box<dyn Agent<Event>> {
+        let mut lines = vec![];
+        for file in line.split_whitespace() {
+            let path = state.path(file);
+            // only wipe what's already there, rather than leaving empty files all over the place
+            let res = state
+                .read(&path)
+                .and_then(|_| state.write(&path, String::new()));
+            match res {
+                Ok(()) => lines.push(text!["Wiped {}\n"(file)]),
+                Err(e) => {
+                    lines.push(text![bright_red "ERROR", ": failed to wipe {}: {}\n"(file, e)])
+                }
+            }
+        }
+        Box::new(FixedOutput(state.pid, lines))
+    }
+}
@@ -0,0 +1,602 @@
+//! The command-line tutorial `admin` walks new players through in chat.
+//!
+//! It talks like an [`NPC`], but instead of waiting on replies it watches what the player does in their terminal: each
+//! [`Lesson`] asks for something, and is done once the events (and the player's filesystem) say it happened. Anything
+//! else gets a hint. Tabs are unlocked as they come up, and once it's over (or the player skips it) the chat is handed
+//! over to whoever's meant to be there afterwards, normally the regular `admin` NPC.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{atomic::Ordering, Arc},
+    time::Instant,
+};
+
+use tuig::{Agent, ControlFlow, Replies};
+
+use crate::{
+    constants::gameplay::INBOX_DIR,
+    event::{Attachment, Event, Pid},
+    npc::NPC,
+    settings,
+    tools::Cat,
+    world::World,
+};
+
+/// The tabs the tutorial unlocks as it gets to them, or all at once if it's skipped
+const UNLOCKS: &[&str] = &["terminal", "notes", "jobs"];
+
+/// Who the tutorial's given by
+const ADMIN: &str = "admin";
+/// The replies the player can pick when asked whether they want the tutorial
+const START_OPTIONS: [&str; 2] = ["teach me", "skip it"];
+/// How long admin pauses before typing each message, in milliseconds
+const PAUSE: usize = 250;
+/// The cheat sheet admin sends over, for the player to practice `cat` on
+const README: &str = "\
+ls           list what's in the directory you're in
+cd <dir>     go into a directory (cd .. goes back up, cd / goes to the top)
+mkdir <dir>  make a directory
+touch <file> make an empty file
+cat <file>   print out a file
+";
+
+/// What the player has to do to finish a lesson
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Goal {
+    /// Say whether they want the tutorial at all
+    Start,
+    /// Run `ls`
+    List,
+    /// Make a directory with `mkdir`
+    MakeDir,
+    /// `cd` into the directory they made
+    EnterDir,
+    /// Make a file with `touch`
+    MakeFile,
+    /// Read the cheat sheet with `cat`
+    ReadFile,
+    /// Pin something to their notes
+    Pin,
+    /// Nothing; the tutorial's over
+    Done,
+}
+
+impl Goal {
+    /// The command that does it, if it's done with a command
+    fn command(self) -> Option<&'static str> {
+        match self {
+            Goal::List => Some("ls"),
+            Goal::MakeDir => Some("mkdir"),
+            Goal::EnterDir => Some("cd"),
+            Goal::MakeFile => Some("touch"),
+            Goal::ReadFile => Some("cat"),
+            Goal::Start | Goal::Pin | Goal::Done => None,
+        }
+    }
+}
+
+/// One step of the tutorial. `{dir}`, `{readme}` and `{pin}` in messages are filled in as they're sent.
+struct Lesson {
+    goal: Goal,
+    /// What admin says to start the lesson
+    say: &'static [&'static str],
+    /// What admin says when the player gets it wrong
+    hint: &'static str,
+}
+
+const LESSONS: &[Lesson] = &[
+    Lesson {
+        goal: Goal::Start,
+        say: &[
+            "hey. before you get going:",
+            "everything here happens on the command line. want a quick crash course?",
+        ],
+        hint: "so, crash course?",
+    },
+    Lesson {
+        goal: Goal::List,
+        say: &[
+            "ok. you've just got a terminal tab, up top. press F2 to get to it, and F1 to get back here.",
+            "type `ls` and press enter. it lists everything in the directory you're in.",
+        ],
+        hint: "just `ls`, then enter. in the terminal, not here.",
+    },
+    Lesson {
+        goal: Goal::MakeDir,
+        say: &[
+            "nice. directories are just folders, and you can make your own.",
+            "try `mkdir loot`. or call it whatever, as long as it's one word.",
+        ],
+        hint: "`mkdir`, a space, then a name. like `mkdir loot`.",
+    },
+    Lesson {
+        goal: Goal::EnterDir,
+        say: &[
+            "now go into it with `cd {dir}`. cd is short for change directory.",
+            "anything you do after that happens in there, until you cd somewhere else.",
+        ],
+        hint: "`cd {dir}`. and if you get lost, `cd /` takes you back to the top.",
+    },
+    Lesson {
+        goal: Goal::MakeFile,
+        say: &["`touch` makes empty files. make one in here: `touch stuff.txt`"],
+        hint: "`touch`, a space, then a file name. make sure you're in {dir} first.",
+    },
+    Lesson {
+        goal: Goal::ReadFile,
+        say: &[
+            "empty files aren't much use though. here's cat. it prints files out.",
+            "I sent you a cheat sheet. read it with `cat {readme}`",
+        ],
+        hint: "`cat {readme}`. the whole path, slashes and all.",
+    },
+    Lesson {
+        goal: Goal::Pin,
+        say: &[
+            "one last thing. you just got a notes tab, up top.",
+            "press {pin} in the terminal and whatever the last command printed gets pinned there.",
+            "pin that cheat sheet. you'll want it.",
+        ],
+        hint: "go to the terminal and press {pin}. it pins whatever the last command printed.",
+    },
+    Lesson {
+        goal: Goal::Done,
+        say: &[
+            "that's the basics. the rest is just more commands.",
+            "oh, and you've got a jobs tab now too. it keeps track of work people give you.",
+        ],
+        hint: "",
+    },
+];
+
+/// The guided tutorial, as an agent. See the [module docs](self).
+pub struct Tutorial {
+    world: Arc<World>,
+    /// Which of [`LESSONS`] the player's on
+    lesson: usize,
+    /// Messages admin still has to send, in order
+    outbox: VecDeque<String>,
+    /// When admin will be done typing the first message in the outbox, if it's typing
+    typing: Option<Instant>,
+    /// Each terminal session's working directory, as far as the tutorial's seen
+    cwds: BTreeMap<usize, String>,
+    /// The command the player's running, to check once it's done
+    running: Option<(Pid, String)>,
+    /// The directory the player made in [`Goal::MakeDir`]
+    dir: String,
+    /// Which of the [`UNLOCKS`] have been unlocked so far
+    unlocked: Vec<&'static str>,
+    /// Who takes over the chat once the tutorial's over
+    then: Option<Box<dyn Agent<Event>>>,
+}
+
+impl Tutorial {
+    /// Make a tutorial for the player in this world, handing over to `then` afterwards
+    pub fn new(world: Arc<World>, then: impl Agent<Event> + 'static) -> Self {
+        Self {
+            world,
+            lesson: 0,
+            outbox: VecDeque::new(),
+            typing: None,
+            cwds: BTreeMap::new(),
+            running: None,
+            dir: String::new(),
+            unlocked: vec![],
+            then: Some(Box::new(then)),
+        }
+    }
+
+    /// Pick up from a lesson the player got to in an earlier session, instead of the start.
+    ///
+    /// Their files aren't saved, so lessons about something they made in an earlier one start back at that one.
+    pub fn resume(mut self, lesson: usize) -> Self {
+        self.lesson = lesson.min(LESSONS.len() - 1);
+        let back_to = match self.goal() {
+            Goal::EnterDir | Goal::MakeFile => Goal::MakeDir,
+            Goal::Pin => Goal::ReadFile,
+            _ => return self,
+        };
+        self.lesson = LESSONS.iter().position(|l| l.goal == back_to).unwrap_or(0);
+        self
+    }
+
+    /// Whether the player's done with the tutorial (or skipped it), as of the given lesson
+    pub fn finished(lesson: usize) -> bool {
+        lesson >= LESSONS.len() - 1
+    }
+
+    fn goal(&self) -> Goal {
+        LESSONS[self.lesson].goal
+    }
+
+    /// Where the cheat sheet ends up
+    fn readme_path() -> String {
+        format!("{}{}/readme.txt", INBOX_DIR, ADMIN)
+    }
+
+    /// Queue a message for admin to send, filling in the blanks
+    fn say(&mut self, text: &str) {
+        let pin = settings::key_name(settings::current().pin);
+        let text = text
+            .replace("{dir}", &self.dir)
+            .replace("{readme}", &Self::readme_path())
+            .replace("{pin}", &pin);
+        self.outbox.push_back(text);
+    }
+
+    /// Give the player a hint for the current lesson
+    fn hint(&mut self) {
+        let hint = LESSONS[self.lesson].hint;
+        self.say(hint);
+    }
+
+    /// Unlock one of the player's tabs, if the tutorial hasn't already
+    fn unlock(&mut self, tab: &'static str, replies: &mut Replies<Event>) {
+        if !self.unlocked.contains(&tab) {
+            self.unlocked.push(tab);
+            replies.queue(Event::UnlockTab(tab.into()));
+        }
+    }
+
+    /// Start a lesson, doing whatever it needs doing first
+    fn begin(&mut self, lesson: usize, replies: &mut Replies<Event>) {
+        self.lesson = lesson;
+        self.world.tutorial.store(lesson, Ordering::Relaxed);
+        self.running = None;
+        match self.goal() {
+            Goal::ReadFile => {
+                replies.queue(Event::install(Cat));
+                let player = self.world.player();
+                let file = Attachment {
+                    name: "readme.txt".into(),
+                    contents: README.into(),
+                };
+                let path = Self::readme_path();
+                let saved = player
+                    .mkdir(&format!("{}{}/", INBOX_DIR, ADMIN), true)
+                    .and_then(|_| player.write(&path, file.contents.clone()));
+                if saved.is_ok() {
+                    replies.queue(Event::NPCSentFile {
+                        from: ADMIN.into(),
+                        file,
+                        path,
+                    });
+                }
+            }
+            Goal::List => self.unlock("terminal", replies),
+            Goal::Pin => self.unlock("notes", replies),
+            Goal::Done => {
+                for tab in UNLOCKS {
+                    self.unlock(tab, replies);
+                }
+            }
+            _ => (),
+        }
+        for line in LESSONS[lesson].say {
+            self.say(line);
+        }
+    }
+
+    /// Skip the rest of the tutorial
+    fn skip(&mut self, replies: &mut Replies<Event>) {
+        self.outbox.clear();
+        self.typing = None;
+        self.begin(LESSONS.len() - 1, replies);
+        // the wrap-up only makes sense after the rest of it
+        self.outbox.clear();
+        self.say("no worries. ask me if you ever get stuck.");
+    }
+
+    /// A terminal session's working directory
+    fn cwd(&self, session: usize) -> &str {
+        self.cwds.get(&session).map(|d| d.as_str()).unwrap_or("/")
+    }
+
+    /// Resolve a path the player typed in some session, relative to its working directory
+    fn resolve(&self, session: usize, path: &str) -> String {
+        if path.starts_with('/') {
+            path.into()
+        } else {
+            format!("{}{}", self.cwd(session), path)
+        }
+    }
+
+    /// Whether a command the player's just finished running did what the current lesson asked
+    fn check(&mut self, session: usize, line: &str) -> bool {
+        let mut words = line.split_whitespace();
+        let cmd = words.next().unwrap_or_default();
+        let arg = words.next().unwrap_or_default();
+        if Some(cmd) != self.goal().command() {
+            return false;
+        }
+        let player = self.world.player();
+        match self.goal() {
+            Goal::List => true,
+            Goal::MakeDir => {
+                let is_dir = player
+                    .entry(&self.resolve(session, arg))
+                    .map(|e| e.is_dir());
+                let made = !arg.is_empty() && is_dir.unwrap_or(false);
+                if made {
+                    let name = arg.trim_end_matches('/').rsplit('/').next();
+                    self.dir = name.unwrap_or_default().into();
+                }
+                made
+            }
+            Goal::EnterDir => self
+                .cwd(session)
+                .trim_end_matches('/')
+                .ends_with(&format!("/{}", self.dir)),
+            Goal::MakeFile => {
+                let is_file = player
+                    .entry(&self.resolve(session, arg))
+                    .map(|e| e.is_file());
+                !arg.is_empty() && is_file.unwrap_or(false)
+            }
+            Goal::ReadFile => self.resolve(session, arg) == Self::readme_path(),
+            Goal::Start | Goal::Pin | Goal::Done => false,
+        }
+    }
+
+    /// Send whatever's in the outbox, typing each message first like an NPC would
+    fn send(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        while let Some(text) = self.outbox.front() {
+            match self.typing {
+                None => {
+                    let wait = NPC::pace(text, PAUSE);
+                    if !wait.is_zero() {
+                        self.typing = Some(Instant::now() + wait);
+                        replies.queue(Event::NPCTyping { from: ADMIN.into() });
+                        return ControlFlow::Continue;
+                    }
+                }
+                Some(done) if Instant::now() < done => return ControlFlow::Continue,
+                Some(_) => (),
+            }
+            self.typing = None;
+            let text = self.outbox.pop_front().unwrap_or_default();
+            let options = if self.outbox.is_empty() && self.goal() == Goal::Start {
+                START_OPTIONS.iter().map(|&o| o.to_owned()).collect()
+            } else {
+                vec![]
+            };
+            replies.queue(Event::NPCChatMessage {
+                from: ADMIN.into(),
+                text,
+                options,
+            });
+        }
+        if self.goal() == Goal::Done {
+            if let Some(then) = self.then.take() {
+                replies.spawn_boxed(then);
+            }
+            return ControlFlow::Kill;
+        }
+        ControlFlow::Continue
+    }
+}
+
+impl Agent<Event> for Tutorial {
+    fn start(&mut self, replies: &mut Replies<Event>) -> ControlFlow {
+        self.begin(self.lesson, replies);
+        self.send(replies)
+    }
+
+    fn react(&mut self, event: &Event, replies: &mut Replies<Event>) -> ControlFlow {
+        match event {
+            Event::PlayerChatMessage { to, text } if to == ADMIN => {
+                // only a message that's asking to skip and nothing else, so questions that happen to mention it don't
+                let text = text.trim().trim_end_matches(['.', '!']).to_lowercase();
+                let skip = text == "skip" || text == START_OPTIONS[1];
+                if skip || (self.goal() == Goal::Start && text.starts_with("no")) {
+                    self.skip(replies);
+                } else if self.goal() == Goal::Start {
+                    self.begin(self.lesson + 1, replies);
+                } else {
+                    self.hint();
+                }
+            }
+            Event::CommandEntered { pid, line } => {
+                let cmd = line.split_whitespace().next();
+                if self.goal().command().is_none() {
+                    // the tutorial isn't asking for a command right now, so anything goes
+                } else if cmd == self.goal().command() {
+                    self.running = Some((*pid, line.clone()));
+                } else {
+                    self.hint();
+                }
+            }
+            Event::ChangeDir { pid, dir } => {
+                self.cwds.insert(pid.session, dir.clone());
+            }
+            Event::CommandDone(done) if self.running.as_ref().map(|r| r.0) == Some(*done) => {
+                if let Some((pid, line)) = self.running.take() {
+                    if self.check(pid.session, &line) {
+                        self.begin(self.lesson + 1, replies);
+                    } else {
+                        self.hint();
+                    }
+                }
+            }
+            Event::PinNote(_) if self.goal() == Goal::Pin => self.begin(self.lesson + 1, replies),
+            _ => (),
+        }
+        self.send(replies)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{machine::Machine, npc::npc};
+
+    use super::*;
+
+    fn tutorial() -> Tutorial {
+        let world = Arc::new(World::new(Arc::new(Machine::default())));
+        Tutorial::new(world, npc!("admin", [say "hi": 0, ask,]))
+    }
+
+    /// Have the tutorial react to some events, as if the player waited for admin to finish typing, returning what it
+    /// did (except typing) and whether it's still going
+    fn react(tut: &mut Tutorial, events: &[Event]) -> (Vec<Event>, bool) {
+        let mut replies = Replies::default();
+        let mut flow = ControlFlow::Continue;
+        for event in events {
+            flow = tut.react(event, &mut replies);
+        }
+        while tut.typing.is_some() {
+            tut.typing = Some(Instant::now());
+            flow = tut.react(&Event::Tick, &mut replies);
+        }
+        let said = replies
+            ._messages()
+            .iter()
+            .filter(|e| !matches!(e, Event::NPCTyping { .. }))
+            .cloned()
+            .collect();
+        (said, matches!(flow, ControlFlow::Continue))
+    }
+
+    /// Run a command in the tutorial's world, as far as it can tell
+    fn run(tut: &mut Tutorial, line: &str) -> Vec<Event> {
+        let events = [
+            Event::CommandEntered {
+                pid: Pid::default(),
+                line: line.into(),
+            },
+            Event::CommandDone(Pid::default()),
+        ];
+        react(tut, &events).0
+    }
+
+    fn unlocks(events: &[Event]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                Event::UnlockTab(tab) => Some(tab.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn texts(events: &[Event]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                Event::NPCChatMessage { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn skipping_unlocks_everything() {
+        let mut tut = tutorial();
+        let mut replies = Replies::default();
+        tut.start(&mut replies);
+        let (said, running) = react(&mut tut, &[Event::player_chat("admin", "skip it")]);
+        assert!(!running);
+        assert_eq!(unlocks(&said), UNLOCKS);
+        assert_eq!(
+            texts(&said),
+            vec!["no worries. ask me if you ever get stuck."]
+        );
+    }
+
+    #[test]
+    fn resuming_redoes_lost_work() {
+        let lesson = |goal| LESSONS.iter().position(|l| l.goal == goal).unwrap();
+        // the directory they made is gone, so they need to make it again before going into it
+        let mut tut = tutorial().resume(lesson(Goal::EnterDir));
+        assert_eq!(tut.goal(), Goal::MakeDir);
+        tut.start(&mut Replies::default());
+        assert_eq!(
+            tut.world.tutorial.load(Ordering::Relaxed),
+            lesson(Goal::MakeDir)
+        );
+        assert_eq!(tutorial().resume(lesson(Goal::List)).goal(), Goal::List);
+        assert_eq!(tutorial().resume(lesson(Goal::Pin)).goal(), Goal::ReadFile);
+        assert!(Tutorial::finished(tutorial().resume(99).lesson));
+    }
+
+    #[test]
+    fn mentioning_skip_isnt_skipping() {
+        let mut tut = tutorial();
+        let mut replies = Replies::default();
+        tut.start(&mut replies);
+        react(&mut tut, &[Event::player_chat("admin", "teach me")]);
+        let question = Event::player_chat("admin", "can ls skip hidden files?");
+        let (said, running) = react(&mut tut, &[question]);
+        assert!(running);
+        assert_eq!(texts(&said), vec![LESSONS[1].hint]);
+        assert_eq!(tut.goal(), Goal::List);
+        let (_, running) = react(&mut tut, &[Event::player_chat("admin", "Skip.")]);
+        assert!(!running);
+    }
+
+    #[test]
+    fn lessons_wait_for_the_right_command() {
+        let mut tut = tutorial();
+        let mut replies = Replies::default();
+        tut.start(&mut replies);
+        let said = react(&mut tut, &[Event::player_chat("admin", "teach me")]).0;
+        assert_eq!(tut.goal(), Goal::List);
+        assert_eq!(unlocks(&said), vec!["terminal"]);
+
+        // the wrong command just gets a hint
+        let said = run(&mut tut, "cd /");
+        assert_eq!(texts(&said), vec![LESSONS[1].hint]);
+        assert_eq!(tut.goal(), Goal::List);
+        run(&mut tut, "ls");
+        assert_eq!(tut.goal(), Goal::MakeDir);
+
+        // mkdir has to actually make the directory
+        run(&mut tut, "mkdir loot");
+        assert_eq!(tut.goal(), Goal::MakeDir);
+        tut.world.player().mkdir("/loot/", false).unwrap();
+        run(&mut tut, "mkdir loot");
+        assert_eq!(tut.goal(), Goal::EnterDir);
+
+        let said = react(
+            &mut tut,
+            &[
+                Event::CommandEntered {
+                    pid: Pid::default(),
+                    line: "cd loot".into(),
+                },
+                Event::cd(Pid::default(), "/loot/"),
+                Event::CommandDone(Pid::default()),
+            ],
+        )
+        .0;
+        assert_eq!(tut.goal(), Goal::MakeFile);
+        assert!(texts(&said)[0].starts_with("`touch`"));
+    }
+
+    #[test]
+    fn reading_and_pinning_finish_it() {
+        let mut tut = tutorial();
+        tut.cwds.insert(0, "/loot/".into());
+        tut.dir = "loot".into();
+        let mut replies = Replies::default();
+        tut.begin(4, &mut replies);
+        tut.world.player().mkdir("/loot/", false).unwrap();
+        tut.world
+            .player()
+            .write("/loot/stuff.txt", "".into())
+            .unwrap();
+
+        let said = run(&mut tut, "touch stuff.txt");
+        assert_eq!(tut.goal(), Goal::ReadFile);
+        assert!(said.iter().any(|e| matches!(e, Event::InstallTool(_))));
+        let readme = tut.world.player().read("/inbox/admin/readme.txt").unwrap();
+        assert_eq!(readme.contents, README);
+
+        let said = run(&mut tut, "cat /inbox/admin/readme.txt");
+        assert_eq!(tut.goal(), Goal::Pin);
+        assert_eq!(unlocks(&said), vec!["notes"]);
+        let (said, running) = react(&mut tut, &[Event::PinNote("ls ...".into())]);
+        assert!(!running);
+        assert_eq!(unlocks(&said), vec!["terminal", "jobs"]);
+    }
+}
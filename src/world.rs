@@ -0,0 +1,175 @@
+//! The in-game world outside of the player's UI, shared between the game and any agents that need to poke at it.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use dashmap::{DashMap, DashSet};
+
+use crate::{
+    constants::gameplay::{
+        CIVILIAN_HOSTS, CORP_HOSTS, CORP_NET, HOME_SUBNET, PLAYER_ADDR, PLAYER_HOST,
+    },
+    economy::Wallet,
+    forensics::Snapshot,
+    jobs::Job,
+    machine::{gen, Machine},
+    net::{Addr, Network, Subnet},
+    npc::{Member, Relations},
+    rng::Rng,
+};
+
+/// Everything in the CyberZone that more than one system needs to see.
+///
+/// Everything in here uses interior mutability, so it can be freely shared behind an `Arc`.
+#[derive(Default)]
+pub struct World {
+    /// Every machine in the world, by hostname. The player's is at [`PLAYER_HOST`].
+    pub hosts: DashMap<String, Arc<Machine>>,
+    /// How every NPC and faction feels about the player
+    pub relations: Relations,
+    /// The NPCs who've joined the player's team, by name
+    pub team: DashMap<String, Member>,
+    /// The jobs the player is currently working on, by ID. Kept up to date by the [`crate::jobs::JobBoard`].
+    pub jobs: DashMap<String, Job>,
+    /// The player's wallet, also shown in the UI
+    pub wallet: Arc<Wallet>,
+    /// The contents of every stolen file the player's already sold, so nothing can be sold twice
+    pub fenced: DashSet<String>,
+    /// Where every machine is on the network, and which ones the player's found
+    pub net: Network,
+    /// How much noise the player's made on each machine, by hostname, e.g. by exploiting it
+    pub alerts: DashMap<String, u32>,
+    /// What each corp machine's logs looked like at its last audit, by hostname. Kept up to date by the
+    /// [`crate::forensics::Forensics`].
+    pub audits: DashMap<String, Snapshot>,
+    /// How many times the player's upgraded their machine's hardware, up to
+    /// [`MAX_HARDWARE`](crate::constants::gameplay::MAX_HARDWARE)
+    pub hardware: AtomicU32,
+    /// The seed the world was last [populated](World::populate) from, so a save can regenerate the same machines
+    pub seed: AtomicU64,
+    /// Which lesson of the tutorial the player's on, kept up to date by the [`crate::tutorial::Tutorial`] so a save can
+    /// pick it back up
+    pub tutorial: AtomicUsize,
+}
+
+impl World {
+    /// Create a world with nothing in it but the player's machine.
+    pub fn new(player: Arc<Machine>) -> Self {
+        let world = Self::default();
+        world.hosts.insert(PLAYER_HOST.into(), player);
+        world.net.add(PLAYER_HOST, PLAYER_ADDR);
+        world.net.learn(PLAYER_HOST);
+        world
+    }
+
+    /// Fill the world with generated corp and civilian machines, and put them on the network. The same seed always
+    /// generates the same machines at the same addresses.
+    pub fn populate(&self, seed: u64) {
+        self.seed.store(seed, Ordering::Relaxed);
+        let mut rng = Rng::seeded(seed);
+        let machines = gen::machines(CORP_HOSTS, CIVILIAN_HOSTS, &mut rng);
+        let mut corps: HashMap<String, Subnet> = HashMap::new();
+        for (i, (host, machine)) in machines.into_iter().enumerate() {
+            if host == PLAYER_HOST {
+                continue;
+            }
+            // corps come first, and each corp's machines share a subnet of their own
+            let subnet = if i < CORP_HOSTS {
+                let org = host.split('-').next().unwrap_or_default().to_owned();
+                let taken: Vec<Subnet> = corps.values().copied().collect();
+                *corps.entry(org).or_insert_with(|| loop {
+                    let [a, b] = CORP_NET;
+                    let subnet = Subnet([a, b, rng.below(256) as u8]);
+                    if !taken.contains(&subnet) {
+                        break subnet;
+                    }
+                })
+            } else {
+                HOME_SUBNET
+            };
+            self.net.add(&host, self.free_addr(subnet, &mut rng));
+            self.hosts.insert(host, Arc::new(machine));
+        }
+    }
+
+    /// Pick a random address on a subnet that nothing's using yet
+    fn free_addr(&self, subnet: Subnet, rng: &mut Rng) -> Addr {
+        loop {
+            let addr = subnet.addr(rng.between(2, 254) as u8);
+            if !self.net.taken(addr) {
+                return addr;
+            }
+        }
+    }
+
+    /// Make some noise on a machine, returning how much has been made there in total.
+    pub fn alert(&self, host: &str, noise: u32) -> u32 {
+        let mut level = self.alerts.entry(host.into()).or_insert(0);
+        *level += noise;
+        *level
+    }
+
+    /// Get a machine by its hostname, if there is one.
+    pub fn host(&self, name: &str) -> Option<Arc<Machine>> {
+        self.hosts.get(name).map(|m| m.value().clone())
+    }
+
+    /// Get the player's machine.
+    ///
+    /// Panics if there isn't one, because that means the world was set up wrong.
+    pub fn player(&self) -> Arc<Machine> {
+        self.host(PLAYER_HOST).expect("world has no player machine")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn populate_is_reproducible() {
+        let w1 = World::new(Default::default());
+        let w2 = World::new(Default::default());
+        w1.populate(99);
+        w2.populate(99);
+        assert_eq!(w1.hosts.len(), 1 + CORP_HOSTS + CIVILIAN_HOSTS);
+        for host in w1.hosts.iter() {
+            assert!(w2.hosts.contains_key(host.key()));
+            assert_eq!(w1.net.addr_of(host.key()), w2.net.addr_of(host.key()));
+        }
+    }
+
+    #[test]
+    fn everyone_gets_an_address() {
+        let world = World::new(Default::default());
+        world.populate(7);
+        let mut addrs = vec![];
+        for host in world.hosts.iter() {
+            let addr = world.net.addr_of(host.key()).expect("host has no address");
+            assert_eq!(
+                world.net.host_at(addr).as_deref(),
+                Some(host.key().as_str())
+            );
+            addrs.push(addr);
+        }
+        addrs.sort();
+        addrs.dedup();
+        assert_eq!(addrs.len(), world.hosts.len());
+        // only the player's machine is known to start with
+        assert_eq!(world.net.known.len(), 1);
+        assert!(world.net.known.contains(PLAYER_HOST));
+    }
+
+    #[test]
+    fn noise_adds_up_per_host() {
+        let world = World::default();
+        assert_eq!(world.alert("hexacorp-db04", 3), 3);
+        assert_eq!(world.alert("hexacorp-db04", 4), 7);
+        assert_eq!(world.alert("fox-pc4", 1), 1);
+    }
+}